@@ -1,3 +1,22 @@
 fn main() {
+  emit_dependency_versions();
   tauri_build::build()
-}
\ No newline at end of file
+}
+
+// 从 Cargo.lock 读取关键依赖实际解析到的版本，供 get_diagnostics 使用
+fn emit_dependency_versions() {
+  println!("cargo:rerun-if-changed=Cargo.lock");
+  let lock = std::fs::read_to_string("Cargo.lock").unwrap_or_default();
+  let lines: Vec<&str> = lock.lines().collect();
+
+  for name in ["tauri", "anitomy", "reqwest"] {
+    let package_line = format!("name = \"{}\"", name);
+    let version = lines.iter()
+      .position(|line| line.trim() == package_line)
+      .and_then(|index| lines.get(index + 1))
+      .and_then(|line| line.trim().strip_prefix("version = "))
+      .map(|version| version.trim_matches('"').to_string())
+      .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=LOCKED_VERSION_{}={}", name.to_uppercase(), version);
+  }
+}
@@ -0,0 +1,711 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use lazy_static::lazy_static;
+use tauri::{command, State};
+use tracing::{info, warn};
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::file_operations::{
+    FileError, FileKind, ProcessResult, scan_directory_internal, sanitize_filename, ensure_output_dir, incomplete_source_reason,
+    link_sidecars_for_batch, sanitize_path, with_default_extension, place_subtitle_target, resolve_file_library_root, generate_season_folder_name, write_batch_manifest,
+};
+use crate::commands::history::record_operation;
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_BATCH_AUTO_MATCH, SOURCE_AUTO_ORGANIZE_FOLDER, SOURCE_FIND_UNIDENTIFIED, SOURCE_EXPORT_METADATA};
+use crate::commands::manifest::build_manifest_entry;
+use crate::commands::metadata::{
+    AniListCache, AniListResponse, ParsedFilename, TitleField, best_title, infer_seasons, normalize_query, parse_path_internal, search_anilist_batch_cached, get_anilist_episode_titles,
+};
+use crate::commands::providers::{UnifiedMatch, rank_anilist_candidates};
+use crate::commands::template::{TemplateContext, render_template, title_override, with_title_override};
+use crate::commands::text_output::{UTF8_BOM, line_ending};
+use crate::commands::undo::BatchJournal;
+use crate::commands::errors::AppError;
+
+// 未匹配文件夹中记录最佳猜测的文件名
+pub const UNMATCHED_RECORD_FILE_NAME: &str = "unmatched.json";
+
+lazy_static! {
+    // 解析出的标题 -> 最佳候选，同一会话中重复匹配同一部动漫时不再请求
+    static ref MATCH_CACHE: RwLock<HashMap<String, Option<(AniListResponse, UnifiedMatch)>>> = RwLock::new(HashMap::new());
+}
+
+// 单个文件的匹配决定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchDecision {
+    pub source: String,
+    pub target: Option<String>,
+    pub parsed_title: String,
+    pub best_guess: Option<UnifiedMatch>,
+    pub auto_applied: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoMatchResult {
+    pub result: ProcessResult,
+    pub auto_count: usize,
+    pub manual_count: usize,
+    pub decisions: Vec<MatchDecision>,
+}
+
+// 无人值守的批量处理：置信度达到阈值的文件按元数据自动命名，
+// 其余文件链接到未匹配文件夹，并记录最佳猜测供之后手动确认
+#[command]
+pub async fn batch_auto_match(
+    files: Vec<String>,
+    output_dir: String,
+    threshold: Option<f32>,
+    scanned_sizes: Option<HashMap<String, u64>>,
+    anilist_cache: State<'_, AniListCache>,
+    log_store: State<'_, LogStore>
+) -> Result<AutoMatchResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_BATCH_AUTO_MATCH);
+    let config = read_app_config();
+    let threshold = threshold.unwrap_or(config.auto_match_threshold);
+    
+    if !config.anilist_enabled {
+        return Err("自动匹配需要启用 AniList 数据源".into());
+    }
+    
+    let options = AutoMatchOptions {
+        scanned_sizes: scanned_sizes.as_ref(),
+        ..AutoMatchOptions::default()
+    };
+    run_auto_match(&files, &output_dir, threshold, options, &anilist_cache, &log, &config).await.map_err(AppError::from)
+}
+
+// 一键整理文件夹：扫描、解析、推断整个文件夹的标题和季度、匹配元数据、计算目标路径并执行
+// dry_run 为 true 时只返回预览；置信度不足或缺少集数的文件链接到未匹配文件夹，各步骤的问题记录在结果的跳过文件和警告中
+#[command]
+pub async fn auto_organize_folder(
+    folder: String,
+    output_dir: Option<String>,
+    threshold: Option<f32>,
+    dry_run: Option<bool>,
+    anilist_cache: State<'_, AniListCache>,
+    log_store: State<'_, LogStore>
+) -> Result<AutoMatchResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_AUTO_ORGANIZE_FOLDER);
+    let config = read_app_config();
+    let threshold = threshold.unwrap_or(config.auto_match_threshold);
+    let output_dir = output_dir.unwrap_or_else(|| config.output_directory.clone());
+    
+    if !config.anilist_enabled {
+        return Err("自动整理需要启用 AniList 数据源".into());
+    }
+    if !Path::new(&folder).is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", folder)));
+    }
+    
+    info!("开始自动整理文件夹: {}", folder);
+    log.info(format!("开始自动整理文件夹: {} -> {}", folder, output_dir));
+    
+    // 附属文件由链接时的 link_sidecars 处理，这里只整理视频和字幕
+    let (scanned, skipped) = scan_directory_internal(&folder, &config);
+    let mut files: Vec<String> = scanned.into_iter()
+        .filter(|file| matches!(file.kind, FileKind::Video | FileKind::Subtitle))
+        .map(|file| file.path)
+        .collect();
+    files.sort();
+    
+    let mut warnings = Vec::new();
+    let mut parsed_files: Vec<(String, ParsedFilename)> = Vec::new();
+    for file_path in &files {
+        match parse_path_internal(Path::new(file_path)) {
+            Ok(parsed) => parsed_files.push((file_path.clone(), parsed)),
+            Err(e) => warnings.push(FileError {
+                path: file_path.clone(),
+                error: format!("文件名解析失败，按未匹配处理: {}", e),
+            }),
+        }
+    }
+    
+    // 整个文件夹通常是同一部动漫：解析不出标题的文件使用文件夹中最常见的标题
+    let mut title_counts: HashMap<&str, usize> = HashMap::new();
+    for (_, parsed) in &parsed_files {
+        if !parsed.anime_title.trim().is_empty() {
+            *title_counts.entry(parsed.anime_title.as_str()).or_default() += 1;
+        }
+    }
+    let series_title = title_counts.into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(title, _)| title.to_string());
+    if let Some(series_title) = &series_title {
+        log.info(format!("推断文件夹标题: {}", series_title));
+        for (file_path, parsed) in parsed_files.iter_mut() {
+            if parsed.anime_title.trim().is_empty() {
+                parsed.anime_title = series_title.clone();
+                warnings.push(FileError {
+                    path: file_path.clone(),
+                    error: format!("文件名中没有标题，使用文件夹标题: {}", series_title),
+                });
+            }
+        }
+    }
+    
+    // 文件名没有季度标记时，同一标题的集数重新开始视为进入下一季
+    let mut by_title: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, (_, parsed)) in parsed_files.iter().enumerate() {
+        by_title.entry(parsed.anime_title.trim().to_lowercase()).or_default().push(index);
+    }
+    for indices in by_title.values() {
+        let parsed: Vec<Option<&ParsedFilename>> = indices.iter().map(|index| Some(&parsed_files[*index].1)).collect();
+        let seasons = infer_seasons(&parsed);
+        for (index, (season, signal)) in indices.iter().zip(seasons) {
+            if signal == "episode_reset" {
+                parsed_files[*index].1.season = Some(season);
+            }
+        }
+    }
+    
+    let options = AutoMatchOptions {
+        parsed_overrides: parsed_files.into_iter().collect(),
+        dry_run: dry_run.unwrap_or(false),
+        skipped,
+        warnings,
+        ..AutoMatchOptions::default()
+    };
+    run_auto_match(&files, &output_dir, threshold, options, &anilist_cache, &log, &config).await.map_err(AppError::from)
+}
+
+// 自动匹配的可选行为，batch_auto_match 只设置扫描时的文件大小
+#[derive(Default)]
+pub(crate) struct AutoMatchOptions<'a> {
+    pub(crate) scanned_sizes: Option<&'a HashMap<String, u64>>,
+    // 源文件 -> 推断出的解析结果，替代单独解析文件名的结果（例如补全标题和季度）
+    pub(crate) parsed_overrides: HashMap<String, ParsedFilename>,
+    // 预览时只计算目标路径，不创建任何文件
+    pub(crate) dry_run: bool,
+    // 之前步骤中产生的跳过文件和警告，并入最终结果
+    pub(crate) skipped: Vec<FileError>,
+    pub(crate) warnings: Vec<FileError>,
+}
+
+// 自动匹配的核心流程：查询每个标题的最佳候选，置信度达到阈值的文件按元数据命名，其余链接到未匹配文件夹
+pub(crate) async fn run_auto_match(
+    files: &[String],
+    output_dir: &str,
+    threshold: f32,
+    options: AutoMatchOptions<'_>,
+    anilist_cache: &AniListCache,
+    log: &CommandLog<'_>,
+    config: &AppConfig
+) -> Result<AutoMatchResult, String> {
+    let AutoMatchOptions { scanned_sizes, parsed_overrides, dry_run, mut skipped, mut warnings } = options;
+    
+    info!("开始自动匹配 {} 个文件，置信度阈值: {} (预览: {})", files.len(), threshold, dry_run);
+    log.info(format!("开始自动匹配 {} 个文件，置信度阈值: {} (预览: {})", files.len(), threshold, dry_run));
+    
+    let sanitized_output_dir = if dry_run {
+        sanitize_path(Path::new(output_dir))
+    } else {
+        ensure_output_dir(&PathBuf::from(output_dir))
+            .map_err(|e| e.to_string())?
+    };
+    let unmatched_dir = sanitized_output_dir.join(sanitize_filename(&config.unmatched_folder));
+    
+    let parse = |file_path: &String| parsed_overrides.get(file_path).cloned()
+        .or_else(|| parse_path_internal(Path::new(file_path)).ok());
+    
+    // 同一部动漫的多集只查询一次
+    let titles: Vec<String> = files.iter()
+        .filter_map(parse)
+        .map(|parsed| parsed.anime_title)
+        .collect();
+    let mut lookup_failures = HashMap::new();
+    let match_cache = lookup_titles(titles, anilist_cache, log, &mut lookup_failures).await;
+    let mut processed = Vec::new();
+    let mut failed = Vec::new();
+    let mut decisions = Vec::new();
+    let mut manifest_entries = Vec::new();
+    let mut linked_sidecars = Vec::new();
+    let journal = BatchJournal::new();
+    let input_files: HashSet<String> = files.iter().cloned().collect();
+    
+    for file_path in files {
+        let source = PathBuf::from(file_path);
+        
+        // 跳过空文件和未下载完成的文件
+        if let Some(reason) = incomplete_source_reason(&source, config, scanned_sizes) {
+            warn!("跳过未完成的文件: {}", file_path);
+            skipped.push(FileError {
+                path: file_path.clone(),
+                error: reason,
+            });
+            continue;
+        }
+        
+        let file_name = match source.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => {
+                failed.push(FileError {
+                    path: file_path.clone(),
+                    error: "无效的文件名".to_string(),
+                });
+                continue;
+            }
+        };
+        
+        let parsed = parse(file_path);
+        let parsed_title = parsed.as_ref()
+            .map(|p| p.anime_title.clone())
+            .unwrap_or_default();
+        
+        let best = match_cache.get(&parsed_title).cloned().flatten();
+        if let Some(reason) = lookup_failures.get(&parsed_title) {
+            warnings.push(FileError {
+                path: file_path.clone(),
+                error: reason.clone(),
+            });
+        }
+        
+        // 置信度达到阈值且解析出集数时才能自动命名
+        let matched_target = match (&best, &parsed) {
+            (Some((anime, unified)), Some(parsed))
+                if unified.confidence >= threshold && parsed.episode_number.is_some() =>
+            {
+                let episode_title = if config.fetch_episode_titles {
+                    episode_title_for(anime.id, parsed.episode_number, log).await
+                } else {
+                    None
+                };
+                Some(place_subtitle_target(build_matched_target(&sanitized_output_dir, &source, Some(anime), parsed, episode_title, config), config))
+            }
+            _ => None,
+        };
+        let auto_applied = matched_target.is_some();
+        let target = matched_target.unwrap_or_else(|| unmatched_dir.join(sanitize_filename(&file_name)));
+        
+        let mut decision = MatchDecision {
+            source: file_path.clone(),
+            target: None,
+            parsed_title,
+            best_guess: best.map(|(_, unified)| unified),
+            auto_applied,
+        };
+        
+        if dry_run {
+            decision.target = Some(with_default_extension(&source, target, config).to_string_lossy().to_string());
+            processed.push(file_path.clone());
+            decisions.push(decision);
+            continue;
+        }
+        
+        match journal.link(&source, &target, config) {
+            Ok(outcome) => {
+                linked_sidecars.extend(link_sidecars_for_batch(&source, &outcome, &input_files, config));
+                if config.write_manifest {
+                    manifest_entries.push(build_manifest_entry(&source, &outcome.target, &sanitized_output_dir, outcome.action.as_str()));
+                }
+                decision.target = Some(outcome.target.to_string_lossy().to_string());
+                processed.push(file_path.clone());
+            }
+            Err(e) => {
+                warn!("文件处理失败: {}, 错误: {}", file_path, e);
+                failed.push(FileError {
+                    path: file_path.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+        
+        decisions.push(decision);
+    }
+    
+    if config.write_manifest && !dry_run {
+        manifest_entries.extend(linked_sidecars.iter().map(|link| {
+            build_manifest_entry(Path::new(&link.source), Path::new(&link.target), &sanitized_output_dir, &link.action)
+        }));
+        write_batch_manifest(log, &sanitized_output_dir, manifest_entries, config);
+    }
+    
+    let unmatched: Vec<MatchDecision> = decisions.iter()
+        .filter(|decision| !decision.auto_applied && decision.target.is_some())
+        .cloned()
+        .collect();
+    if !unmatched.is_empty() && !dry_run {
+        if let Err(e) = append_unmatched_records(&unmatched_dir, unmatched) {
+            log.warn(format!("记录未匹配文件失败: {}", e));
+        }
+    }
+    
+    let auto_count = decisions.iter().filter(|decision| decision.auto_applied).count();
+    let manual_count = decisions.len() - auto_count;
+    let success_count = processed.len();
+    let failed_count = failed.len();
+    let skipped_count = skipped.len();
+    let total_count = files.len();
+    
+    info!("自动匹配完成: 自动 {}, 待确认 {}, 失败 {}, 跳过 {}", auto_count, manual_count, failed_count, skipped_count);
+    log.info(format!("自动匹配完成: 自动 {}, 待确认 {}, 失败 {}, 跳过 {}", auto_count, manual_count, failed_count, skipped_count));
+    for failed_file in &failed {
+        log.error(format!("文件处理失败: {} - {}", failed_file.path, failed_file.error));
+    }
+    
+    let result = ProcessResult {
+        success: failed_count == 0,
+        message: if dry_run {
+            format!("预览: 自动命名 {}, 待确认 {}, 跳过 {}", auto_count, manual_count, skipped_count)
+        } else {
+            format!("处理完成: 成功 {}/{}, 失败 {}, 跳过 {}", success_count, total_count, failed_count, skipped_count)
+        },
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: skipped,
+        linked_sidecars,
+        warnings,
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    if !dry_run {
+        journal.save(log.source(), &result.linked_sidecars, log);
+        record_operation(log.source(), output_dir, &result, config);
+    }
+    
+    Ok(AutoMatchResult {
+        result,
+        auto_count,
+        manual_count,
+        decisions,
+    })
+}
+
+// 查询多个标题的最佳候选，未缓存的标题合并为尽量少的 AniList 批量请求
+// 查询成功的结果在进程内缓存，查询失败的标题按未匹配处理，失败原因按标题记录到 failures
+async fn lookup_titles(
+    titles: Vec<String>,
+    anilist_cache: &AniListCache,
+    log: &CommandLog<'_>,
+    failures: &mut HashMap<String, String>
+) -> HashMap<String, Option<(AniListResponse, UnifiedMatch)>> {
+    let mut titles: Vec<String> = titles.into_iter()
+        .filter(|title| !title.is_empty())
+        .collect();
+    titles.sort();
+    titles.dedup();
+    
+    let mut matches = HashMap::new();
+    let mut uncached = Vec::new();
+    for title in titles {
+        if let Some(cached) = MATCH_CACHE.read().unwrap().get(&title) {
+            matches.insert(title, cached.clone());
+            continue;
+        }
+        uncached.push(title);
+    }
+    
+    let searches = search_anilist_batch_cached(anilist_cache, &uncached).await;
+    let prefer_romaji = read_app_config().use_romaji_names;
+    for title in uncached {
+        match searches.get(&normalize_query(&title)) {
+            Some(Ok(candidates)) => {
+                let found = rank_anilist_candidates(candidates.clone(), &title, prefer_romaji).into_iter().next();
+                MATCH_CACHE.write().unwrap().insert(title.clone(), found.clone());
+                matches.insert(title, found);
+            }
+            Some(Err(e)) => {
+                log.warn(format!("查询 \"{}\" 失败，按未匹配处理: {}", title, e));
+                failures.insert(title.clone(), format!("元数据查询失败，按未匹配处理: {}", e));
+                matches.insert(title, None);
+            }
+            None => {
+                matches.insert(title, None);
+            }
+        }
+    }
+    
+    matches
+}
+
+// 获取单集标题，查询失败时仅记录警告
+pub(crate) async fn episode_title_for(anime_id: u32, episode: Option<u32>, log: &CommandLog<'_>) -> Option<String> {
+    match get_anilist_episode_titles(anime_id).await {
+        Ok(titles) => titles.get(&episode?).cloned(),
+        Err(e) => {
+            log.warn(format!("获取单集标题失败 (AniList ID {}): {}", anime_id, e));
+            None
+        }
+    }
+}
+
+// 按配置中的文件夹、季度文件夹和命名模板计算文件的目标路径（自动匹配、单文件整理和配置预览共用）
+pub(crate) fn build_matched_target(
+    output_dir: &Path,
+    source: &Path,
+    anime: Option<&AniListResponse>,
+    parsed: &ParsedFilename,
+    episode_title: Option<String>,
+    config: &AppConfig
+) -> PathBuf {
+    let season = parsed.season.unwrap_or(1);
+    // 没有元数据时使用文件名解析出的标题
+    let (title, _) = best_title(anime, config.use_romaji_names, &parsed.anime_title);
+    
+    let mut context = TemplateContext {
+        title,
+        title_clean: Some(parsed.title_clean.clone()),
+        title_romaji: anime.and_then(|anime| anime.title.get(TitleField::Romaji)).map(str::to_string),
+        title_english: anime.and_then(|anime| anime.title.get(TitleField::English)).map(str::to_string),
+        season: Some(season),
+        episode: parsed.episode_number,
+        episode_abs: parsed.episode_number,
+        episode_title,
+        year: anime.and_then(|anime| anime.season_year),
+        group: parsed.group.clone(),
+        resolution: parsed.resolution.clone(),
+        ext: source.extension().map(|ext| ext.to_string_lossy().to_string()),
+    };
+    context.apply_episode_offsets(config, &parsed.anime_title);
+    
+    let mut target = resolve_file_library_root(output_dir, source, anime, config);
+    if config.create_anime_folders {
+        // 配置了标题覆盖时，文件夹模板中的标题字段使用覆盖的标题
+        let folder_name = match title_override(anime, &parsed.anime_title, config) {
+            Some(folder_title) => render_template(&config.folder_template, &with_title_override(&context, folder_title), &config.template_separators),
+            None => render_template(&config.folder_template, &context, &config.template_separators),
+        };
+        target.push(sanitize_filename(&folder_name));
+    }
+    if config.create_season_folders {
+        let season_folder = if config.route_specials && parsed.is_special {
+            generate_season_folder_name(&config.specials_folder_template, 0, &config.template_separators)
+        } else {
+            generate_season_folder_name(&config.season_folder_template, season, &config.template_separators)
+        };
+        target.push(season_folder);
+    }
+    
+    let stem = sanitize_filename(&render_template(&config.naming_template, &context, &config.template_separators));
+    let file_name = match source.extension() {
+        Some(extension) => format!("{}.{}", stem, extension.to_string_lossy()),
+        None => stem,
+    };
+    
+    target.join(file_name)
+}
+
+// 将未匹配文件的最佳猜测追加到未匹配文件夹的记录中
+fn append_unmatched_records(unmatched_dir: &Path, records: Vec<MatchDecision>) -> Result<(), String> {
+    let record_path = unmatched_dir.join(UNMATCHED_RECORD_FILE_NAME);
+    
+    let mut all_records: Vec<MatchDecision> = if record_path.exists() {
+        let content = fs::read_to_string(&record_path)
+            .map_err(|e| format!("读取未匹配记录失败: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("未匹配记录格式错误: {}", e))?
+    } else {
+        Vec::new()
+    };
+    
+    all_records.extend(records);
+    
+    let json = serde_json::to_string_pretty(&all_records)
+        .map_err(|e| format!("序列化未匹配记录失败: {}", e))?;
+    fs::write(&record_path, json)
+        .map_err(|e| format!("写入未匹配记录失败: {}", e))
+}
+
+// 无法识别的文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnidentifiedFile {
+    pub path: String,
+    pub parsed_title: String,
+    pub best_guess: Option<UnifiedMatch>,
+    pub reason: String,
+}
+
+// 扫描媒体库中的视频文件，列出解析不出标题、没有匹配或匹配置信度低于阈值的文件
+#[command]
+pub async fn find_unidentified(
+    root: String,
+    threshold: Option<f32>,
+    anilist_cache: State<'_, AniListCache>,
+    log_store: State<'_, LogStore>
+) -> Result<Vec<UnidentifiedFile>, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_FIND_UNIDENTIFIED);
+    let config = read_app_config();
+    let threshold = threshold.unwrap_or(config.auto_match_threshold);
+    
+    if !config.anilist_enabled {
+        return Err("识别检查需要启用 AniList 数据源".into());
+    }
+    if !Path::new(&root).is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", root)));
+    }
+    
+    let (files, _) = scan_directory_internal(&root, &config);
+    let videos: Vec<(String, String)> = files.into_iter()
+        .filter(|file| file.kind == FileKind::Video)
+        .map(|file| {
+            let parsed_title = parse_path_internal(Path::new(&file.path))
+                .map(|parsed| parsed.anime_title)
+                .unwrap_or_default();
+            (file.path, parsed_title)
+        })
+        .collect();
+    
+    info!("开始检查 {} 个视频文件的识别情况", videos.len());
+    log.info(format!("开始检查 {} 个视频文件的识别情况，置信度阈值: {}", videos.len(), threshold));
+    
+    let matches = lookup_titles(videos.iter().map(|(_, title)| title.clone()).collect(), &anilist_cache, &log, &mut HashMap::new()).await;
+    
+    let mut unidentified: Vec<UnidentifiedFile> = videos.into_iter()
+        .filter_map(|(path, parsed_title)| {
+            let best = matches.get(&parsed_title).cloned().flatten().map(|(_, unified)| unified);
+            let reason = if parsed_title.is_empty() {
+                "无法从文件名解析出标题".to_string()
+            } else {
+                match &best {
+                    None => "没有找到匹配的条目".to_string(),
+                    Some(unified) if unified.confidence < threshold => format!("匹配置信度过低: {:.2}", unified.confidence),
+                    Some(_) => return None,
+                }
+            };
+            Some(UnidentifiedFile {
+                path,
+                parsed_title,
+                best_guess: best,
+                reason,
+            })
+        })
+        .collect();
+    unidentified.sort_by(|a, b| a.path.cmp(&b.path));
+    
+    info!("识别检查完成: {} 个文件无法识别", unidentified.len());
+    log.info(format!("识别检查完成: {} 个文件无法识别", unidentified.len()));
+    
+    Ok(unidentified)
+}
+
+// 导出的一行元数据
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataRow {
+    pub filename: String,
+    pub path: String,
+    pub title: String,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+    pub group: Option<String>,
+    pub resolution: Option<String>,
+    pub matched_id: Option<String>,
+    pub matched_title: Option<String>,
+}
+
+const METADATA_CSV_HEADER: &str = "filename,path,title,season,episode,group,resolution,matched_id,matched_title";
+
+// 扫描文件夹并将每个视频的解析结果导出为 CSV 或 JSON，match_titles 为 true 时附带 AniList 匹配结果
+// 解析并行进行，结果逐行写入文件，返回导出的行数
+#[command]
+pub async fn export_metadata(
+    folder: String,
+    format: String,
+    path: String,
+    match_titles: Option<bool>,
+    anilist_cache: State<'_, AniListCache>,
+    log_store: State<'_, LogStore>
+) -> Result<usize, AppError> {
+    use rayon::prelude::*;
+    use std::io::{BufWriter, Write};
+    
+    let log = CommandLog::new(&log_store, SOURCE_EXPORT_METADATA);
+    let config = read_app_config();
+    let match_titles = match_titles.unwrap_or(false);
+    
+    if format != "csv" && format != "json" {
+        return Err(format!("不支持的导出格式: {}，可选值: csv, json", format).into());
+    }
+    if !Path::new(&folder).is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", folder)));
+    }
+    if match_titles && !config.anilist_enabled {
+        return Err("匹配元数据需要启用 AniList 数据源".into());
+    }
+    
+    let (files, _) = scan_directory_internal(&folder, &config);
+    let mut rows: Vec<MetadataRow> = files.into_par_iter()
+        .filter(|file| file.kind == FileKind::Video)
+        .map(|file| {
+            let parsed = parse_path_internal(Path::new(&file.path)).ok();
+            MetadataRow {
+                title: parsed.as_ref().map(|p| p.anime_title.clone()).unwrap_or_default(),
+                season: parsed.as_ref().and_then(|p| p.season),
+                episode: parsed.as_ref().and_then(|p| p.episode_number),
+                group: parsed.as_ref().and_then(|p| p.group.clone()),
+                resolution: parsed.and_then(|p| p.resolution),
+                filename: file.name,
+                path: file.path,
+                matched_id: None,
+                matched_title: None,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| a.path.cmp(&b.path));
+    
+    info!("开始导出 {} 个文件的元数据: {}", rows.len(), path);
+    log.info(format!("开始导出 {} 个文件的元数据 ({}): {}", rows.len(), format, path));
+    
+    if match_titles {
+        let matches = lookup_titles(rows.iter().map(|row| row.title.clone()).collect(), &anilist_cache, &log, &mut HashMap::new()).await;
+        for row in &mut rows {
+            if let Some((_, unified)) = matches.get(&row.title).cloned().flatten() {
+                row.matched_id = Some(unified.id);
+                row.matched_title = Some(unified.title);
+            }
+        }
+    }
+    
+    let file = fs::File::create(&path)
+        .map_err(|e| format!("创建导出文件失败: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    let write_error = |e: std::io::Error| format!("写入导出文件失败: {}", e);
+    let newline = line_ending(&config);
+    if config.write_utf8_bom {
+        writer.write_all(UTF8_BOM).map_err(write_error)?;
+    }
+    
+    if format == "csv" {
+        write!(writer, "{}{}", METADATA_CSV_HEADER, newline).map_err(write_error)?;
+        for row in &rows {
+            let optional = |value: Option<u32>| value.map(|v| v.to_string()).unwrap_or_default();
+            let fields = [
+                row.filename.clone(),
+                row.path.clone(),
+                row.title.clone(),
+                optional(row.season),
+                optional(row.episode),
+                row.group.clone().unwrap_or_default(),
+                row.resolution.clone().unwrap_or_default(),
+                row.matched_id.clone().unwrap_or_default(),
+                row.matched_title.clone().unwrap_or_default(),
+            ];
+            let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            write!(writer, "{}{}", line.join(","), newline).map_err(write_error)?;
+        }
+    } else {
+        write!(writer, "[{}", newline).map_err(write_error)?;
+        for (index, row) in rows.iter().enumerate() {
+            let json = serde_json::to_string(row)
+                .map_err(|e| format!("序列化元数据失败: {}", e))?;
+            let separator = if index + 1 < rows.len() { "," } else { "" };
+            write!(writer, "  {}{}{}", json, separator, newline).map_err(write_error)?;
+        }
+        write!(writer, "]{}", newline).map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)?;
+    
+    info!("元数据导出完成: {} 行", rows.len());
+    log.info(format!("元数据导出完成: {} 行", rows.len()));
+    
+    Ok(rows.len())
+}
+
+// 按 CSV 规则转义字段：包含逗号、引号或换行时加引号，引号本身双写
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
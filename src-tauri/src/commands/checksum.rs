@@ -0,0 +1,37 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+// CRC32（IEEE 802.3，与压制组文件名中的校验值相同）的查找表
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+// 流式计算文件的 CRC32，返回 8 位大写十六进制
+pub(crate) fn file_crc32(path: &Path) -> io::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut buffer = vec![0u8; 1024 * 1024];
+    let mut crc = 0xFFFF_FFFFu32;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        for &byte in &buffer[..read] {
+            crc = CRC32_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+        }
+    }
+    Ok(format!("{:08X}", !crc))
+}
@@ -2,8 +2,19 @@ use serde::{Deserialize, Serialize};
 use tauri::command;
 use anyhow::Result;
 use std::path::PathBuf;
+use std::collections::HashMap;
+use tracing::warn;
+use crate::commands::file_operations::{LinkMode, OutputDirError, ensure_output_dir, extension_listed, generate_season_folder_name, refresh_char_replacements};
+use crate::commands::metadata::episode_from_regex;
+use crate::commands::providers::{KNOWN_PROVIDERS, check_provider_order};
+use crate::commands::text_output::LINE_ENDINGS;
+use crate::commands::template::{
+    absolute_episode, episode_offset_for, normalize_rendered_name, replace_padded_number, template_tokens, token_allowed, unknown_template_tokens, TEMPLATE_TOKENS,
+};
+use crate::commands::errors::AppError;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct AppConfig {
     pub output_directory: String,
     pub naming_template: String,
@@ -18,6 +29,61 @@ pub struct AppConfig {
     pub tmdb_enabled: bool,
     pub concurrent_limit: usize,
     pub log_level: String,
+    pub write_manifest: bool,
+    pub manifest_append: bool,
+    pub skip_zero_byte_files: bool,
+    pub in_progress_extensions: Vec<String>,
+    pub fsync_dirs: bool,
+    pub write_series_nfo: bool,
+    pub template_separators: Vec<String>,
+    pub group_by_broadcast_season: bool,
+    pub broadcast_season_template: String,
+    pub broadcast_season_labels: HashMap<String, String>,
+    pub auto_match_threshold: f32,
+    pub unmatched_folder: String,
+    pub link_sidecars: bool,
+    pub sidecar_extensions: Vec<String>,
+    pub group_by_device: bool,
+    pub ssd_concurrency: usize,
+    pub hdd_concurrency: usize,
+    pub title_replace_underscores: bool,
+    pub title_replace_dots: bool,
+    pub title_collapse_whitespace: bool,
+    pub title_strip_brackets: bool,
+    pub fetch_episode_titles: bool,
+    pub subtitle_subfolder: String,
+    pub scan_include_sidecars: bool,
+    pub extension_change_policy: String,
+    pub record_operation_history: bool,
+    pub metadata_concurrent_limit: usize,
+    pub episode_regex: Option<String>,
+    pub subtitle_language_aliases: HashMap<String, String>,
+    pub route_specials: bool,
+    pub specials_folder_template: String,
+    pub duplicate_resolution: String,
+    pub fallback_title_extractor: bool,
+    pub folder_context_parsing: bool,
+    pub group_top_level: bool,
+    pub copy_forbidden_extensions: Vec<String>,
+    pub provider_order: Vec<String>,
+    pub line_ending: String,
+    pub write_utf8_bom: bool,
+    pub write_season_assets: bool,
+    pub existing_file_check: String,
+    pub accept_extensionless_files: bool,
+    pub extensionless_default_extension: String,
+    pub title_overrides: HashMap<String, String>,
+    pub junk_patterns: Vec<String>,
+    pub link_mode: String,
+    pub tmdb_api_key: Option<String>,
+    pub anilist_cache_ttl_secs: u64,
+    pub video_extensions: Vec<String>,
+    pub subtitle_extensions: Vec<String>,
+    // 系列标题 -> 季度 -> 该季之前已有的集数，用于 {episode_abs}，例如第 2 季偏移 12 时 S02E01 为第 13 集
+    pub episode_offsets: HashMap<String, HashMap<u32, i64>>,
+    // 监视模式默认监视的下载目录，为空时需要在 start_watch 中指定
+    pub watch_directory: String,
+    pub char_replacements: HashMap<String, String>,
 }
 
 impl Default for AppConfig {
@@ -40,12 +106,116 @@ impl Default for AppConfig {
             tmdb_enabled: false,
             concurrent_limit: 4,
             log_level: "info".to_string(),
+            write_manifest: false,
+            manifest_append: true,
+            skip_zero_byte_files: true,
+            in_progress_extensions: vec![
+                "part".to_string(),
+                "!qb".to_string(),
+                "crdownload".to_string(),
+                "downloading".to_string(),
+                "tmp".to_string(),
+            ],
+            fsync_dirs: false,
+            write_series_nfo: false,
+            template_separators: vec![
+                "-".to_string(),
+                "_".to_string(),
+                "~".to_string(),
+            ],
+            group_by_broadcast_season: false,
+            broadcast_season_template: "{year} {broadcast_season}".to_string(),
+            broadcast_season_labels: HashMap::from([
+                ("WINTER".to_string(), "Winter".to_string()),
+                ("SPRING".to_string(), "Spring".to_string()),
+                ("SUMMER".to_string(), "Summer".to_string()),
+                ("FALL".to_string(), "Fall".to_string()),
+            ]),
+            auto_match_threshold: 0.85,
+            unmatched_folder: "_unmatched".to_string(),
+            link_sidecars: false,
+            sidecar_extensions: vec![
+                "ass".to_string(),
+                "ssa".to_string(),
+                "srt".to_string(),
+                "sup".to_string(),
+                "vtt".to_string(),
+                "nfo".to_string(),
+                "jpg".to_string(),
+                "png".to_string(),
+            ],
+            group_by_device: false,
+            ssd_concurrency: 8,
+            hdd_concurrency: 1,
+            title_replace_underscores: true,
+            title_replace_dots: true,
+            title_collapse_whitespace: true,
+            title_strip_brackets: false,
+            fetch_episode_titles: false,
+            subtitle_subfolder: String::new(),
+            scan_include_sidecars: false,
+            extension_change_policy: "warn".to_string(),
+            record_operation_history: true,
+            metadata_concurrent_limit: 2,
+            episode_regex: None,
+            subtitle_language_aliases: [
+                ("chs", "chs"), ("sc", "chs"), ("gb", "chs"), ("zh-hans", "chs"), ("zh-cn", "chs"), ("简体", "chs"), ("简中", "chs"),
+                ("cht", "cht"), ("tc", "cht"), ("big5", "cht"), ("zh-hant", "cht"), ("zh-tw", "cht"), ("繁体", "cht"), ("繁中", "cht"),
+                ("jpn", "jpn"), ("jp", "jpn"), ("ja", "jpn"),
+                ("eng", "eng"), ("en", "eng"),
+            ].into_iter().map(|(alias, language)| (alias.to_string(), language.to_string())).collect(),
+            route_specials: false,
+            specials_folder_template: "Season {season:02}".to_string(),
+            duplicate_resolution: "keep_all".to_string(),
+            fallback_title_extractor: true,
+            folder_context_parsing: true,
+            group_top_level: false,
+            copy_forbidden_extensions: Vec::new(),
+            provider_order: KNOWN_PROVIDERS.iter().map(|provider| provider.to_string()).collect(),
+            line_ending: "native".to_string(),
+            write_utf8_bom: false,
+            write_season_assets: false,
+            existing_file_check: "off".to_string(),
+            accept_extensionless_files: false,
+            extensionless_default_extension: String::new(),
+            title_overrides: HashMap::new(),
+            junk_patterns: vec![
+                "*.txt".to_string(),
+                "*.url".to_string(),
+                "*.website".to_string(),
+                "*.lnk".to_string(),
+                "*.exe".to_string(),
+                "*.bat".to_string(),
+                "*.scr".to_string(),
+                "*.htm".to_string(),
+                "*.html".to_string(),
+                "Thumbs.db".to_string(),
+                "desktop.ini".to_string(),
+                ".DS_Store".to_string(),
+            ],
+            link_mode: "hardlink".to_string(),
+            tmdb_api_key: None,
+            anilist_cache_ttl_secs: 3600,
+            video_extensions: vec![
+                "mkv".to_string(),
+                "mp4".to_string(),
+                "avi".to_string(),
+                "mov".to_string(),
+            ],
+            subtitle_extensions: vec![
+                "ass".to_string(),
+                "srt".to_string(),
+                "vtt".to_string(),
+            ],
+            episode_offsets: HashMap::new(),
+            watch_directory: String::new(),
+            char_replacements: HashMap::new(),
         }
     }
 }
 
 #[command]
-pub async fn load_config() -> Result<AppConfig, String> {
+pub async fn load_config() -> Result<AppConfig, AppError> {
     let config_path = get_config_path()?;
     
     if config_path.exists() {
@@ -100,17 +270,202 @@ pub async fn load_config() -> Result<AppConfig, String> {
                             if let Some(log_level) = obj.get("log_level").and_then(|v| v.as_str()) {
                                 default_config.log_level = log_level.to_string();
                             }
+                            if let Some(write_manifest) = obj.get("write_manifest").and_then(|v| v.as_bool()) {
+                                default_config.write_manifest = write_manifest;
+                            }
+                            if let Some(manifest_append) = obj.get("manifest_append").and_then(|v| v.as_bool()) {
+                                default_config.manifest_append = manifest_append;
+                            }
+                            if let Some(skip_zero_byte_files) = obj.get("skip_zero_byte_files").and_then(|v| v.as_bool()) {
+                                default_config.skip_zero_byte_files = skip_zero_byte_files;
+                            }
+                            if let Some(in_progress_extensions) = obj.get("in_progress_extensions").and_then(|v| v.as_array()) {
+                                default_config.in_progress_extensions = in_progress_extensions.iter()
+                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                    .collect();
+                            }
+                            if let Some(fsync_dirs) = obj.get("fsync_dirs").and_then(|v| v.as_bool()) {
+                                default_config.fsync_dirs = fsync_dirs;
+                            }
+                            if let Some(write_series_nfo) = obj.get("write_series_nfo").and_then(|v| v.as_bool()) {
+                                default_config.write_series_nfo = write_series_nfo;
+                            }
+                            if let Some(template_separators) = obj.get("template_separators").and_then(|v| v.as_array()) {
+                                default_config.template_separators = template_separators.iter()
+                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                    .collect();
+                            }
+                            if let Some(group_by_broadcast_season) = obj.get("group_by_broadcast_season").and_then(|v| v.as_bool()) {
+                                default_config.group_by_broadcast_season = group_by_broadcast_season;
+                            }
+                            if let Some(broadcast_season_template) = obj.get("broadcast_season_template").and_then(|v| v.as_str()) {
+                                default_config.broadcast_season_template = broadcast_season_template.to_string();
+                            }
+                            if let Some(broadcast_season_labels) = obj.get("broadcast_season_labels").and_then(|v| v.as_object()) {
+                                for (season, label) in broadcast_season_labels {
+                                    if let Some(label) = label.as_str() {
+                                        default_config.broadcast_season_labels.insert(season.clone(), label.to_string());
+                                    }
+                                }
+                            }
+                            if let Some(auto_match_threshold) = obj.get("auto_match_threshold").and_then(|v| v.as_f64()) {
+                                default_config.auto_match_threshold = auto_match_threshold as f32;
+                            }
+                            if let Some(unmatched_folder) = obj.get("unmatched_folder").and_then(|v| v.as_str()) {
+                                default_config.unmatched_folder = unmatched_folder.to_string();
+                            }
+                            if let Some(link_sidecars) = obj.get("link_sidecars").and_then(|v| v.as_bool()) {
+                                default_config.link_sidecars = link_sidecars;
+                            }
+                            if let Some(sidecar_extensions) = obj.get("sidecar_extensions").and_then(|v| v.as_array()) {
+                                default_config.sidecar_extensions = sidecar_extensions.iter()
+                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                    .collect();
+                            }
+                            if let Some(group_by_device) = obj.get("group_by_device").and_then(|v| v.as_bool()) {
+                                default_config.group_by_device = group_by_device;
+                            }
+                            if let Some(ssd_concurrency) = obj.get("ssd_concurrency").and_then(|v| v.as_u64()) {
+                                default_config.ssd_concurrency = ssd_concurrency as usize;
+                            }
+                            if let Some(hdd_concurrency) = obj.get("hdd_concurrency").and_then(|v| v.as_u64()) {
+                                default_config.hdd_concurrency = hdd_concurrency as usize;
+                            }
+                            if let Some(title_replace_underscores) = obj.get("title_replace_underscores").and_then(|v| v.as_bool()) {
+                                default_config.title_replace_underscores = title_replace_underscores;
+                            }
+                            if let Some(title_replace_dots) = obj.get("title_replace_dots").and_then(|v| v.as_bool()) {
+                                default_config.title_replace_dots = title_replace_dots;
+                            }
+                            if let Some(title_collapse_whitespace) = obj.get("title_collapse_whitespace").and_then(|v| v.as_bool()) {
+                                default_config.title_collapse_whitespace = title_collapse_whitespace;
+                            }
+                            if let Some(title_strip_brackets) = obj.get("title_strip_brackets").and_then(|v| v.as_bool()) {
+                                default_config.title_strip_brackets = title_strip_brackets;
+                            }
+                            if let Some(fetch_episode_titles) = obj.get("fetch_episode_titles").and_then(|v| v.as_bool()) {
+                                default_config.fetch_episode_titles = fetch_episode_titles;
+                            }
+                            if let Some(subtitle_subfolder) = obj.get("subtitle_subfolder").and_then(|v| v.as_str()) {
+                                default_config.subtitle_subfolder = subtitle_subfolder.to_string();
+                            }
+                            if let Some(scan_include_sidecars) = obj.get("scan_include_sidecars").and_then(|v| v.as_bool()) {
+                                default_config.scan_include_sidecars = scan_include_sidecars;
+                            }
+                            if let Some(extension_change_policy) = obj.get("extension_change_policy").and_then(|v| v.as_str()) {
+                                default_config.extension_change_policy = extension_change_policy.to_string();
+                            }
+                            if let Some(record_operation_history) = obj.get("record_operation_history").and_then(|v| v.as_bool()) {
+                                default_config.record_operation_history = record_operation_history;
+                            }
+                            if let Some(metadata_concurrent_limit) = obj.get("metadata_concurrent_limit").and_then(|v| v.as_u64()) {
+                                default_config.metadata_concurrent_limit = metadata_concurrent_limit as usize;
+                            }
+                            if let Some(episode_regex) = obj.get("episode_regex").and_then(|v| v.as_str()) {
+                                default_config.episode_regex = Some(episode_regex.to_string());
+                            }
+                            if let Some(subtitle_language_aliases) = obj.get("subtitle_language_aliases").and_then(|v| v.as_object()) {
+                                default_config.subtitle_language_aliases = subtitle_language_aliases.iter()
+                                    .filter_map(|(alias, language)| Some((alias.clone(), language.as_str()?.to_string())))
+                                    .collect();
+                            }
+                            if let Some(route_specials) = obj.get("route_specials").and_then(|v| v.as_bool()) {
+                                default_config.route_specials = route_specials;
+                            }
+                            if let Some(specials_folder_template) = obj.get("specials_folder_template").and_then(|v| v.as_str()) {
+                                default_config.specials_folder_template = specials_folder_template.to_string();
+                            }
+                            if let Some(duplicate_resolution) = obj.get("duplicate_resolution").and_then(|v| v.as_str()) {
+                                default_config.duplicate_resolution = duplicate_resolution.to_string();
+                            }
+                            if let Some(fallback_title_extractor) = obj.get("fallback_title_extractor").and_then(|v| v.as_bool()) {
+                                default_config.fallback_title_extractor = fallback_title_extractor;
+                            }
+                            if let Some(folder_context_parsing) = obj.get("folder_context_parsing").and_then(|v| v.as_bool()) {
+                                default_config.folder_context_parsing = folder_context_parsing;
+                            }
+                            if let Some(group_top_level) = obj.get("group_top_level").and_then(|v| v.as_bool()) {
+                                default_config.group_top_level = group_top_level;
+                            }
+                            if let Some(copy_forbidden_extensions) = obj.get("copy_forbidden_extensions").and_then(|v| v.as_array()) {
+                                default_config.copy_forbidden_extensions = copy_forbidden_extensions.iter()
+                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                    .collect();
+                            }
+                            if let Some(provider_order) = obj.get("provider_order").and_then(|v| v.as_array()) {
+                                default_config.provider_order = provider_order.iter()
+                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                    .collect();
+                            }
+                            if let Some(line_ending) = obj.get("line_ending").and_then(|v| v.as_str()) {
+                                default_config.line_ending = line_ending.to_string();
+                            }
+                            if let Some(write_utf8_bom) = obj.get("write_utf8_bom").and_then(|v| v.as_bool()) {
+                                default_config.write_utf8_bom = write_utf8_bom;
+                            }
+                            if let Some(write_season_assets) = obj.get("write_season_assets").and_then(|v| v.as_bool()) {
+                                default_config.write_season_assets = write_season_assets;
+                            }
+                            if let Some(existing_file_check) = obj.get("existing_file_check").and_then(|v| v.as_str()) {
+                                default_config.existing_file_check = existing_file_check.to_string();
+                            }
+                            if let Some(accept_extensionless_files) = obj.get("accept_extensionless_files").and_then(|v| v.as_bool()) {
+                                default_config.accept_extensionless_files = accept_extensionless_files;
+                            }
+                            if let Some(extensionless_default_extension) = obj.get("extensionless_default_extension").and_then(|v| v.as_str()) {
+                                default_config.extensionless_default_extension = extensionless_default_extension.to_string();
+                            }
+                            if let Some(title_overrides) = obj.get("title_overrides").and_then(|v| v.as_object()) {
+                                default_config.title_overrides = title_overrides.iter()
+                                    .filter_map(|(key, title)| Some((key.clone(), title.as_str()?.to_string())))
+                                    .collect();
+                            }
+                            if let Some(junk_patterns) = obj.get("junk_patterns").and_then(|v| v.as_array()) {
+                                default_config.junk_patterns = junk_patterns.iter()
+                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                    .collect();
+                            }
+                            if let Some(link_mode) = obj.get("link_mode").and_then(|v| v.as_str()) {
+                                default_config.link_mode = link_mode.to_string();
+                            }
+                            if let Some(tmdb_api_key) = obj.get("tmdb_api_key").and_then(|v| v.as_str()) {
+                                default_config.tmdb_api_key = Some(tmdb_api_key.to_string());
+                            }
+                            if let Some(anilist_cache_ttl_secs) = obj.get("anilist_cache_ttl_secs").and_then(|v| v.as_u64()) {
+                                default_config.anilist_cache_ttl_secs = anilist_cache_ttl_secs;
+                            }
+                            if let Some(video_extensions) = obj.get("video_extensions").and_then(|v| v.as_array()) {
+                                default_config.video_extensions = video_extensions.iter()
+                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                    .collect();
+                            }
+                            if let Some(subtitle_extensions) = obj.get("subtitle_extensions").and_then(|v| v.as_array()) {
+                                default_config.subtitle_extensions = subtitle_extensions.iter()
+                                    .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                                    .collect();
+                            }
+                            if let Some(episode_offsets) = obj.get("episode_offsets").and_then(|v| serde_json::from_value(v.clone()).ok()) {
+                                default_config.episode_offsets = episode_offsets;
+                            }
+                            if let Some(watch_directory) = obj.get("watch_directory").and_then(|v| v.as_str()) {
+                                default_config.watch_directory = watch_directory.to_string();
+                            }
+                            if let Some(char_replacements) = obj.get("char_replacements").and_then(|v| v.as_object()) {
+                                default_config.char_replacements = char_replacements.iter()
+                                    .filter_map(|(from, to)| Some((from.clone(), to.as_str()?.to_string())))
+                                    .collect();
+                            }
                         }
                         
                         // 保存更新后的配置
-                        save_config(default_config.clone()).await?;
+                        write_config_file(&default_config)?;
                         Ok(default_config)
                     }
                     Err(e) => {
                         // 如果完全无法解析，使用默认配置
                         let default_config = AppConfig::default();
-                        save_config(default_config.clone()).await?;
-                        Err(format!("配置文件格式错误，已重置为默认配置: {}", e))
+                        write_config_file(&default_config)?;
+                        Err(AppError::Parse(format!("配置文件格式错误，已重置为默认配置: {}", e)))
                     }
                 }
             }
@@ -118,13 +473,25 @@ pub async fn load_config() -> Result<AppConfig, String> {
     } else {
         // 如果配置文件不存在，返回默认配置并保存
         let default_config = AppConfig::default();
-        save_config(default_config.clone()).await?;
+        write_config_file(&default_config)?;
         Ok(default_config)
     }
 }
 
 #[command]
-pub async fn save_config(config: AppConfig) -> Result<bool, String> {
+pub async fn save_config(config: AppConfig) -> Result<bool, AppError> {
+    check_naming_templates(&config)?;
+    check_season_template(&config.season_folder_template)?;
+    write_config_file(&config)?;
+    // 自定义字符替换立即生效
+    if let Err(e) = refresh_char_replacements(&config) {
+        warn!("更新字符映射失败: {}", e);
+    }
+    Ok(true)
+}
+
+// 写入配置文件，不做校验；加载时迁移旧配置也走这里，避免旧配置中的问题导致无法加载
+pub(crate) fn write_config_file(config: &AppConfig) -> Result<(), String> {
     let config_path = get_config_path()?;
     
     // 确保配置目录存在
@@ -133,32 +500,27 @@ pub async fn save_config(config: AppConfig) -> Result<bool, String> {
             .map_err(|e| format!("创建配置目录失败: {}", e))?;
     }
     
-    let config_json = serde_json::to_string_pretty(&config)
+    let config_json = serde_json::to_string_pretty(config)
         .map_err(|e| format!("序列化配置失败: {}", e))?;
     
     std::fs::write(&config_path, config_json)
-        .map_err(|e| format!("保存配置文件失败: {}", e))?;
-    
-    Ok(true)
+        .map_err(|e| format!("保存配置文件失败: {}", e))
 }
 
 #[command]
-pub async fn reset_config() -> Result<AppConfig, String> {
+pub async fn reset_config() -> Result<AppConfig, AppError> {
     let default_config = AppConfig::default();
-    save_config(default_config.clone()).await?;
+    write_config_file(&default_config)?;
+    if let Err(e) = refresh_char_replacements(&default_config) {
+        warn!("更新字符映射失败: {}", e);
+    }
     Ok(default_config)
 }
 
 #[command]
-pub async fn validate_output_directory(path: String) -> Result<bool, String> {
-    let path_buf = PathBuf::from(&path);
-    
-    // 检查路径是否存在
-    if !path_buf.exists() {
-        // 尝试创建目录
-        std::fs::create_dir_all(&path_buf)
-            .map_err(|e| format!("无法创建输出目录: {}", e))?;
-    }
+pub async fn validate_output_directory(path: String) -> Result<bool, OutputDirError> {
+    // 清理路径并尝试创建目录
+    let path_buf = ensure_output_dir(&PathBuf::from(&path))?;
     
     // 检查是否有写权限
     let test_file = path_buf.join(".write_test");
@@ -167,12 +529,231 @@ pub async fn validate_output_directory(path: String) -> Result<bool, String> {
             let _ = std::fs::remove_file(&test_file);
             Ok(true)
         }
-        Err(e) => Err(format!("输出目录无写权限: {}", e)),
+        Err(e) => Err(OutputDirError::from_io(&path_buf, &e)),
+    }
+}
+
+// 配置问题的严重程度：error 会导致功能无法使用，warning 仅提示
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigIssue {
+    pub field: String,
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn error(field: &str, message: String) -> Self {
+        Self { field: field.to_string(), severity: IssueSeverity::Error, message }
+    }
+    
+    fn warning(field: &str, message: String) -> Self {
+        Self { field: field.to_string(), severity: IssueSeverity::Warning, message }
+    }
+}
+
+const LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+const EXISTING_FILE_CHECKS: [&str; 3] = ["off", "warn", "skip"];
+const EXTENSION_CHANGE_POLICIES: [&str; 3] = ["error", "warn", "allow"];
+const DUPLICATE_RESOLUTIONS: [&str; 4] = ["keep_largest", "keep_highest_resolution", "keep_first", "keep_all"];
+const SEASON_FOLDER_TOKENS: [&str; 3] = ["{season}", "{season:02}", "{season:03}"];
+const BROADCAST_SEASON_TOKENS: [&str; 2] = ["{year}", "{broadcast_season}"];
+
+// 季度文件夹模板示例
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeasonTemplateExamples {
+    pub season_1: String,
+    pub season_2: String,
+}
+
+fn has_season_token(template: &str) -> bool {
+    template_tokens(template).iter().any(|token| token_allowed(token, &SEASON_FOLDER_TOKENS))
+}
+
+// 命名、文件夹和字幕模板中有未知字段时拒绝保存，避免字段原样出现在文件名中
+fn check_naming_templates(config: &AppConfig) -> Result<(), String> {
+    let mut templates = vec![
+        ("命名模板", config.naming_template.as_str()),
+        ("文件夹模板", config.folder_template.as_str()),
+    ];
+    if let Some(subtitle_template) = &config.subtitle_template {
+        templates.push(("字幕模板", subtitle_template.as_str()));
+    }
+    
+    for (name, template) in templates {
+        let unknown = unknown_template_tokens(template);
+        if !unknown.is_empty() {
+            return Err(format!("{}中有未知字段: {}，可用字段: {}", name, unknown.join(", "), TEMPLATE_TOKENS.join(", ")));
+        }
     }
+    Ok(())
 }
 
+// 检查季度文件夹模板：不能为空，必须包含季度字段，且不能有季度字段以外的字段
+pub(crate) fn check_season_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("季度文件夹模板不能为空".to_string());
+    }
+    if let Some(token) = template_tokens(template).into_iter().find(|token| !token_allowed(token, &SEASON_FOLDER_TOKENS)) {
+        return Err(format!("季度文件夹模板中有未知字段: {}，可用字段: {}", token, SEASON_FOLDER_TOKENS.join(", ")));
+    }
+    if !has_season_token(template) {
+        return Err(format!("季度文件夹模板中没有季度字段，可用字段: {}", SEASON_FOLDER_TOKENS.join(", ")));
+    }
+    Ok(())
+}
+
+// 单独校验季度文件夹模板，通过时返回第1季和第2季的文件夹名示例
 #[command]
-pub async fn get_default_directories() -> Result<Vec<String>, String> {
+pub async fn validate_season_template(template: String) -> Result<SeasonTemplateExamples, AppError> {
+    check_season_template(&template)?;
+    
+    let separators = read_app_config().template_separators;
+    Ok(SeasonTemplateExamples {
+        season_1: generate_season_folder_name(&template, 1, &separators),
+        season_2: generate_season_folder_name(&template, 2, &separators),
+    })
+}
+
+// 一次性检查整个配置，返回所有问题，供设置页保存时统一展示
+#[command]
+pub async fn validate_config(config: AppConfig) -> Result<Vec<ConfigIssue>, AppError> {
+    let mut issues = Vec::new();
+    
+    // 模板中的未知字段
+    let mut templates = vec![
+        ("naming_template", config.naming_template.as_str(), &TEMPLATE_TOKENS[..]),
+        ("folder_template", config.folder_template.as_str(), &TEMPLATE_TOKENS[..]),
+        ("season_folder_template", config.season_folder_template.as_str(), &SEASON_FOLDER_TOKENS[..]),
+        ("specials_folder_template", config.specials_folder_template.as_str(), &SEASON_FOLDER_TOKENS[..]),
+        ("broadcast_season_template", config.broadcast_season_template.as_str(), &BROADCAST_SEASON_TOKENS[..]),
+    ];
+    if let Some(subtitle_template) = &config.subtitle_template {
+        templates.push(("subtitle_template", subtitle_template.as_str(), &TEMPLATE_TOKENS[..]));
+    }
+    for (field, template, allowed) in templates {
+        if template.trim().is_empty() {
+            issues.push(ConfigIssue::error(field, "模板不能为空".to_string()));
+            continue;
+        }
+        for token in template_tokens(template) {
+            if !token_allowed(&token, allowed) {
+                issues.push(ConfigIssue::error(field, format!("未知的模板字段: {}", token)));
+            }
+        }
+    }
+    if !config.season_folder_template.trim().is_empty() && !has_season_token(&config.season_folder_template) {
+        issues.push(ConfigIssue::error("season_folder_template", "模板中没有季度字段，所有季度将使用同一个文件夹".to_string()));
+    }
+    
+    // 并发设置，concurrent_limit 为 0 表示使用所有核心
+    for (field, value) in [
+        ("ssd_concurrency", config.ssd_concurrency),
+        ("hdd_concurrency", config.hdd_concurrency),
+        ("metadata_concurrent_limit", config.metadata_concurrent_limit),
+    ] {
+        if value == 0 {
+            issues.push(ConfigIssue::error(field, "并发数必须大于 0".to_string()));
+        }
+    }
+    
+    if !LOG_LEVELS.contains(&config.log_level.to_lowercase().as_str()) {
+        issues.push(ConfigIssue::error("log_level", format!("无效的日志级别: {}，可选值: {}", config.log_level, LOG_LEVELS.join(", "))));
+    }
+    
+    if let Some(pattern) = config.episode_regex.as_deref().filter(|pattern| !pattern.trim().is_empty()) {
+        if let Err(e) = episode_from_regex("", pattern) {
+            issues.push(ConfigIssue::error("episode_regex", e));
+        }
+    }
+    
+    if !EXTENSION_CHANGE_POLICIES.contains(&config.extension_change_policy.as_str()) {
+        issues.push(ConfigIssue::error("extension_change_policy", format!("无效的扩展名变更策略: {}，可选值: {}", config.extension_change_policy, EXTENSION_CHANGE_POLICIES.join(", "))));
+    }
+    
+    if let Err(e) = LinkMode::parse(&config.link_mode) {
+        issues.push(ConfigIssue::error("link_mode", e));
+    }
+    
+    if !EXISTING_FILE_CHECKS.contains(&config.existing_file_check.as_str()) {
+        issues.push(ConfigIssue::error("existing_file_check", format!("无效的已存在文件检查策略: {}，可选值: {}", config.existing_file_check, EXISTING_FILE_CHECKS.join(", "))));
+    }
+    
+    if !LINE_ENDINGS.contains(&config.line_ending.as_str()) {
+        issues.push(ConfigIssue::error("line_ending", format!("无效的换行符设置: {}，可选值: {}", config.line_ending, LINE_ENDINGS.join(", "))));
+    }
+    
+    if !DUPLICATE_RESOLUTIONS.contains(&config.duplicate_resolution.as_str()) {
+        issues.push(ConfigIssue::error("duplicate_resolution", format!("无效的重复集数处理策略: {}，可选值: {}", config.duplicate_resolution, DUPLICATE_RESOLUTIONS.join(", "))));
+    }
+    
+    if !(0.0..=1.0).contains(&config.auto_match_threshold) {
+        issues.push(ConfigIssue::error("auto_match_threshold", "自动匹配阈值必须在 0 到 1 之间".to_string()));
+    }
+    
+    // 输出目录：只检查，不创建
+    let output_dir = PathBuf::from(&config.output_directory);
+    if config.output_directory.trim().is_empty() {
+        issues.push(ConfigIssue::error("output_directory", "输出目录不能为空".to_string()));
+    } else if output_dir.exists() {
+        if !output_dir.is_dir() {
+            issues.push(ConfigIssue::error("output_directory", "输出路径已存在且不是文件夹".to_string()));
+        } else {
+            let test_file = output_dir.join(".write_test");
+            match std::fs::write(&test_file, "test") {
+                Ok(_) => {
+                    let _ = std::fs::remove_file(&test_file);
+                }
+                Err(e) => issues.push(ConfigIssue::error("output_directory", format!("输出目录无写权限: {}", e))),
+            }
+        }
+    } else if output_dir.ancestors().skip(1).any(|ancestor| ancestor.is_dir()) {
+        issues.push(ConfigIssue::warning("output_directory", "输出目录不存在，将在首次处理时创建".to_string()));
+    } else {
+        issues.push(ConfigIssue::error("output_directory", "输出目录无法解析到任何已存在的位置".to_string()));
+    }
+    
+    // 数据源
+    if !config.anilist_enabled && !config.tmdb_enabled {
+        issues.push(ConfigIssue::warning("anilist_enabled", "没有启用任何元数据数据源，无法自动匹配".to_string()));
+    }
+    if config.tmdb_enabled && config.tmdb_api_key.as_deref().is_none_or(|key| key.trim().is_empty()) {
+        issues.push(ConfigIssue::warning("tmdb_api_key", "已启用 TMDB 数据源但未填写 API 密钥，TMDB 查询会失败".to_string()));
+    }
+    if let Err(e) = check_provider_order(&config.provider_order) {
+        issues.push(ConfigIssue::error("provider_order", e));
+    }
+    
+    if config.template_separators.iter().any(|sep| sep.trim().is_empty()) {
+        issues.push(ConfigIssue::warning("template_separators", "分隔符列表中包含空白项，将被忽略".to_string()));
+    }
+    
+    for (series, seasons) in &config.episode_offsets {
+        for (season, offset) in seasons {
+            if *offset < 0 {
+                issues.push(ConfigIssue::error("episode_offsets", format!("{} 第 {} 季的集数偏移不能为负数: {}", series, season, offset)));
+            }
+        }
+    }
+    
+    if config.video_extensions.iter().all(|ext| ext.trim().is_empty()) {
+        issues.push(ConfigIssue::error("video_extensions", "视频扩展名列表不能为空".to_string()));
+    }
+    if let Some(ext) = config.subtitle_extensions.iter().find(|ext| extension_listed(&config.video_extensions, ext.trim().trim_start_matches('.'))) {
+        issues.push(ConfigIssue::warning("subtitle_extensions", format!("扩展名 {} 同时出现在视频扩展名中，将按视频处理", ext)));
+    }
+    
+    Ok(issues)
+}
+
+#[command]
+pub async fn get_default_directories() -> Result<Vec<String>, AppError> {
     let mut directories = Vec::new();
     
     if let Some(videos_dir) = dirs::video_dir() {
@@ -194,12 +775,24 @@ pub async fn get_default_directories() -> Result<Vec<String>, String> {
     Ok(directories)
 }
 
-fn get_config_path() -> Result<PathBuf, String> {
-    let config_dir = dirs::config_dir()
+// 供后端命令读取当前配置，读取或解析失败时使用默认配置
+pub fn read_app_config() -> AppConfig {
+    get_config_path()
+        .ok()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<AppConfig>(&content).ok())
+        .unwrap_or_default()
+}
+
+// 应用配置目录，存放 config.json 及其他用户自定义文件
+pub fn get_config_dir() -> Result<PathBuf, String> {
+    Ok(dirs::config_dir()
         .ok_or("无法获取配置目录")?
-        .join("anime-file-manager");
-    
-    Ok(config_dir.join("config.json"))
+        .join("anime-file-manager"))
+}
+
+pub(crate) fn get_config_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join("config.json"))
 }
 
 #[command]
@@ -209,14 +802,16 @@ pub async fn preview_naming(
     episode: u32,
     group: Option<String>,
     year: Option<u32>,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
     let mut result = template;
     
     result = result.replace("{title}", &anime_title);
     result = result.replace("{title_romaji}", &anime_title);
+    result = replace_padded_number(&result, "episode", Some(episode));
     result = result.replace("{episode}", &format!("{:02}", episode));
-    result = result.replace("{episode:02}", &format!("{:02}", episode));
-    result = result.replace("{episode:03}", &format!("{:03}", episode));
+    let episode_abs = absolute_episode(episode, episode_offset_for(&read_app_config(), &[&anime_title], None));
+    result = replace_padded_number(&result, "episode_abs", Some(episode_abs));
+    result = result.replace("{episode_abs}", &format!("{:02}", episode_abs));
     
     if let Some(group_name) = group {
         result = result.replace("{group}", &group_name);
@@ -232,5 +827,5 @@ pub async fn preview_naming(
     
     result = result.replace("{ext}", "mkv");
     
-    Ok(result)
+    Ok(normalize_rendered_name(&result, &read_app_config().template_separators))
 }
\ No newline at end of file
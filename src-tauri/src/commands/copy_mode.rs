@@ -0,0 +1,250 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{command, AppHandle, State};
+use tracing::{info, warn, error};
+use crate::commands::config::read_app_config;
+use crate::commands::devices::process_grouped_by_device;
+use crate::commands::history::record_operation;
+use crate::commands::file_operations::{
+    FileError, ProcessResult, LinkAction, LinkOutcome, sanitize_filename, ensure_output_dir,
+    incomplete_source_reason, place_subtitle_target, unique_target_path, sync_link_to_disk, write_batch_manifest, copy_or_clean_up,
+};
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_BATCH_COPY_FILES};
+use crate::commands::manifest::build_manifest_entry;
+use crate::commands::throughput::ThroughputTracker;
+use crate::commands::errors::AppError;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CopyOptions {
+    // 保留源文件的修改和访问时间，默认开启
+    pub preserve_timestamps: Option<bool>,
+    // 复制后校验目标文件大小，默认开启
+    pub verify: Option<bool>,
+    // 目标已存在时的处理方式：skip / overwrite / rename，默认 skip
+    pub conflict_strategy: Option<String>,
+}
+
+// 复制模式：始终复制文件而不创建硬链接，适用于 exFAT 等不支持硬链接或需要独立副本的目标
+#[command]
+pub async fn batch_copy_files(
+    files: Vec<String>,
+    output_dir: String,
+    rename_map: Option<HashMap<String, String>>,
+    options: Option<CopyOptions>,
+    app: AppHandle,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_BATCH_COPY_FILES);
+    let options = options.unwrap_or_default();
+    let preserve_timestamps = options.preserve_timestamps.unwrap_or(true);
+    let verify = options.verify.unwrap_or(true);
+    let conflict_strategy = options.conflict_strategy.unwrap_or_else(|| "skip".to_string());
+    let rename_map = rename_map.unwrap_or_default();
+    
+    if !matches!(conflict_strategy.as_str(), "skip" | "overwrite" | "rename") {
+        return Err(format!("不支持的冲突处理策略: {}", conflict_strategy).into());
+    }
+    
+    info!("开始批量复制 {} 个文件到目录: {}", files.len(), output_dir);
+    log.info(format!("开始批量复制 {} 个文件到目录: {}", files.len(), output_dir));
+    
+    let sanitized_output_dir = ensure_output_dir(&PathBuf::from(&output_dir))
+        .map_err(|e| e.to_string())?;
+    
+    // 复制前检查目标磁盘剩余空间
+    let required: u64 = files.iter()
+        .filter_map(|file_path| fs::metadata(file_path).ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    let available = fs2::available_space(&sanitized_output_dir)
+        .map_err(|e| format!("获取目标磁盘剩余空间失败: {}", e))?;
+    if required > available {
+        log.error(format!("目标磁盘空间不足: 需要 {} 字节, 剩余 {} 字节", required, available));
+        return Err(format!("目标磁盘空间不足: 需要 {:.2} GB, 剩余 {:.2} GB",
+            required as f64 / 1024f64.powi(3), available as f64 / 1024f64.powi(3)).into());
+    }
+    
+    let processed_files = Arc::new(Mutex::new(Vec::new()));
+    let failed_files = Arc::new(Mutex::new(Vec::new()));
+    let skipped_files = Arc::new(Mutex::new(Vec::new()));
+    let manifest_entries = Arc::new(Mutex::new(Vec::new()));
+    let config = read_app_config();
+    // 定期通过 throughput 事件报告复制速率和预计剩余时间
+    let tracker = ThroughputTracker::new(app, SOURCE_BATCH_COPY_FILES, &files);
+    
+    process_grouped_by_device(&files, &config, |file_path| {
+        let source = PathBuf::from(file_path);
+        let mut pending = tracker.begin(&source);
+        
+        // 跳过空文件和未下载完成的文件
+        if let Some(reason) = incomplete_source_reason(&source, &config, None) {
+            skipped_files.lock().unwrap().push(FileError {
+                path: file_path.clone(),
+                error: reason,
+            });
+            warn!("跳过未完成的文件: {}", file_path);
+            return;
+        }
+        
+        let target_name = match rename_map.get(file_path) {
+            Some(new_name) => new_name.replace('\\', "/")
+                .split('/')
+                .map(sanitize_filename)
+                .collect::<Vec<_>>()
+                .join("/"),
+            None => match source.file_name() {
+                Some(name) => sanitize_filename(&name.to_string_lossy()),
+                None => {
+                    failed_files.lock().unwrap().push(FileError {
+                        path: file_path.clone(),
+                        error: "无效的文件名".to_string(),
+                    });
+                    return;
+                }
+            }
+        };
+        let mut target = place_subtitle_target(sanitized_output_dir.join(target_name), &config);
+        
+        // 处理目标已存在的情况
+        if target.exists() {
+            match conflict_strategy.as_str() {
+                "overwrite" => {
+                    if let Err(e) = fs::remove_file(&target) {
+                        failed_files.lock().unwrap().push(FileError {
+                            path: file_path.clone(),
+                            error: format!("删除已存在的文件失败: {}", e),
+                        });
+                        return;
+                    }
+                }
+                "rename" => match unique_target_path(&target) {
+                    Some(unique) => target = unique,
+                    None => {
+                        failed_files.lock().unwrap().push(FileError {
+                            path: file_path.clone(),
+                            error: "无法生成唯一的文件名".to_string(),
+                        });
+                        return;
+                    }
+                },
+                _ => {
+                    skipped_files.lock().unwrap().push(FileError {
+                        path: file_path.clone(),
+                        error: format!("目标文件已存在: {}", target.display()),
+                    });
+                    return;
+                }
+            }
+        }
+        
+        match copy_file(&source, &target, preserve_timestamps, verify) {
+            Ok(_) => {
+                let outcome = LinkOutcome { target, action: LinkAction::Copy };
+                if config.fsync_dirs {
+                    if let Err(e) = sync_link_to_disk(&outcome) {
+                        error!("同步到磁盘失败: {}, 错误: {}", outcome.target.display(), e);
+                        failed_files.lock().unwrap().push(FileError {
+                            path: file_path.clone(),
+                            error: format!("已复制但同步到磁盘失败: {}", e),
+                        });
+                        return;
+                    }
+                }
+                if config.write_manifest {
+                    manifest_entries.lock().unwrap().push(build_manifest_entry(&source, &outcome.target, &sanitized_output_dir, outcome.action.as_str()));
+                }
+                pending.succeeded(outcome.action);
+                processed_files.lock().unwrap().push(file_path.clone());
+            }
+            Err(e) => {
+                warn!("文件复制失败: {}, 错误: {}", file_path, e);
+                failed_files.lock().unwrap().push(FileError {
+                    path: file_path.clone(),
+                    error: e,
+                });
+            }
+        }
+    });
+    tracker.finish();
+    
+    let processed = Arc::try_unwrap(processed_files).unwrap().into_inner().unwrap();
+    let failed = Arc::try_unwrap(failed_files).unwrap().into_inner().unwrap();
+    let skipped = Arc::try_unwrap(skipped_files).unwrap().into_inner().unwrap();
+    let manifest_entries = Arc::try_unwrap(manifest_entries).unwrap().into_inner().unwrap();
+    
+    if config.write_manifest {
+        write_batch_manifest(&log, &sanitized_output_dir, manifest_entries, &config);
+    }
+    
+    let success_count = processed.len();
+    let failed_count = failed.len();
+    let skipped_count = skipped.len();
+    let total_count = files.len();
+    
+    info!("批量复制完成: 成功 {}, 失败 {}, 跳过 {}, 总计 {}", success_count, failed_count, skipped_count, total_count);
+    log.info(format!("批量复制完成: 成功 {}, 失败 {}, 跳过 {}, 总计 {}", success_count, failed_count, skipped_count, total_count));
+    for failed_file in &failed {
+        log.error(format!("文件复制失败: {} - {}", failed_file.path, failed_file.error));
+    }
+    
+    let result = ProcessResult {
+        success: failed_count == 0,
+        message: format!("复制完成: 成功 {}/{}, 失败 {}, 跳过 {}", success_count, total_count, failed_count, skipped_count),
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: skipped,
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    record_operation(SOURCE_BATCH_COPY_FILES, &output_dir, &result, &config);
+    
+    Ok(result)
+}
+
+// 复制单个文件，可选保留时间戳并校验大小，校验失败时删除不完整的副本
+fn copy_file(source: &Path, target: &Path, preserve_timestamps: bool, verify: bool) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    
+    copy_or_clean_up(source, target)
+        .map_err(|e| format!("复制失败: {}", e))?;
+    
+    if verify {
+        let source_size = fs::metadata(source)
+            .map_err(|e| format!("读取源文件信息失败: {}", e))?
+            .len();
+        let target_size = fs::metadata(target)
+            .map_err(|e| format!("读取目标文件信息失败: {}", e))?
+            .len();
+        if source_size != target_size {
+            let _ = fs::remove_file(target);
+            return Err(format!("校验失败: 源文件 {} 字节, 目标文件 {} 字节", source_size, target_size));
+        }
+    }
+    
+    if preserve_timestamps {
+        if let Err(e) = copy_timestamps(source, target) {
+            warn!("保留时间戳失败: {}, 错误: {}", target.display(), e);
+        }
+    }
+    
+    Ok(())
+}
+
+// 将源文件的修改和访问时间写入目标文件
+fn copy_timestamps(source: &Path, target: &Path) -> std::io::Result<()> {
+    let metadata = fs::metadata(source)?;
+    let mut times = FileTimes::new().set_modified(metadata.modified()?);
+    if let Ok(accessed) = metadata.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    File::options().write(true).open(target)?.set_times(times)
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::path::Path;
+use rayon::prelude::*;
+use tracing::{info, warn};
+use crate::commands::config::AppConfig;
+
+// 源文件所在设备的类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviceKind {
+    Rotational,
+    NonRotational,
+    Unknown,
+}
+
+// 获取路径所在的设备号，无法获取时返回 None
+#[cfg(unix)]
+pub fn device_id(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+pub fn device_id(_path: &Path) -> Option<u64> {
+    None
+}
+
+// 通过 /sys/dev/block 判断设备是否为机械硬盘，分区需要查看其所属磁盘的队列信息
+#[cfg(target_os = "linux")]
+pub fn device_kind(device: u64) -> DeviceKind {
+    // 与 glibc 的 major()/minor() 编码一致
+    let major = ((device >> 32) & 0xffff_f000) | ((device >> 8) & 0x0000_0fff);
+    let minor = ((device >> 12) & 0xffff_ff00) | (device & 0x0000_00ff);
+    
+    let block_dir = match std::fs::canonicalize(format!("/sys/dev/block/{}:{}", major, minor)) {
+        Ok(dir) => dir,
+        Err(_) => return DeviceKind::Unknown,
+    };
+    
+    let rotational = [block_dir.join("queue/rotational"), block_dir.join("../queue/rotational")]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok());
+    
+    match rotational.as_deref().map(str::trim) {
+        Some("1") => DeviceKind::Rotational,
+        Some("0") => DeviceKind::NonRotational,
+        _ => DeviceKind::Unknown,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn device_kind(_device: u64) -> DeviceKind {
+    DeviceKind::Unknown
+}
+
+// 设备类型对应的并发数，0 表示使用所有核心
+fn concurrency_for(kind: DeviceKind, config: &AppConfig) -> usize {
+    match kind {
+        DeviceKind::Rotational => config.hdd_concurrency.max(1),
+        DeviceKind::NonRotational => config.ssd_concurrency.max(1),
+        DeviceKind::Unknown => config.concurrent_limit,
+    }
+}
+
+// 在指定并发数的独立线程池中执行，并发数为 0 时直接使用 rayon 全局线程池（所有核心）
+fn run_with_threads<F>(threads: usize, job: F)
+where
+    F: FnOnce() + Send,
+{
+    if threads == 0 {
+        job();
+        return;
+    }
+    
+    match rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+        Ok(pool) => pool.install(job),
+        Err(e) => {
+            warn!("创建线程池失败，使用默认并发: {}", e);
+            job();
+        }
+    }
+}
+
+// 批量处理的执行入口
+// 开启按设备分组时，依次处理每个源设备上的文件，每组使用该设备类型的并发数；否则按 concurrent_limit 并行处理
+pub fn process_grouped_by_device<F>(files: &[String], config: &AppConfig, process: F)
+where
+    F: Fn(&String) + Sync + Send,
+{
+    if !config.group_by_device {
+        run_with_threads(config.concurrent_limit, || files.par_iter().for_each(&process));
+        return;
+    }
+    
+    let mut groups: HashMap<Option<u64>, Vec<&String>> = HashMap::new();
+    for file_path in files {
+        groups.entry(device_id(Path::new(file_path))).or_default().push(file_path);
+    }
+    
+    for (device, group) in groups {
+        let kind = device.map(device_kind).unwrap_or(DeviceKind::Unknown);
+        let threads = concurrency_for(kind, config);
+        info!("处理设备 {:?} ({:?}) 上的 {} 个文件，并发数: {}", device, kind, group.len(), threads);
+        
+        run_with_threads(threads, || group.par_iter().for_each(|file_path| process(file_path)));
+    }
+}
@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Instant;
+use tauri::command;
+use tracing::{info, warn};
+use crate::commands::config::{get_config_dir, get_config_path, read_app_config};
+use crate::commands::devices::device_id;
+use crate::commands::file_operations::{
+    CHAR_MAP_FILE_NAME, FileError, LinkMode, copy_forbidden_extension, is_same_filesystem, preview_target,
+    sanitize_path, with_default_extension,
+};
+use crate::commands::metadata::AniListResponse;
+use crate::commands::history::get_history_path;
+use crate::commands::errors::AppError;
+
+// 运行时使用的路径和版本信息，便于附在问题报告中
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Diagnostics {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub config_dir: String,
+    pub config_path: String,
+    pub config_exists: bool,
+    pub char_map_path: String,
+    pub char_map_exists: bool,
+    pub history_path: String,
+    // 关键依赖在构建时解析到的版本
+    pub library_versions: HashMap<String, String>,
+}
+
+#[command]
+pub async fn get_diagnostics() -> Result<Diagnostics, AppError> {
+    let config_dir = get_config_dir()?;
+    let config_path = get_config_path()?;
+    let char_map_path = config_dir.join(CHAR_MAP_FILE_NAME);
+    
+    let library_versions = HashMap::from([
+        ("tauri".to_string(), option_env!("LOCKED_VERSION_TAURI").unwrap_or("unknown").to_string()),
+        ("anitomy".to_string(), option_env!("LOCKED_VERSION_ANITOMY").unwrap_or("unknown").to_string()),
+        ("reqwest".to_string(), option_env!("LOCKED_VERSION_REQWEST").unwrap_or("unknown").to_string()),
+    ]);
+    
+    Ok(Diagnostics {
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        config_dir: config_dir.to_string_lossy().to_string(),
+        config_exists: config_path.exists(),
+        config_path: config_path.to_string_lossy().to_string(),
+        char_map_exists: char_map_path.exists(),
+        char_map_path: char_map_path.to_string_lossy().to_string(),
+        history_path: get_history_path()?.to_string_lossy().to_string(),
+        library_versions,
+    })
+}
+
+// 测速文件大小和硬链接次数
+const BENCHMARK_FILE_SIZE: usize = 16 * 1024 * 1024;
+const BENCHMARK_LINK_COUNT: usize = 20;
+
+// 目标卷上硬链接与复制的实测速度
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VolumeBenchmark {
+    pub path: String,
+    pub hardlink_supported: bool,
+    // 创建一个硬链接的平均耗时（毫秒），不支持硬链接时为空
+    pub hardlink_ms: Option<f64>,
+    pub copy_bytes: u64,
+    // 复制测速文件（含同步到磁盘）的耗时（毫秒）
+    pub copy_ms: f64,
+    pub copy_mb_per_second: f64,
+    // 硬链接失败时的错误信息
+    pub hardlink_error: Option<String>,
+}
+
+// 在目标目录中创建临时文件，分别测量硬链接和复制的耗时，帮助用户在硬链接和复制模式间取舍
+// 所有临时文件放在单独的隐藏目录中，测速结束后无论成功与否都会删除
+#[command]
+pub async fn benchmark_volume(path: String) -> Result<VolumeBenchmark, AppError> {
+    let dir = Path::new(&path);
+    if !dir.is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", path)));
+    }
+    
+    let bench_dir = dir.join(format!(".volume-benchmark-{}", uuid::Uuid::new_v4().simple()));
+    let task_dir = bench_dir.clone();
+    let result = tokio::task::spawn_blocking(move || run_benchmark(&task_dir))
+        .await
+        .map_err(|e| format!("测速任务异常: {}", e))?;
+    
+    if let Err(e) = fs::remove_dir_all(&bench_dir) {
+        if e.kind() != io::ErrorKind::NotFound {
+            warn!("删除测速临时目录失败: {}, 错误: {}", bench_dir.display(), e);
+        }
+    }
+    
+    let mut benchmark = result.map_err(|e| format!("测速失败: {}", e))?;
+    benchmark.path = path;
+    info!("目标卷测速完成: 硬链接 {:?} ms, 复制 {:.1} MB/s", benchmark.hardlink_ms, benchmark.copy_mb_per_second);
+    
+    Ok(benchmark)
+}
+
+fn run_benchmark(bench_dir: &Path) -> io::Result<VolumeBenchmark> {
+    fs::create_dir(bench_dir)?;
+    
+    let source = bench_dir.join("source.bin");
+    let data: Vec<u8> = (0..BENCHMARK_FILE_SIZE).map(|i| (i % 251) as u8).collect();
+    let mut file = fs::File::create(&source)?;
+    file.write_all(&data)?;
+    file.sync_all()?;
+    drop(file);
+    
+    let started = Instant::now();
+    let mut hardlink_error = None;
+    for index in 0..BENCHMARK_LINK_COUNT {
+        if let Err(e) = fs::hard_link(&source, bench_dir.join(format!("link-{}.bin", index))) {
+            hardlink_error = Some(e.to_string());
+            break;
+        }
+    }
+    let hardlink_ms = hardlink_error.is_none()
+        .then(|| started.elapsed().as_secs_f64() * 1000.0 / BENCHMARK_LINK_COUNT as f64);
+    
+    let copy_target = bench_dir.join("copy.bin");
+    let started = Instant::now();
+    let copy_bytes = fs::copy(&source, &copy_target)?;
+    fs::OpenOptions::new().write(true).open(&copy_target)?.sync_all()?;
+    let copy_seconds = started.elapsed().as_secs_f64();
+    
+    Ok(VolumeBenchmark {
+        path: String::new(),
+        hardlink_supported: hardlink_error.is_none(),
+        hardlink_ms,
+        copy_bytes,
+        copy_ms: copy_seconds * 1000.0,
+        copy_mb_per_second: if copy_seconds > 0.0 { copy_bytes as f64 / 1024.0 / 1024.0 / copy_seconds } else { 0.0 },
+        hardlink_error,
+    })
+}
+
+// 批量处理前按源驱动器汇总的链接可行性
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DrivePreflight {
+    // 该驱动器上第一个文件所在的文件夹，便于用户辨认
+    pub sample_dir: String,
+    pub same_filesystem: bool,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PreflightConflict {
+    pub source: String,
+    pub target: String,
+}
+
+// 批量处理前的空间和链接可行性预估，供界面在确认前展示
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchPreflight {
+    pub link_mode: String,
+    pub drives: Vec<DrivePreflight>,
+    // 会创建硬链接或符号链接、不占用额外空间的文件
+    pub linked_files: usize,
+    pub linked_bytes: u64,
+    // 复制模式下会被复制、占用等量空间的文件
+    pub copied_files: usize,
+    pub copied_bytes: u64,
+    // 实际会占用的空间，即 copied_bytes
+    pub required_bytes: u64,
+    // 输出目录所在卷的可用空间，无法获取时为空
+    pub available_bytes: Option<u64>,
+    // 执行时会失败的文件：硬链接模式下与输出目录不在同一文件系统，或复制模式下配置禁止复制
+    pub unlinkable_files: Vec<FileError>,
+    pub existing_targets: Vec<PreflightConflict>,
+    pub missing_files: Vec<String>,
+    // 传入的是文件夹而不是文件，批量处理不会处理
+    pub directories: Vec<String>,
+}
+
+// 预估批量处理会链接、复制还是失败的文件，实际占用多少空间，以及哪些目标已存在
+// 每个源驱动器只检查一次是否与输出目录在同一文件系统；目标路径的计算与 preview_file_processing 相同
+#[command]
+pub async fn preflight_batch(
+    files: Vec<String>,
+    output_dir: String,
+    rename_map: Option<HashMap<String, String>>,
+    anime_metadata: Option<AniListResponse>
+) -> Result<BatchPreflight, AppError> {
+    let config = read_app_config();
+    let mode = LinkMode::parse(&config.link_mode).unwrap_or_default();
+    let rename_map = rename_map.unwrap_or_default();
+    let sanitized_output_dir = sanitize_path(&PathBuf::from(&output_dir));
+    
+    // 输出目录可能尚未创建，文件系统和可用空间按最近的已存在上级目录检查
+    let existing_output_dir = sanitized_output_dir.ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .map(Path::to_path_buf);
+    let available_bytes = existing_output_dir.as_ref()
+        .and_then(|dir| fs2::available_space(dir).ok());
+    
+    let mut drives: Vec<(String, DrivePreflight)> = Vec::new();
+    let mut preflight = BatchPreflight {
+        link_mode: config.link_mode.clone(),
+        drives: Vec::new(),
+        linked_files: 0,
+        linked_bytes: 0,
+        copied_files: 0,
+        copied_bytes: 0,
+        required_bytes: 0,
+        available_bytes,
+        unlinkable_files: Vec::new(),
+        existing_targets: Vec::new(),
+        missing_files: Vec::new(),
+        directories: Vec::new(),
+    };
+    
+    for file_path in files {
+        let source = PathBuf::from(&file_path);
+        let size = match fs::metadata(&source) {
+            Ok(metadata) if metadata.is_dir() => {
+                preflight.directories.push(file_path);
+                continue;
+            }
+            Ok(metadata) => metadata.len(),
+            Err(_) => {
+                preflight.missing_files.push(file_path);
+                continue;
+            }
+        };
+        
+        let key = drive_key(&source);
+        let index = match drives.iter().position(|(existing, _)| *existing == key) {
+            Some(index) => index,
+            None => {
+                let same_filesystem = match &existing_output_dir {
+                    // is_same_filesystem 比较的是目标的上级目录
+                    Some(dir) => is_same_filesystem(&source, &dir.join("preflight")).unwrap_or_else(|e| {
+                        warn!("检查文件系统失败: {}, 错误: {}", source.display(), e);
+                        false
+                    }),
+                    None => true,
+                };
+                drives.push((key, DrivePreflight {
+                    sample_dir: source.parent().unwrap_or(&source).to_string_lossy().to_string(),
+                    same_filesystem,
+                    file_count: 0,
+                    total_bytes: 0,
+                }));
+                drives.len() - 1
+            }
+        };
+        let drive = &mut drives[index].1;
+        drive.file_count += 1;
+        drive.total_bytes += size;
+        
+        // 与 link_in_target_dir 一致：硬链接跨文件系统时直接失败而不是复制，只有复制模式会占用空间
+        let failure = match mode {
+            LinkMode::HardLink if !drive.same_filesystem => Some("源文件与输出目录不在同一文件系统上，无法创建硬链接".to_string()),
+            LinkMode::Copy => copy_forbidden_extension(&source, &config)
+                .map(|extension| format!("配置禁止复制 .{} 文件", extension)),
+            _ => None,
+        };
+        match failure {
+            Some(error) => preflight.unlinkable_files.push(FileError {
+                path: file_path.clone(),
+                error,
+            }),
+            None if mode == LinkMode::Copy => {
+                preflight.copied_files += 1;
+                preflight.copied_bytes += size;
+            }
+            None => {
+                preflight.linked_files += 1;
+                preflight.linked_bytes += size;
+            }
+        }
+        
+        let target = preview_target(&file_path, &sanitized_output_dir, &rename_map, anime_metadata.as_ref(), &config);
+        let target = sanitize_path(&with_default_extension(&source, target, &config));
+        if target.exists() {
+            preflight.existing_targets.push(PreflightConflict {
+                source: file_path,
+                target: target.to_string_lossy().to_string(),
+            });
+        }
+    }
+    
+    preflight.required_bytes = preflight.copied_bytes;
+    preflight.drives = drives.into_iter().map(|(_, drive)| drive).collect();
+    info!(
+        "批量预检完成: 链接 {} 个文件, 复制 {} 个文件 ({} 字节), 无法处理 {}, 目标已存在 {}",
+        preflight.linked_files, preflight.copied_files, preflight.copied_bytes,
+        preflight.unlinkable_files.len(), preflight.existing_targets.len()
+    );
+    
+    Ok(preflight)
+}
+
+// 源文件所在驱动器的标识：Unix 下为设备号，Windows 下为盘符
+fn drive_key(path: &Path) -> String {
+    if let Some(device) = device_id(path) {
+        return device.to_string();
+    }
+    match path.components().next() {
+        Some(Component::Prefix(prefix)) => prefix.as_os_str().to_string_lossy().to_uppercase(),
+        _ => String::new(),
+    }
+}
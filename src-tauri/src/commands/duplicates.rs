@@ -0,0 +1,383 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use lazy_static::lazy_static;
+use rayon::prelude::*;
+use regex::Regex;
+use tauri::command;
+use tracing::warn;
+use walkdir::WalkDir;
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::file_operations::{FileError, FileKind, is_same_file};
+use crate::commands::library::file_content_hash;
+use crate::commands::metadata::parse_path_internal;
+use crate::commands::errors::AppError;
+
+lazy_static! {
+    // 1080p / 720i / 2160P
+    static ref HEIGHT_RE: Regex = Regex::new(r"(?i)(\d{3,4})[pi]").unwrap();
+    // 1920x1080 / 1280×720
+    static ref DIMENSIONS_RE: Regex = Regex::new(r"(?i)\d{3,4}\s*[x×]\s*(\d{3,4})").unwrap();
+}
+
+// 同一部动漫同一季同一集对应多个文件时的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateEpisodeGroup {
+    pub anime_title: String,
+    pub season: u32,
+    pub episode: u32,
+    pub winner: String,
+    // 按策略不再处理的文件
+    pub dropped: Vec<String>,
+    // keep_all 策略下目标文件名重复、需要追加序号的文件 -> 新的重命名目标
+    pub suffixed: HashMap<String, String>,
+}
+
+// 检测输入文件中解析为同一集的视频，并按配置的 duplicate_resolution 给出保留结果，供处理前预览
+#[command]
+pub async fn detect_duplicate_episodes(
+    files: Vec<String>,
+    rename_map: Option<HashMap<String, String>>
+) -> Result<Vec<DuplicateEpisodeGroup>, AppError> {
+    let config = read_app_config();
+    Ok(resolve_duplicate_episodes(&files, &rename_map.unwrap_or_default(), &config))
+}
+
+// 批量处理前应用重复集数策略：被舍弃的文件返回为跳过记录，追加序号的文件直接写回重命名映射
+pub(crate) fn apply_duplicate_resolution(
+    files: &[String],
+    rename_map: &mut HashMap<String, String>,
+    config: &AppConfig
+) -> (Vec<DuplicateEpisodeGroup>, Vec<FileError>) {
+    let groups = resolve_duplicate_episodes(files, rename_map, config);
+    
+    let mut dropped = Vec::new();
+    for group in &groups {
+        for file_path in &group.dropped {
+            dropped.push(FileError {
+                path: file_path.clone(),
+                error: format!("重复集数 ({} S{:02}E{:02})，已保留: {}", group.anime_title, group.season, group.episode, group.winner),
+            });
+        }
+        rename_map.extend(group.suffixed.clone());
+    }
+    
+    (groups, dropped)
+}
+
+// 批量处理前检查输出目录中是否已有内容相同的文件（可能以其他名称放在其他文件夹中），
+// 按配置 existing_file_check 返回 (跳过记录, 警告)，off 时不检查
+pub(crate) fn check_existing_in_output(files: &[String], output_root: &Path, config: &AppConfig) -> (Vec<FileError>, Vec<FileError>) {
+    if config.existing_file_check == "off" || !output_root.is_dir() {
+        return (Vec::new(), Vec::new());
+    }
+    
+    // 先按大小建立索引，只对大小相同的文件计算内容哈希
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for entry in WalkDir::new(output_root).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata() {
+            by_size.entry(metadata.len()).or_default().push(entry.into_path());
+        }
+    }
+    
+    let hash_cache: Mutex<HashMap<PathBuf, u64>> = Mutex::new(HashMap::new());
+    let cached_hash = |path: &Path| -> Option<u64> {
+        if let Some(hash) = hash_cache.lock().unwrap().get(path) {
+            return Some(*hash);
+        }
+        match file_content_hash(path) {
+            Ok(hash) => {
+                hash_cache.lock().unwrap().insert(path.to_path_buf(), hash);
+                Some(hash)
+            }
+            Err(e) => {
+                warn!("计算文件哈希失败: {}, 错误: {}", path.display(), e);
+                None
+            }
+        }
+    };
+    
+    let found: Vec<FileError> = files.par_iter()
+        .filter_map(|file_path| {
+            let source = Path::new(file_path);
+            // 空文件彼此内容相同，不作比较
+            let size = fs::metadata(source).ok().map(|metadata| metadata.len()).filter(|size| *size > 0)?;
+            let candidates = by_size.get(&size)?;
+            
+            // 已经是源文件的硬链接时无需比较内容
+            let existing = candidates.iter().find(|candidate| is_same_file(source, candidate))
+                .or_else(|| {
+                    let source_hash = file_content_hash(source).ok()?;
+                    candidates.iter().find(|candidate| cached_hash(candidate) == Some(source_hash))
+                })?;
+            
+            Some(FileError {
+                path: file_path.clone(),
+                error: format!("输出目录中已存在内容相同的文件: {}", existing.display()),
+            })
+        })
+        .collect();
+    
+    if config.existing_file_check == "skip" {
+        (found, Vec::new())
+    } else {
+        (Vec::new(), found)
+    }
+}
+
+// 部分哈希读取文件开头和结尾的字节数
+const PARTIAL_HASH_BYTES: u64 = 64 * 1024;
+
+// 大小相同且开头、结尾内容相同的一组文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateFileCluster {
+    pub size: u64,
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateFilesResult {
+    // 按文件大小从大到小排列
+    pub clusters: Vec<DuplicateFileCluster>,
+    // 空文件彼此内容相同，单独列出，不作为重复文件
+    pub zero_byte_files: Vec<String>,
+    // 无法读取的文件
+    pub failed: Vec<FileError>,
+}
+
+// 查找目录中可能重复的文件：先按大小分组，再比较开头和结尾各 64KB 的哈希，供处理前选择保留哪一个
+#[command]
+pub async fn find_duplicate_files(directory: String) -> Result<DuplicateFilesResult, AppError> {
+    let root = Path::new(&directory);
+    if !root.is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", directory)));
+    }
+    
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut zero_byte_files = Vec::new();
+    for entry in WalkDir::new(root).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        match entry.metadata() {
+            Ok(metadata) if metadata.len() == 0 => zero_byte_files.push(entry.path().to_string_lossy().to_string()),
+            Ok(metadata) => by_size.entry(metadata.len()).or_default().push(entry.into_path()),
+            Err(e) => warn!("读取文件信息失败: {}, 错误: {}", entry.path().display(), e),
+        }
+    }
+    
+    let candidates: Vec<(u64, PathBuf)> = by_size.into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .flat_map(|(size, paths)| paths.into_iter().map(move |path| (size, path)))
+        .collect();
+    
+    let hashed: Vec<(u64, PathBuf, std::io::Result<u64>)> = candidates.into_par_iter()
+        .map(|(size, path)| {
+            let hash = partial_hash(&path, size);
+            (size, path, hash)
+        })
+        .collect();
+    
+    let mut by_hash: HashMap<(u64, u64), Vec<String>> = HashMap::new();
+    let mut failed = Vec::new();
+    for (size, path, hash) in hashed {
+        match hash {
+            Ok(hash) => by_hash.entry((size, hash)).or_default().push(path.to_string_lossy().to_string()),
+            Err(e) => {
+                warn!("计算部分哈希失败: {}, 错误: {}", path.display(), e);
+                failed.push(FileError {
+                    path: path.to_string_lossy().to_string(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    
+    let mut clusters: Vec<DuplicateFileCluster> = by_hash.into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|((size, _), mut files)| {
+            files.sort();
+            DuplicateFileCluster { size, files }
+        })
+        .collect();
+    clusters.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.files.cmp(&b.files)));
+    zero_byte_files.sort();
+    
+    Ok(DuplicateFilesResult { clusters, zero_byte_files, failed })
+}
+
+// 流式读取文件开头和结尾各 PARTIAL_HASH_BYTES 字节计算哈希，仅用于同一次运行内的比较
+fn partial_hash(path: &Path, size: u64) -> std::io::Result<u64> {
+    use std::hash::Hasher;
+    use std::io::{Read, Seek, SeekFrom};
+    
+    let mut file = fs::File::open(path)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buffer = vec![0u8; PARTIAL_HASH_BYTES as usize];
+    
+    let head = size.min(PARTIAL_HASH_BYTES);
+    file.read_exact(&mut buffer[..head as usize])?;
+    hasher.write(&buffer[..head as usize]);
+    
+    // 小文件开头和结尾重叠时只读取剩余部分
+    let tail_start = size.saturating_sub(PARTIAL_HASH_BYTES).max(head);
+    if tail_start < size {
+        let tail = (size - tail_start) as usize;
+        file.seek(SeekFrom::Start(tail_start))?;
+        file.read_exact(&mut buffer[..tail])?;
+        hasher.write(&buffer[..tail]);
+    }
+    
+    Ok(hasher.finish())
+}
+
+// 按解析出的标题、季度和集数对视频分组，每组超过一个文件时按策略选出保留的文件
+pub(crate) fn resolve_duplicate_episodes(
+    files: &[String],
+    rename_map: &HashMap<String, String>,
+    config: &AppConfig
+) -> Vec<DuplicateEpisodeGroup> {
+    let policy = config.duplicate_resolution.as_str();
+    // (标题, 季度, 集数) -> 按输入顺序排列的文件
+    let mut by_episode: HashMap<(String, u32, u32), (String, Vec<&String>)> = HashMap::new();
+    let mut order = Vec::new();
+    
+    for file_path in files {
+        let path = Path::new(file_path);
+        let is_video = path.extension()
+            .map(|ext| FileKind::from_extension(&ext.to_string_lossy(), config) == FileKind::Video)
+            .unwrap_or(false);
+        if !is_video {
+            continue;
+        }
+        
+        let parsed = match parse_path_internal(path) {
+            Ok(parsed) => parsed,
+            Err(_) => continue,
+        };
+        let episode = match parsed.episode_number {
+            Some(episode) if !parsed.anime_title.trim().is_empty() => episode,
+            _ => continue,
+        };
+        
+        let key = (parsed.anime_title.trim().to_lowercase(), parsed.season.unwrap_or(1), episode);
+        if !by_episode.contains_key(&key) {
+            order.push(key.clone());
+        }
+        by_episode.entry(key)
+            .or_insert_with(|| (parsed.anime_title.trim().to_string(), Vec::new()))
+            .1
+            .push(file_path);
+    }
+    
+    order.into_iter()
+        .filter_map(|key| {
+            let (anime_title, candidates) = by_episode.remove(&key)?;
+            if candidates.len() < 2 {
+                return None;
+            }
+            
+            let (winner, dropped, suffixed) = match policy {
+                "keep_all" => (candidates[0].clone(), Vec::new(), suffix_duplicate_targets(&candidates, rename_map)),
+                _ => {
+                    let winner_index = match policy {
+                        "keep_largest" => best_index(&candidates, |file_path| (file_size(file_path), 0)),
+                        "keep_highest_resolution" => best_index(&candidates, |file_path| (resolution_height(file_path), file_size(file_path))),
+                        _ => 0,
+                    };
+                    let dropped = candidates.iter()
+                        .enumerate()
+                        .filter(|(index, _)| *index != winner_index)
+                        .map(|(_, file_path)| (*file_path).clone())
+                        .collect();
+                    (candidates[winner_index].clone(), dropped, HashMap::new())
+                }
+            };
+            
+            Some(DuplicateEpisodeGroup {
+                anime_title,
+                season: key.1,
+                episode: key.2,
+                winner,
+                dropped,
+                suffixed,
+            })
+        })
+        .collect()
+}
+
+// 取评分最高的文件下标，评分相同时保留先出现的文件
+fn best_index<F: Fn(&str) -> (u64, u64)>(candidates: &[&String], score: F) -> usize {
+    let mut best = 0;
+    let mut best_score = score(candidates[0]);
+    for (index, file_path) in candidates.iter().enumerate().skip(1) {
+        let current = score(file_path);
+        if current > best_score {
+            best = index;
+            best_score = current;
+        }
+    }
+    best
+}
+
+fn file_size(file_path: &str) -> u64 {
+    fs::metadata(file_path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+// 由解析出的分辨率换算为画面高度，无法识别时为 0
+fn resolution_height(file_path: &str) -> u64 {
+    let resolution = parse_path_internal(Path::new(file_path)).ok()
+        .and_then(|parsed| parsed.resolution);
+    let resolution = match resolution {
+        Some(resolution) => resolution,
+        None => return 0,
+    };
+    
+    let upper = resolution.to_uppercase();
+    if upper.contains("8K") {
+        return 4320;
+    }
+    if upper.contains("4K") || upper.contains("UHD") {
+        return 2160;
+    }
+    
+    DIMENSIONS_RE.captures(&resolution)
+        .or_else(|| HEIGHT_RE.captures(&resolution))
+        .and_then(|captures| captures[1].parse().ok())
+        .unwrap_or(0)
+}
+
+// 目标文件名与前面的文件重复时，在扩展名前追加 " (2)"、" (3)"……
+fn suffix_duplicate_targets(candidates: &[&String], rename_map: &HashMap<String, String>) -> HashMap<String, String> {
+    let target_of = |file_path: &String| {
+        rename_map.get(file_path).cloned().unwrap_or_else(|| {
+            Path::new(file_path).file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    };
+    
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut suffixed = HashMap::new();
+    for file_path in candidates {
+        let target = target_of(file_path);
+        let count = counts.entry(target.to_lowercase()).or_insert(0);
+        *count += 1;
+        if *count == 1 {
+            continue;
+        }
+        
+        let new_target = match target.rfind('.').filter(|dot| !target[dot + 1..].contains('/')) {
+            Some(dot) => format!("{} ({}){}", &target[..dot], count, &target[dot..]),
+            None => format!("{} ({})", target, count),
+        };
+        suffixed.insert((*file_path).clone(), new_target);
+    }
+    
+    suffixed
+}
@@ -0,0 +1,141 @@
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use std::io;
+use crate::commands::file_operations::{FileSystemError, OutputDirError, OutputDirErrorCode};
+use crate::commands::metadata::AniListError;
+
+// 命令返回给前端的错误，序列化为 { code, message }
+// code 是稳定的错误类型，前端据此分支处理和本地化；message 是中文说明，同时用于日志
+#[derive(Debug)]
+pub enum AppError {
+    // 以下与 FileSystemError 对应
+    Io(io::Error),
+    DifferentFilesystems,
+    TargetExists,
+    PermissionDenied,
+    SourceNotFound,
+    SourceImmutable,
+    CopyForbidden(String),
+    // 指定的文件或目录不存在，附带路径说明
+    NotFound(String),
+    // 网络请求失败或远端返回错误
+    Network(String),
+    // 文件名、配置、JSON 等内容无法解析
+    Parse(String),
+    Other(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Io(_) => "io",
+            AppError::DifferentFilesystems => "different_filesystems",
+            AppError::TargetExists => "target_exists",
+            AppError::PermissionDenied => "permission_denied",
+            AppError::SourceNotFound => "source_not_found",
+            AppError::SourceImmutable => "source_immutable",
+            AppError::CopyForbidden(_) => "copy_forbidden",
+            AppError::NotFound(_) => "not_found",
+            AppError::Network(_) => "network",
+            AppError::Parse(_) => "parse",
+            AppError::Other(_) => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Io(e) => write!(f, "IO错误: {}", e),
+            AppError::DifferentFilesystems => write!(f, "源文件和目标文件不在同一文件系统上，无法创建硬链接"),
+            AppError::TargetExists => write!(f, "目标文件已存在"),
+            AppError::PermissionDenied => write!(f, "权限不足"),
+            AppError::SourceNotFound => write!(f, "源文件不存在"),
+            AppError::SourceImmutable => write!(f, "源文件设置了不可变或仅追加属性 (chattr +i/+a)，无法创建硬链接"),
+            AppError::CopyForbidden(ext) => write!(f, "无法创建硬链接，且配置禁止复制 .{} 文件", ext),
+            AppError::NotFound(s) | AppError::Network(s) | AppError::Parse(s) | AppError::Other(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
+
+// 尚未区分类型的错误说明，归为 other
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Other(message.to_string())
+    }
+}
+
+// 供仍返回 String 的内部函数调用命令函数
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
+
+impl From<FileSystemError> for AppError {
+    fn from(error: FileSystemError) -> Self {
+        match error {
+            FileSystemError::IoError(e) => AppError::Io(e),
+            FileSystemError::DifferentFilesystems => AppError::DifferentFilesystems,
+            FileSystemError::TargetExists => AppError::TargetExists,
+            FileSystemError::PermissionDenied => AppError::PermissionDenied,
+            FileSystemError::SourceNotFound => AppError::SourceNotFound,
+            FileSystemError::SourceImmutable => AppError::SourceImmutable,
+            FileSystemError::CopyForbidden(ext) => AppError::CopyForbidden(ext),
+            FileSystemError::Other(s) => AppError::Other(s),
+        }
+    }
+}
+
+// 没有明确上下文的 IO 错误，只区分权限问题，不把 NotFound 当作源文件缺失
+impl From<io::Error> for AppError {
+    fn from(error: io::Error) -> Self {
+        match error.kind() {
+            io::ErrorKind::PermissionDenied => AppError::PermissionDenied,
+            _ => AppError::Io(error),
+        }
+    }
+}
+
+impl From<OutputDirError> for AppError {
+    fn from(error: OutputDirError) -> Self {
+        match error.code {
+            OutputDirErrorCode::PermissionDenied => AppError::PermissionDenied,
+            _ => AppError::Other(error.message),
+        }
+    }
+}
+
+impl From<AniListError> for AppError {
+    fn from(error: AniListError) -> Self {
+        AppError::Network(error.message)
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(error: reqwest::Error) -> Self {
+        AppError::Network(format!("网络请求失败: {}", error))
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::Parse(format!("JSON 解析失败: {}", error))
+    }
+}
@@ -1,21 +1,158 @@
 use std::path::{Path, PathBuf};
 use std::fs::{self};
 use serde::{Deserialize, Serialize};
-use tauri::{command, State};
+use tauri::{command, AppHandle, State};
+use tauri::ipc::Channel;
 use anyhow::Result;
 use tracing::{info, warn, error};
 use std::io;
-use std::collections::HashMap;
-use crate::commands::logs::{LogStore, add_log_entry, LogLevel};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
+use lazy_static::lazy_static;
+use unicode_normalization::UnicodeNormalization;
+use crate::commands::history::record_operation;
+use crate::commands::logs::{
+    LogStore, CommandLog, SOURCE_SCAN_DIRECTORY, SOURCE_DIFF_SCANS, SOURCE_CREATE_HARD_LINK,
+    SOURCE_BATCH_PROCESS_FILES, SOURCE_BATCH_PROCESS_WITH_RENAME, SOURCE_BATCH_PROCESS_WITH_SEASON_FOLDERS,
+    SOURCE_RELOAD_CHAR_MAP, SOURCE_SCAN_DIRECTORY_WITH_PROGRESS, SOURCE_SCAN_DIRECTORY_GROUPED,
+};
+use crate::commands::config::{AppConfig, read_app_config, get_config_dir};
+use crate::commands::manifest::{ManifestEntry, build_manifest_entry, write_manifest};
+use crate::commands::metadata::{AniListResponse, parse_filename_internal, parse_filename_with_config, parse_path_internal};
+use crate::commands::auto_match::build_matched_target;
+use crate::commands::nfo::{write_series_nfo, write_season_assets};
+use crate::commands::sidecars::{SidecarLink, link_sidecars};
+use crate::commands::duplicates::{apply_duplicate_resolution, check_existing_in_output};
+use crate::commands::devices::process_grouped_by_device;
+use crate::commands::throughput::ThroughputTracker;
+use crate::commands::template::{normalize_rendered_name, broadcast_season_folder, replace_padded_number};
+use crate::commands::undo::{BatchJournal, first_missing_dir};
+use crate::commands::checksum::file_crc32;
+use crate::commands::errors::AppError;
 
-#[derive(Debug, Serialize, Deserialize)]
+// 扫描到的文件类别
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileKind {
+    Video,
+    Subtitle,
+    Image,
+    Nfo,
+    #[default]
+    Other,
+}
+
+impl FileKind {
+    // 视频和字幕扩展名来自配置的 video_extensions / subtitle_extensions
+    pub fn from_extension(extension: &str, config: &AppConfig) -> Self {
+        if extension_listed(&config.video_extensions, extension) {
+            return FileKind::Video;
+        }
+        if extension_listed(&config.subtitle_extensions, extension) {
+            return FileKind::Subtitle;
+        }
+        match extension.to_lowercase().as_str() {
+            "jpg" | "jpeg" | "png" | "webp" => FileKind::Image,
+            "nfo" => FileKind::Nfo,
+            _ => FileKind::Other,
+        }
+    }
+    
+    // 图片和NFO属于随视频一起的附属文件
+    pub fn is_sidecar(&self) -> bool {
+        matches!(self, FileKind::Image | FileKind::Nfo)
+    }
+}
+
+// 按文件头识别常见的视频容器，返回对应的扩展名
+pub(crate) fn probe_container_extension(path: &Path) -> Option<&'static str> {
+    use std::io::Read;
+    
+    let mut header = [0u8; 12];
+    let read = fs::File::open(path).and_then(|mut file| file.read(&mut header)).ok()?;
+    let header = &header[..read];
+    
+    if header.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        Some("mkv")
+    } else if header.len() == 12 && &header[4..8] == b"ftyp" {
+        Some(if &header[8..12] == b"qt  " { "mov" } else { "mp4" })
+    } else if header.len() == 12 && header.starts_with(b"RIFF") && &header[8..12] == b"AVI " {
+        Some("avi")
+    } else {
+        None
+    }
+}
+
+// 按扩展名判断文件类别；没有扩展名且配置接受时，文件头是已知容器的视为视频，第二项为识别出的容器
+pub(crate) fn classify_file(path: &Path, extension: &str, config: &AppConfig) -> (FileKind, Option<String>) {
+    if extension.is_empty() && config.accept_extensionless_files {
+        if let Some(container) = probe_container_extension(path) {
+            info!("无扩展名文件识别为 {} 视频: {}", container, path.display());
+            return (FileKind::Video, Some(container.to_string()));
+        }
+    }
+    (FileKind::from_extension(extension, config), None)
+}
+
+// 扩展名是否在配置的列表中，忽略大小写和开头的点
+pub(crate) fn extension_listed(extensions: &[String], extension: &str) -> bool {
+    !extension.is_empty() && extensions.iter()
+        .any(|listed| listed.trim().trim_start_matches('.').eq_ignore_ascii_case(extension))
+}
+
+// 源文件和目标都没有扩展名时，按配置为目标追加默认扩展名；未配置时保持原样
+pub(crate) fn with_default_extension(source: &Path, target: PathBuf, config: &AppConfig) -> PathBuf {
+    let default_extension = config.extensionless_default_extension.trim().trim_start_matches('.');
+    if default_extension.is_empty() || source.extension().is_some() || target.extension().is_some() {
+        return target;
+    }
+    
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(default_extension);
+    target.with_file_name(name)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
     pub path: String,
     pub name: String,
     pub size: u64,
     pub file_type: String,
+    // 兼容旧版前端，由 kind 推导
     pub is_video: bool,
     pub is_subtitle: bool,
+    #[serde(default)]
+    pub kind: FileKind,
+    pub modified: Option<u64>,
+    // 没有扩展名的文件按文件头识别出的容器（例如 mkv），有扩展名或未识别时为空
+    #[serde(default)]
+    pub probed_container: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SourceStatus {
+    pub path: String,
+    pub exists: bool,
+    pub readable: bool,
+    pub size: Option<u64>,
+    pub error: Option<String>,
+}
+
+// 仅大小写不同、在大小写不敏感的文件系统上会互相覆盖的一组目标
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaseCollision {
+    pub key: String,
+    pub targets: Vec<String>,
+    pub existing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanDiff {
+    pub added: Vec<FileInfo>,
+    pub removed: Vec<FileInfo>,
+    pub unchanged: Vec<FileInfo>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,22 +161,87 @@ pub struct ProcessResult {
     pub message: String,
     pub processed_files: Vec<String>,
     pub failed_files: Vec<FileError>,
+    pub skipped_files: Vec<FileError>,
+    pub linked_sidecars: Vec<SidecarLink>,
+    // 不影响处理结果的提示，例如重命名改变了扩展名
+    #[serde(default)]
+    pub warnings: Vec<FileError>,
+    // 源文件 -> 实际执行的链接操作 (hardlink/symlink/copy)
+    #[serde(default)]
+    pub link_actions: HashMap<String, String>,
+    // 收到停止请求后未处理的文件，与失败的文件分开报告
+    #[serde(default)]
+    pub cancelled_files: Vec<String>,
+    // 源文件 -> 复制后校验通过的 CRC32，只在开启校验时记录复制的文件
+    #[serde(default)]
+    pub checksums: HashMap<String, String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileError {
     pub path: String,
     pub error: String,
 }
 
+// 实际执行的链接操作
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum LinkAction {
+    HardLink,
+    Symlink,
+    Copy,
+}
+
+impl LinkAction {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            LinkAction::HardLink => "hardlink",
+            LinkAction::Symlink => "symlink",
+            LinkAction::Copy => "copy",
+        }
+    }
+}
+
+// 可选的链接方式
+pub const LINK_MODES: [&str; 3] = ["hardlink", "symlink", "copy"];
+
+// 请求的链接方式：硬链接（失败时可回退为复制）、符号链接（可跨文件系统）或直接复制
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub(crate) enum LinkMode {
+    #[default]
+    HardLink,
+    Symlink,
+    Copy,
+}
+
+impl LinkMode {
+    pub(crate) fn parse(mode: &str) -> Result<Self, String> {
+        match mode.trim().to_lowercase().as_str() {
+            "hardlink" => Ok(LinkMode::HardLink),
+            "symlink" => Ok(LinkMode::Symlink),
+            "copy" => Ok(LinkMode::Copy),
+            _ => Err(format!("无效的链接方式: {}，可选值: {}", mode, LINK_MODES.join(", "))),
+        }
+    }
+}
+
+// 链接结果：最终目标路径以及实际执行的操作
+#[derive(Debug)]
+pub(crate) struct LinkOutcome {
+    pub(crate) target: PathBuf,
+    pub(crate) action: LinkAction,
+}
+
 // 文件系统错误类型
 #[derive(Debug)]
-enum FileSystemError {
+pub(crate) enum FileSystemError {
     IoError(io::Error),
     DifferentFilesystems,
     TargetExists,
     PermissionDenied,
     SourceNotFound,
+    SourceImmutable,
+    // 扩展名在 copy_forbidden_extensions 中，不允许回退为复制
+    CopyForbidden(String),
     Other(String),
 }
 
@@ -62,13 +264,30 @@ impl std::fmt::Display for FileSystemError {
             FileSystemError::TargetExists => write!(f, "目标文件已存在"),
             FileSystemError::PermissionDenied => write!(f, "权限不足，无法创建硬链接"),
             FileSystemError::SourceNotFound => write!(f, "源文件不存在"),
+            FileSystemError::SourceImmutable => write!(f, "源文件设置了不可变或仅追加属性 (chattr +i/+a)，无法创建硬链接"),
+            FileSystemError::CopyForbidden(ext) => write!(f, "无法创建硬链接，且配置禁止复制 .{} 文件", ext),
             FileSystemError::Other(s) => write!(f, "{}", s),
         }
     }
 }
 
+// 两个路径是否指向同一个文件（例如互为硬链接），无法判断时返回 false
+#[cfg(unix)]
+pub(crate) fn is_same_file(path1: &Path, path2: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(path1), fs::metadata(path2)) {
+        (Ok(metadata1), Ok(metadata2)) => metadata1.dev() == metadata2.dev() && metadata1.ino() == metadata2.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn is_same_file(_path1: &Path, _path2: &Path) -> bool {
+    false
+}
+
 // 检查两个路径是否在同一文件系统上
-fn is_same_filesystem(path1: &Path, path2: &Path) -> Result<bool, FileSystemError> {
+pub(crate) fn is_same_filesystem(path1: &Path, path2: &Path) -> Result<bool, FileSystemError> {
     // 在Windows上，检查驱动器号是否相同
     #[cfg(target_os = "windows")]
     {
@@ -107,17 +326,20 @@ fn check_file_permissions(source: &Path, target_parent: &Path) -> Result<(), Fil
         return Err(FileSystemError::SourceNotFound);
     }
     
-    // 检查源文件是否可读
-    let _source_metadata = fs::metadata(source)?;
-    
-    // 检查目标目录是否可写
+    // 创建硬链接只是在目标目录中新增一个目录项，不需要源文件可写，只读的源文件也可以链接
+    // 因此只检查目标目录是否可写
     if target_parent.exists() {
-        let _target_metadata = fs::metadata(target_parent)?;
-        
+        // Unix上按当前用户实际的权限检查，而不是只看所有者的写权限位
         #[cfg(unix)]
         {
-            use std::os::unix::fs::PermissionsExt;
-            if target_metadata.permissions().mode() & 0o200 == 0 {
+            if !is_writable_dir(target_parent) {
+                return Err(FileSystemError::PermissionDenied);
+            }
+        }
+        
+        #[cfg(not(unix))]
+        {
+            if fs::metadata(target_parent)?.permissions().readonly() {
                 return Err(FileSystemError::PermissionDenied);
             }
         }
@@ -126,16 +348,257 @@ fn check_file_permissions(source: &Path, target_parent: &Path) -> Result<(), Fil
     Ok(())
 }
 
+// 当前用户是否可以在目录中新建条目
+#[cfg(unix)]
+fn is_writable_dir(dir: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    
+    match CString::new(dir.as_os_str().as_bytes()) {
+        Ok(path) => unsafe { libc::access(path.as_ptr(), libc::W_OK | libc::X_OK) == 0 },
+        Err(_) => false,
+    }
+}
+
+// 源文件是否设置了不可变（chattr +i）或仅追加（chattr +a）属性，这两种属性都会阻止创建硬链接
+#[cfg(target_os = "linux")]
+fn has_immutable_attribute(path: &Path) -> bool {
+    use std::os::unix::io::AsRawFd;
+    const FS_IMMUTABLE_FL: libc::c_int = 0x10;
+    const FS_APPEND_FL: libc::c_int = 0x20;
+    
+    let file = match fs::File::open(path) {
+        Ok(file) => file,
+        Err(_) => return false,
+    };
+    let mut flags: libc::c_int = 0;
+    // 文件系统不支持该 ioctl 时返回错误，按未设置处理
+    let result = unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_GETFLAGS, &mut flags) };
+    result == 0 && flags & (FS_IMMUTABLE_FL | FS_APPEND_FL) != 0
+}
+
+#[cfg(not(target_os = "linux"))]
+fn has_immutable_attribute(_path: &Path) -> bool {
+    false
+}
+
 #[command]
-pub async fn scan_directory(path: String, log_store: State<'_, LogStore>) -> Result<Vec<FileInfo>, String> {
-    use walkdir::WalkDir;
+pub async fn scan_directory(path: String, log_store: State<'_, LogStore>) -> Result<Vec<FileInfo>, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_SCAN_DIRECTORY);
     
     info!("扫描目录: {}", path);
-    add_log_entry(&log_store, LogLevel::INFO, format!("开始扫描目录: {}", path), Some("文件扫描".to_string()));
+    log.info(format!("开始扫描目录: {}", path));
+    
+    let config = read_app_config();
+    let (files, skipped) = scan_directory_internal(&path, &config);
+    
+    if !skipped.is_empty() {
+        warn!("扫描时跳过 {} 个未完成的下载文件", skipped.len());
+        log.warn(format!("扫描时跳过 {} 个未完成的下载文件", skipped.len()));
+        for skipped_file in &skipped {
+            log.debug(format!("跳过文件: {} - {}", skipped_file.path, skipped_file.error));
+        }
+    }
+    
+    info!("扫描完成，找到 {} 个文件", files.len());
+    log.info(format!("扫描完成，找到 {} 个文件", files.len()));
+    Ok(files)
+}
+
+// 一个视频及与它同名的字幕
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VideoGroup {
+    pub video: FileInfo,
+    pub subtitles: Vec<FileInfo>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GroupedScanResult {
+    pub groups: Vec<VideoGroup>,
+    // 同目录下没有同名视频的字幕
+    pub unpaired_subtitles: Vec<FileInfo>,
+    // 视频和字幕以外的文件
+    pub others: Vec<FileInfo>,
+}
+
+// 扫描目录并将字幕归入同名视频，需要平铺列表时使用 scan_directory
+#[command]
+pub async fn scan_directory_grouped(path: String, log_store: State<'_, LogStore>) -> Result<GroupedScanResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_SCAN_DIRECTORY_GROUPED);
+    
+    info!("分组扫描目录: {}", path);
+    log.info(format!("开始分组扫描目录: {}", path));
+    
+    let config = read_app_config();
+    let (files, skipped) = scan_directory_internal(&path, &config);
+    if !skipped.is_empty() {
+        log.warn(format!("扫描时跳过 {} 个未完成的下载文件", skipped.len()));
+    }
+    
+    let result = group_subtitles_with_videos(files);
+    log.info(format!(
+        "扫描完成: 视频 {}, 已配对字幕 {}, 未配对字幕 {}",
+        result.groups.len(),
+        result.groups.iter().map(|group| group.subtitles.len()).sum::<usize>(),
+        result.unpaired_subtitles.len()
+    ));
+    Ok(result)
+}
+
+pub(crate) fn group_subtitles_with_videos(files: Vec<FileInfo>) -> GroupedScanResult {
+    let mut groups = Vec::new();
+    // (所在目录, 视频文件名主干) -> 在 groups 中的位置
+    let mut videos: HashMap<(PathBuf, String), usize> = HashMap::new();
+    let mut subtitles = Vec::new();
+    let mut others = Vec::new();
+    
+    for file in files {
+        match file.kind {
+            FileKind::Video => {
+                let path = Path::new(&file.path);
+                if let (Some(parent), Some(stem)) = (path.parent(), path.file_stem()) {
+                    videos.insert((parent.to_path_buf(), stem.to_string_lossy().to_string()), groups.len());
+                }
+                groups.push(VideoGroup { video: file, subtitles: Vec::new() });
+            }
+            FileKind::Subtitle => subtitles.push(file),
+            _ => others.push(file),
+        }
+    }
+    
+    let mut unpaired_subtitles = Vec::new();
+    for subtitle in subtitles {
+        match matching_video(Path::new(&subtitle.path), &videos) {
+            Some(index) => groups[index].subtitles.push(subtitle),
+            None => unpaired_subtitles.push(subtitle),
+        }
+    }
+    
+    GroupedScanResult { groups, unpaired_subtitles, others }
+}
+
+// 字幕主干与视频主干相同，或是视频主干加语言后缀（例如 "Ep01.chs.ass"、"Ep01.sc.forced.ass"）
+// 从后往前逐段去掉后缀查找，多个视频都能匹配时取主干最长的
+fn matching_video(subtitle: &Path, videos: &HashMap<(PathBuf, String), usize>) -> Option<usize> {
+    let parent = subtitle.parent()?.to_path_buf();
+    let mut stem = subtitle.file_stem()?.to_string_lossy().to_string();
+    loop {
+        if let Some(&index) = videos.get(&(parent.clone(), stem.clone())) {
+            return Some(index);
+        }
+        stem.truncate(stem.rfind('.')?);
+    }
+}
+
+// 扫描进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanProgress {
+    pub files_found: usize,
+    pub dirs_scanned: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScanResult {
+    pub files: Vec<FileInfo>,
+    pub cancelled: bool,
+}
+
+// 每遍历多少个条目报告一次扫描进度
+const SCAN_PROGRESS_INTERVAL: usize = 200;
+
+// 带进度和取消的扫描，适用于很大的目录树；小目录直接使用 scan_directory
+// scan_id 由前端生成，用于 cancel_scan 取消对应的扫描
+#[command]
+pub async fn scan_directory_with_progress(
+    path: String,
+    scan_id: String,
+    on_progress: Channel<ScanProgress>,
+    log_store: State<'_, LogStore>
+) -> Result<ScanResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_SCAN_DIRECTORY_WITH_PROGRESS);
+    
+    info!("扫描目录: {} (扫描ID: {})", path, scan_id);
+    log.info(format!("开始扫描目录: {}", path));
+    
+    let cancel = Arc::new(AtomicBool::new(false));
+    SCAN_CANCEL_FLAGS.lock().unwrap().insert(scan_id.clone(), cancel.clone());
+    
+    let config = read_app_config();
+    let scan_path = path.clone();
+    let scan = tokio::task::spawn_blocking(move || {
+        scan_directory_cancellable(&scan_path, &config, &cancel, |progress| {
+            if let Err(e) = on_progress.send(progress) {
+                warn!("发送扫描进度失败: {}", e);
+            }
+        })
+    }).await;
+    
+    SCAN_CANCEL_FLAGS.lock().unwrap().remove(&scan_id);
+    let (files, skipped, cancelled) = scan.map_err(|e| format!("扫描任务异常: {}", e))?;
+    
+    if !skipped.is_empty() {
+        log.warn(format!("扫描时跳过 {} 个未完成的下载文件", skipped.len()));
+    }
+    if cancelled {
+        info!("扫描已取消，已找到 {} 个文件", files.len());
+        log.info(format!("扫描已取消，返回已找到的 {} 个文件", files.len()));
+    } else {
+        info!("扫描完成，找到 {} 个文件", files.len());
+        log.info(format!("扫描完成，找到 {} 个文件", files.len()));
+    }
+    
+    Ok(ScanResult { files, cancelled })
+}
+
+// 批量处理的停止请求，由 stop_batch_processing 设置，每次批量处理开始时清除
+pub type BatchCancelFlag = Arc<AtomicBool>;
+
+pub fn create_batch_cancel_flag() -> BatchCancelFlag {
+    Arc::new(AtomicBool::new(false))
+}
+
+// 请求停止正在进行的批量处理，已开始处理的文件会完成，其余文件记为已取消
+#[command]
+pub async fn stop_batch_processing(cancel: State<'_, BatchCancelFlag>) -> Result<bool, AppError> {
+    info!("收到停止批量处理的请求");
+    cancel.store(true, Ordering::Relaxed);
+    Ok(true)
+}
+
+// 取消正在进行的扫描，扫描不存在或已结束时返回 false
+#[command]
+pub async fn cancel_scan(scan_id: String) -> Result<bool, AppError> {
+    match SCAN_CANCEL_FLAGS.lock().unwrap().get(&scan_id) {
+        Some(cancel) => {
+            cancel.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// 递归扫描目录中的视频和字幕文件，同时返回因未下载完成而跳过的文件
+pub(crate) fn scan_directory_internal(path: &str, config: &AppConfig) -> (Vec<FileInfo>, Vec<FileError>) {
+    let (files, skipped, _) = scan_directory_cancellable(path, config, &AtomicBool::new(false), |_| {});
+    (files, skipped)
+}
+
+// 可取消的扫描，定期通过 on_progress 报告进度；取消时返回已扫描到的部分结果，第三项为是否被取消
+pub(crate) fn scan_directory_cancellable(
+    path: &str,
+    config: &AppConfig,
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(ScanProgress)
+) -> (Vec<FileInfo>, Vec<FileError>, bool) {
+    use walkdir::WalkDir;
     
     let mut files = Vec::new();
+    let mut skipped = Vec::new();
+    let mut dirs_scanned = 0;
+    let mut entries_seen = 0;
+    let mut cancelled = false;
     
-    for entry in WalkDir::new(&path)
+    for entry in WalkDir::new(path)
         .follow_links(true)
         .into_iter()
         .filter_map(|e| {
@@ -145,6 +608,21 @@ pub async fn scan_directory(path: String, log_store: State<'_, LogStore>) -> Res
             e.ok()
         })
     {
+        if cancel.load(Ordering::Relaxed) {
+            cancelled = true;
+            break;
+        }
+        
+        entries_seen += 1;
+        if entries_seen % SCAN_PROGRESS_INTERVAL == 0 {
+            on_progress(ScanProgress { files_found: files.len(), dirs_scanned });
+        }
+        
+        if entry.file_type().is_dir() {
+            dirs_scanned += 1;
+            continue;
+        }
+        
         if entry.file_type().is_file() {
             let path_buf = entry.path().to_path_buf();
             let extension = path_buf
@@ -153,11 +631,25 @@ pub async fn scan_directory(path: String, log_store: State<'_, LogStore>) -> Res
                 .unwrap_or("")
                 .to_lowercase();
             
-            let is_video = matches!(extension.as_str(), "mkv" | "mp4" | "avi" | "mov");
-            let is_subtitle = matches!(extension.as_str(), "ass" | "srt" | "vtt");
+            let (kind, probed_container) = classify_file(&path_buf, &extension, config);
+            
+            if is_in_progress_extension(&extension, config) {
+                skipped.push(FileError {
+                    path: path_buf.to_string_lossy().to_string(),
+                    error: format!("未完成的下载文件 (.{})", extension),
+                });
+                continue;
+            }
             
-            if is_video || is_subtitle {
+            // 图片、NFO等附属文件仅在配置开启时返回
+            if matches!(kind, FileKind::Video | FileKind::Subtitle) || (config.scan_include_sidecars && kind.is_sidecar()) {
                 match std::fs::metadata(&path_buf) {
+                    Ok(metadata) if config.skip_zero_byte_files && metadata.len() == 0 => {
+                        skipped.push(FileError {
+                            path: path_buf.to_string_lossy().to_string(),
+                            error: "空文件 (0 字节)".to_string(),
+                        });
+                    },
                     Ok(metadata) => {
                         files.push(FileInfo {
                             path: path_buf.to_string_lossy().to_string(),
@@ -167,8 +659,11 @@ pub async fn scan_directory(path: String, log_store: State<'_, LogStore>) -> Res
                                 .to_string(),
                             size: metadata.len(),
                             file_type: extension,
-                            is_video,
-                            is_subtitle,
+                            is_video: kind == FileKind::Video,
+                            is_subtitle: kind == FileKind::Subtitle,
+                            kind,
+                            modified: modified_secs(&metadata),
+                            probed_container,
                         });
                     },
                     Err(e) => {
@@ -179,13 +674,233 @@ pub async fn scan_directory(path: String, log_store: State<'_, LogStore>) -> Res
         }
     }
     
-    info!("扫描完成，找到 {} 个文件", files.len());
-    add_log_entry(&log_store, LogLevel::INFO, format!("扫描完成，找到 {} 个文件", files.len()), Some("文件扫描".to_string()));
-    Ok(files)
+    on_progress(ScanProgress { files_found: files.len(), dirs_scanned });
+    (files, skipped, cancelled)
+}
+
+// 检查扩展名是否为字幕文件
+pub(crate) fn is_subtitle_extension(extension: &str, config: &AppConfig) -> bool {
+    FileKind::from_extension(extension, config) == FileKind::Subtitle
+}
+
+// 按配置将字幕文件放入所在文件夹下的字幕子文件夹，未配置子文件夹或不是字幕时原样返回
+pub(crate) fn place_subtitle_target(target: PathBuf, config: &AppConfig) -> PathBuf {
+    let is_subtitle = target.extension()
+        .is_some_and(|ext| is_subtitle_extension(&ext.to_string_lossy(), config));
+    
+    if config.subtitle_subfolder.trim().is_empty() || !is_subtitle {
+        return target;
+    }
+    
+    let subfolder = sanitize_filename(config.subtitle_subfolder.trim());
+    match (target.parent(), target.file_name()) {
+        // 重命名映射中已经包含字幕子文件夹时不再重复添加
+        (Some(parent), Some(_)) if parent.file_name().is_some_and(|name| name.to_string_lossy() == subfolder) => target,
+        (Some(parent), Some(name)) => parent.join(&subfolder).join(name),
+        _ => target,
+    }
+}
+
+// 检查扩展名是否属于下载工具的临时文件
+fn is_in_progress_extension(extension: &str, config: &AppConfig) -> bool {
+    config.in_progress_extensions.iter()
+        .any(|ext| ext.trim_start_matches('.').eq_ignore_ascii_case(extension))
+}
+
+// 链接前检查源文件是否已下载完成，返回跳过原因
+// scanned_sizes 提供扫描时记录的大小，大小发生变化的文件视为仍在下载
+pub(crate) fn incomplete_source_reason(source: &Path, config: &AppConfig, scanned_sizes: Option<&HashMap<String, u64>>) -> Option<String> {
+    let extension = source.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    
+    if is_in_progress_extension(&extension, config) {
+        return Some(format!("未完成的下载文件 (.{})", extension));
+    }
+    
+    // 无法获取元数据时交由后续链接步骤报告错误
+    let metadata = fs::metadata(source).ok()?;
+    
+    if config.skip_zero_byte_files && metadata.len() == 0 {
+        return Some("空文件 (0 字节)".to_string());
+    }
+    
+    if let Some(scanned_size) = scanned_sizes.and_then(|sizes| sizes.get(&source.to_string_lossy().to_string())) {
+        if *scanned_size != metadata.len() {
+            return Some(format!("文件大小自扫描后发生变化 ({} -> {} 字节)，可能仍在下载", scanned_size, metadata.len()));
+        }
+    }
+    
+    None
+}
+
+// 获取文件修改时间（Unix时间戳，秒）
+fn modified_secs(metadata: &fs::Metadata) -> Option<u64> {
+    metadata.modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
+
+// 比较上一次扫描结果与当前目录，找出新增、移除和未变化的文件
+// 以 路径+大小+修改时间 作为判断依据，内容发生变化的文件会同时出现在新增和移除中
+#[command]
+pub async fn diff_scans(previous: Vec<FileInfo>, current_folder: String, log_store: State<'_, LogStore>) -> Result<ScanDiff, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_DIFF_SCANS);
+    
+    if !Path::new(&current_folder).is_dir() {
+        return Err(AppError::NotFound("当前目录不存在".to_string()));
+    }
+    
+    let (current, _) = scan_directory_internal(&current_folder, &read_app_config());
+    
+    let scan_key = |file: &FileInfo| (file.path.clone(), file.size, file.modified);
+    let previous_keys: HashSet<_> = previous.iter().map(scan_key).collect();
+    let current_keys: HashSet<_> = current.iter().map(scan_key).collect();
+    
+    let (unchanged, added): (Vec<FileInfo>, Vec<FileInfo>) = current
+        .into_iter()
+        .partition(|file| previous_keys.contains(&scan_key(file)));
+    
+    let removed: Vec<FileInfo> = previous
+        .into_iter()
+        .filter(|file| !current_keys.contains(&scan_key(file)))
+        .collect();
+    
+    info!("扫描对比完成: 新增 {}, 移除 {}, 未变化 {}", added.len(), removed.len(), unchanged.len());
+    log.info(format!("扫描对比完成: 新增 {}, 移除 {}, 未变化 {}", added.len(), removed.len(), unchanged.len()));
+    
+    Ok(ScanDiff {
+        added,
+        removed,
+        unchanged,
+    })
+}
+
+// 内置的字符替换规则
+const DEFAULT_CHAR_REPLACEMENTS: [(char, &str); 16] = [
+    ('☆', "★"),  // 替换空心星号为实心星号
+    ('～', "~"),  // 替换全角波浪号为半角
+    ('＆', "&"),  // 替换全角&为半角
+    ('！', "!"),  // 替换全角!为半角
+    ('？', "?"),  // 替换全角?为半角
+    ('：', ":"),  // 替换全角:为半角
+    ('；', ";"),  // 替换全角;为半角
+    ('，', ","),  // 替换全角,为半角
+    ('。', "."),  // 替换全角.为半角
+    ('（', "("),  // 替换全角(为半角
+    ('）', ")"),  // 替换全角)为半角
+    ('【', "["),  // 替换全角[为半角
+    ('】', "]"),  // 替换全角]为半角
+    ('｛', "{"),  // 替换全角{为半角
+    ('｝', "}"),  // 替换全角}为半角
+    ('　', " "),  // 替换全角空格为半角空格
+];
+
+// 用户自定义字符映射文件名，位于配置目录
+pub(crate) const CHAR_MAP_FILE_NAME: &str = "char_map.json";
+
+// 生效的字符替换规则，from 在 NFC 规范化后的文件名中替换为 to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharReplacementRule {
+    pub from: char,
+    pub to: String,
+    // 规则来源：default（内置）、config（配置中的 char_replacements）、char_map（字符映射文件）
+    pub source: String,
+}
+
+lazy_static! {
+    // 扫描ID -> 取消标记，扫描结束后移除
+    static ref SCAN_CANCEL_FLAGS: Mutex<HashMap<String, Arc<AtomicBool>>> = Mutex::new(HashMap::new());
+    static ref CHAR_REPLACEMENTS: RwLock<Vec<CharReplacementRule>> = RwLock::new(
+        load_char_replacements(&read_app_config()).unwrap_or_else(|e| {
+            warn!("加载字符映射文件失败，使用内置规则: {}", e);
+            default_char_replacements()
+        })
+    );
+}
+
+fn default_char_replacements() -> Vec<CharReplacementRule> {
+    DEFAULT_CHAR_REPLACEMENTS.iter()
+        .map(|(from, to)| CharReplacementRule { from: *from, to: to.to_string(), source: "default".to_string() })
+        .collect()
+}
+
+// 在内置规则基础上依次合并配置中的 char_replacements 和配置目录中的字符映射文件，后者优先
+// 两者格式相同，如 {"☆": "*", "～": "～"}，映射为自身即保留原字符
+fn load_char_replacements(config: &AppConfig) -> Result<Vec<CharReplacementRule>, String> {
+    let mut replacements = default_char_replacements();
+    merge_char_map(&mut replacements, config.char_replacements.clone(), "config");
+    
+    let char_map_path = get_config_dir()?.join(CHAR_MAP_FILE_NAME);
+    if !char_map_path.exists() {
+        return Ok(replacements);
+    }
+    
+    let content = fs::read_to_string(&char_map_path)
+        .map_err(|e| format!("读取字符映射文件失败: {}", e))?;
+    let char_map: HashMap<String, String> = serde_json::from_str(&content)
+        .map_err(|e| format!("字符映射文件格式错误: {}", e))?;
+    merge_char_map(&mut replacements, char_map, "char_map");
+    
+    Ok(replacements)
+}
+
+// 覆盖同一字符的已有规则，新字符追加到末尾
+fn merge_char_map(replacements: &mut Vec<CharReplacementRule>, char_map: HashMap<String, String>, source: &str) {
+    for (from, to) in char_map {
+        // 键也做 NFC 规范化，与规范化后的文件名一致
+        let from: String = from.nfc().collect();
+        let mut chars = from.chars();
+        let (Some(from_char), None) = (chars.next(), chars.next()) else {
+            warn!("字符映射的键必须是单个字符，已忽略: {}", from);
+            continue;
+        };
+        
+        match replacements.iter_mut().find(|rule| rule.from == from_char) {
+            Some(rule) => {
+                rule.to = to;
+                rule.source = source.to_string();
+            }
+            None => replacements.push(CharReplacementRule { from: from_char, to, source: source.to_string() }),
+        }
+    }
+}
+
+// 按当前配置和字符映射文件重新生成替换规则，返回生效的规则数量
+pub(crate) fn refresh_char_replacements(config: &AppConfig) -> Result<usize, String> {
+    let replacements = load_char_replacements(config)?;
+    let count = replacements.len();
+    
+    let mut current = CHAR_REPLACEMENTS.write()
+        .map_err(|e| format!("更新字符映射失败: {}", e))?;
+    *current = replacements;
+    Ok(count)
+}
+
+// 重新加载字符映射文件，返回生效的规则数量
+#[command]
+pub async fn reload_char_map(log_store: State<'_, LogStore>) -> Result<usize, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_RELOAD_CHAR_MAP);
+    
+    let count = refresh_char_replacements(&read_app_config())?;
+    
+    log.info(format!("字符映射已重新加载，共 {} 条规则", count));
+    Ok(count)
+}
+
+// Windows 保留的设备名，不能作为文件或文件夹名（包括带扩展名的情况，如 "CON.txt"）
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end().to_uppercase();
+    matches!(stem.as_str(), "CON" | "PRN" | "AUX" | "NUL")
+        || ((stem.starts_with("COM") || stem.starts_with("LPT"))
+            && stem.len() == 4
+            && stem[3..].chars().all(|c| ('1'..='9').contains(&c)))
 }
 
 // 清理文件名中的非法字符
-fn sanitize_filename(filename: &str) -> String {
+pub(crate) fn sanitize_filename(filename: &str) -> String {
     let mut sanitized = filename.to_string();
     
     // Windows 不支持的字符
@@ -194,24 +909,14 @@ fn sanitize_filename(filename: &str) -> String {
         sanitized = sanitized.replace(ch, "_");
     }
     
-    // 替换一些特殊Unicode字符
-    sanitized = sanitized
-        .replace('☆', "★")  // 替换空心星号为实心星号
-        .replace('～', "~")  // 替换全角波浪号为半角
-        .replace('＆', "&")  // 替换全角&为半角
-        .replace('！', "!")  // 替换全角!为半角
-        .replace('？', "?")  // 替换全角?为半角
-        .replace('：', ":")  // 替换全角:为半角
-        .replace('；', ";")  // 替换全角;为半角
-        .replace('，', ",")  // 替换全角,为半角
-        .replace('。', ".")  // 替换全角.为半角
-        .replace('（', "(")  // 替换全角(为半角
-        .replace('）', ")")  // 替换全角)为半角
-        .replace('【', "[")  // 替换全角[为半角
-        .replace('】', "]")  // 替换全角]为半角
-        .replace('｛', "{")  // 替换全角{为半角
-        .replace('｝', "}")  // 替换全角}为半角
-        .replace('　', " ");  // 替换全角空格为半角空格
+    // 先做 NFC 规范化，合并 macOS 等来源的分解字符（如 か + ゛），再替换一些特殊Unicode字符
+    sanitized = sanitized.nfc().collect();
+    {
+        let replacements = CHAR_REPLACEMENTS.read().unwrap();
+        for rule in replacements.iter() {
+            sanitized = sanitized.replace(rule.from, &rule.to);
+        }
+    }
     
     // 移除控制字符
     sanitized = sanitized.chars()
@@ -226,6 +931,12 @@ fn sanitize_filename(filename: &str) -> String {
         sanitized = "unnamed_file".to_string();
     }
     
+    // Windows 保留设备名即使带扩展名也无法使用，加前缀避免生成无法访问的文件
+    // 其他平台上这些名称是合法的，sanitize_path 也会处理用户选择的目录，不能改名
+    if cfg!(windows) && is_reserved_name(&sanitized) {
+        sanitized = format!("_{}", sanitized);
+    }
+    
     // 限制文件名长度（Windows文件名最大255字符）
     if sanitized.len() > 200 {
         sanitized.truncate(200);
@@ -239,7 +950,7 @@ fn sanitize_filename(filename: &str) -> String {
 }
 
 // 清理路径，处理长路径问题
-fn sanitize_path(path: &Path) -> PathBuf {
+pub(crate) fn sanitize_path(path: &Path) -> PathBuf {
     let mut components = Vec::new();
     
     for component in path.components() {
@@ -264,9 +975,142 @@ fn sanitize_path(path: &Path) -> PathBuf {
     PathBuf::from(components.join(std::path::MAIN_SEPARATOR_STR))
 }
 
+// 输出目录创建失败的错误码，供前端给出对应的处理建议
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputDirErrorCode {
+    ReservedName,
+    PathTooLong,
+    ReadOnly,
+    PermissionDenied,
+    NotADirectory,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutputDirError {
+    pub code: OutputDirErrorCode,
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for OutputDirError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl OutputDirError {
+    fn new(code: OutputDirErrorCode, path: &Path, message: String) -> Self {
+        Self {
+            code,
+            path: path.to_string_lossy().to_string(),
+            message,
+        }
+    }
+    
+    // 将IO错误转换为带处理建议的错误
+    pub(crate) fn from_io(path: &Path, error: &io::Error) -> Self {
+        // EROFS (Linux/macOS)、ERROR_WRITE_PROTECT (Windows)
+        let read_only = matches!(error.raw_os_error(), Some(30) if cfg!(unix))
+            || matches!(error.raw_os_error(), Some(19) if cfg!(windows));
+        // ENAMETOOLONG (Linux 36 / macOS 63)、ERROR_FILENAME_EXCED_RANGE (Windows)
+        let too_long = matches!(error.raw_os_error(), Some(36) if cfg!(target_os = "linux"))
+            || matches!(error.raw_os_error(), Some(63) if cfg!(target_os = "macos"))
+            || matches!(error.raw_os_error(), Some(206) if cfg!(windows));
+        
+        if read_only {
+            Self::new(OutputDirErrorCode::ReadOnly, path, format!("输出目录所在的磁盘为只读，请更换目录或检查挂载选项: {}", path.display()))
+        } else if too_long {
+            Self::new(OutputDirErrorCode::PathTooLong, path, format!("输出目录路径过长，请选择层级更浅的目录: {}", path.display()))
+        } else if error.kind() == io::ErrorKind::PermissionDenied {
+            Self::new(OutputDirErrorCode::PermissionDenied, path, format!("没有权限在此位置创建输出目录，请更换目录或调整权限: {}", path.display()))
+        } else {
+            Self::new(OutputDirErrorCode::Other, path, format!("创建输出目录失败: {}", error))
+        }
+    }
+}
+
+// 清理并创建输出目录，返回清理后的路径
+// 目录被并发创建时视为成功；常见的失败原因转换为明确的错误码和提示
+pub(crate) fn ensure_output_dir(path: &Path) -> Result<PathBuf, OutputDirError> {
+    // sanitize_filename 会给保留名称加前缀，要在清理前检查用户选择的路径，而不是悄悄创建别的目录
+    if cfg!(windows) {
+        if let Some(reserved) = path.components()
+            .filter_map(|component| match component {
+                std::path::Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .find(|name| is_reserved_name(name))
+        {
+            return Err(OutputDirError::new(
+                OutputDirErrorCode::ReservedName,
+                path,
+                format!("路径中的 \"{}\" 是Windows保留名称，请重命名该文件夹", reserved),
+            ));
+        }
+    }
+    
+    let sanitized = sanitize_path(path);
+    
+    // Windows 下目录路径超过 248 字符时无法再在其中创建文件
+    if cfg!(windows) && sanitized.to_string_lossy().len() > 248 {
+        return Err(OutputDirError::new(
+            OutputDirErrorCode::PathTooLong,
+            &sanitized,
+            format!("输出目录路径过长 ({} 字符)，请选择层级更浅的目录", sanitized.to_string_lossy().len()),
+        ));
+    }
+    
+    if sanitized.exists() && !sanitized.is_dir() {
+        return Err(OutputDirError::new(
+            OutputDirErrorCode::NotADirectory,
+            &sanitized,
+            format!("输出路径已存在且不是文件夹: {}", sanitized.display()),
+        ));
+    }
+    
+    // 中断等临时错误重试几次
+    let mut attempts = 0;
+    loop {
+        match fs::create_dir_all(&sanitized) {
+            Ok(_) => return Ok(sanitized),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && sanitized.is_dir() => return Ok(sanitized),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted && attempts < 3 => {
+                attempts += 1;
+                warn!("创建输出目录被中断，重试第 {} 次: {}", attempts, sanitized.display());
+            }
+            Err(e) => return Err(OutputDirError::from_io(&sanitized, &e)),
+        }
+    }
+}
+
+// 批量处理结束后写入处理清单，失败时仅记录警告
+pub(crate) fn write_batch_manifest(log: &CommandLog, output_dir: &Path, entries: Vec<ManifestEntry>, config: &AppConfig) {
+    if entries.is_empty() {
+        return;
+    }
+    
+    match write_manifest(output_dir, entries, config.manifest_append, config) {
+        Ok(total) => {
+            info!("处理清单已写入: {}, 共 {} 条记录", output_dir.display(), total);
+            log.info(format!("处理清单已写入，共 {} 条记录", total));
+        }
+        Err(e) => {
+            warn!("写入处理清单失败: {}", e);
+            log.warn(format!("写入处理清单失败: {}", e));
+        }
+    }
+}
+
 // 创建硬链接的核心函数，包含完整的错误处理
-fn create_hard_link_internal(source: &Path, target: &Path) -> Result<(), FileSystemError> {
-    info!("创建硬链接: {} -> {}", source.display(), target.display());
+pub(crate) fn create_hard_link_internal(source: &Path, target: &Path) -> Result<LinkOutcome, FileSystemError> {
+    create_link_with_mode(source, target, LinkMode::HardLink, true)
+}
+
+// 按指定的链接方式创建目标文件，allow_copy 为 false 时硬链接失败不回退为复制，复制方式直接失败
+pub(crate) fn create_link_with_mode(source: &Path, target: &Path, mode: LinkMode, allow_copy: bool) -> Result<LinkOutcome, FileSystemError> {
+    info!("创建链接 ({:?}): {} -> {}", mode, source.display(), target.display());
     
     // 检查源文件是否存在
     if !source.exists() {
@@ -286,68 +1130,393 @@ fn create_hard_link_internal(source: &Path, target: &Path) -> Result<(), FileSys
         return Err(FileSystemError::TargetExists);
     }
     
-    // 确保目标目录存在
-    if let Some(parent) = final_target.parent() {
-        if !parent.exists() {
-            info!("创建目标目录: {}", parent.display());
-            fs::create_dir_all(parent)?;
+    // 确保目标目录存在，记录本次新建的最上层目录，链接失败时清理掉这些空目录
+    let created_dir = match final_target.parent() {
+        Some(parent) if !parent.exists() => {
+            info!("创建目标目录: {}", parent.display());
+            let first_missing = parent.ancestors()
+                .take_while(|ancestor| !ancestor.exists())
+                .last()
+                .map(Path::to_path_buf);
+            fs::create_dir_all(extended_length_path(parent))?;
+            first_missing
+        }
+        _ => None,
+    };
+    
+    let result = link_in_target_dir(source, final_target, mode, allow_copy);
+    if result.is_err() {
+        if let (Some(created_dir), Some(parent)) = (&created_dir, final_target.parent()) {
+            remove_created_dirs(parent, created_dir);
+        }
+    }
+    result
+}
+
+// 目标目录已存在时的链接步骤：检查文件系统、权限和路径长度后创建链接
+fn link_in_target_dir(source: &Path, final_target: &Path, mode: LinkMode, allow_copy: bool) -> Result<LinkOutcome, FileSystemError> {
+    // 检查源文件和目标文件是否在同一文件系统，符号链接和复制可以跨文件系统
+    if let Some(target_parent) = final_target.parent() {
+        if mode == LinkMode::HardLink && !is_same_filesystem(source, target_parent)? {
+            error!("源文件和目标文件不在同一文件系统上");
+            return Err(FileSystemError::DifferentFilesystems);
+        }
+        
+        // 检查文件权限
+        check_file_permissions(source, target_parent)?;
+    }
+    
+    // 检查路径长度，Windows 下已使用扩展路径，只有超过平台真实上限时才缩短文件名
+    if path_too_long(final_target) {
+        warn!("目标路径过长 ({} 字符)，尝试使用短路径", path_len(final_target));
+        
+        // 尝试使用相对路径或缩短路径
+        if let Some(parent) = final_target.parent() {
+            if let Some(filename) = final_target.file_name() {
+                let short_filename = sanitize_filename(&filename.to_string_lossy());
+                let short_target = parent.join(short_filename);
+                
+                if !path_too_long(&short_target) {
+                    return link_file(source, &short_target, mode, allow_copy);
+                }
+            }
+        }
+        
+        return Err(FileSystemError::Other("目标路径过长".to_string()));
+    }
+    
+    link_file(source, final_target, mode, allow_copy)
+}
+
+// 按链接方式在目标位置创建文件，返回的目标路径不带扩展路径前缀
+fn link_file(source: &Path, target: &Path, mode: LinkMode, allow_copy: bool) -> Result<LinkOutcome, FileSystemError> {
+    let io_target = extended_length_path(target);
+    let outcome = match mode {
+        LinkMode::HardLink => create_hard_link_with_fallback(&extended_length_path(source), &io_target, allow_copy),
+        LinkMode::Symlink => create_symlink(source, &io_target),
+        LinkMode::Copy if !allow_copy => Err(copy_mode_forbidden(source)),
+        LinkMode::Copy => {
+            copy_or_clean_up(&extended_length_path(source), &io_target)?;
+            info!("文件复制成功: {} -> {}", source.display(), target.display());
+            Ok(LinkOutcome { target: target.to_path_buf(), action: LinkAction::Copy })
+        }
+    }?;
+    Ok(LinkOutcome { target: target.to_path_buf(), ..outcome })
+}
+
+// 目标路径长度上限：Windows 使用 \\?\ 扩展路径后约 32767 个字符，Unix 使用 PATH_MAX
+#[cfg(windows)]
+const MAX_PATH_LEN: usize = 32_767;
+#[cfg(unix)]
+const MAX_PATH_LEN: usize = libc::PATH_MAX as usize;
+#[cfg(not(any(unix, windows)))]
+const MAX_PATH_LEN: usize = 4096;
+
+// Windows 传统路径长度限制（MAX_PATH），超过时需要扩展路径前缀
+#[cfg(windows)]
+const WINDOWS_LEGACY_MAX_PATH: usize = 260;
+
+// 路径长度：Windows 按 UTF-16 字符计算，其他平台按字节计算
+#[cfg(windows)]
+fn path_len(path: &Path) -> usize {
+    use std::os::windows::ffi::OsStrExt;
+    path.as_os_str().encode_wide().count()
+}
+
+#[cfg(not(windows))]
+fn path_len(path: &Path) -> usize {
+    path.as_os_str().len()
+}
+
+// 路径是否超过平台真实上限（Windows 下按加上扩展路径前缀后的长度计算）
+pub(crate) fn path_too_long(path: &Path) -> bool {
+    path_len(&extended_length_path(path)) >= MAX_PATH_LEN
+}
+
+// Windows 下超过 MAX_PATH 的路径转为 \\?\ 扩展路径（网络路径为 \\?\UNC\），其他平台原样返回
+#[cfg(windows)]
+pub(crate) fn extended_length_path(path: &Path) -> PathBuf {
+    let raw = path.to_string_lossy();
+    if path_len(path) < WINDOWS_LEGACY_MAX_PATH || raw.starts_with(r"\\?\") {
+        return path.to_path_buf();
+    }
+    
+    // 扩展路径不会被系统规范化，需要绝对路径并统一使用反斜杠
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let raw = absolute.to_string_lossy().replace('/', "\\");
+    match raw.strip_prefix(r"\\") {
+        Some(unc) => PathBuf::from(format!(r"\\?\UNC\{}", unc)),
+        None => PathBuf::from(format!(r"\\?\{}", raw)),
+    }
+}
+
+#[cfg(not(windows))]
+pub(crate) fn extended_length_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+// 复制方式下源文件扩展名被配置禁止复制时的错误
+fn copy_mode_forbidden(source: &Path) -> FileSystemError {
+    let extension = source.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+    FileSystemError::Other(format!("配置禁止复制 .{} 文件", extension))
+}
+
+// Windows 上没有创建符号链接的权限时返回的错误码 (ERROR_PRIVILEGE_NOT_HELD)
+#[cfg(windows)]
+const ERROR_PRIVILEGE_NOT_HELD: i32 = 1314;
+
+// 创建指向源文件绝对路径的符号链接，链接不受源文件所在文件系统的限制
+fn create_symlink(source: &Path, target: &Path) -> Result<LinkOutcome, FileSystemError> {
+    let absolute_source = source.canonicalize()?;
+    
+    #[cfg(unix)]
+    let result = std::os::unix::fs::symlink(&absolute_source, target);
+    #[cfg(windows)]
+    let result = std::os::windows::fs::symlink_file(&absolute_source, target);
+    #[cfg(not(any(unix, windows)))]
+    let result: io::Result<()> = Err(io::Error::new(io::ErrorKind::Unsupported, "当前平台不支持符号链接"));
+    
+    match result {
+        Ok(_) => {
+            info!("符号链接创建成功: {} -> {}", target.display(), absolute_source.display());
+            Ok(LinkOutcome { target: target.to_path_buf(), action: LinkAction::Symlink })
+        }
+        // Windows 默认只有管理员或开启开发者模式时才能创建符号链接
+        #[cfg(windows)]
+        Err(e) if e.raw_os_error() == Some(ERROR_PRIVILEGE_NOT_HELD) => {
+            error!("没有创建符号链接的权限: {}", target.display());
+            Err(FileSystemError::Other("没有创建符号链接的权限，请以管理员身份运行或在 Windows 设置中开启开发者模式".to_string()))
+        }
+        Err(e) => {
+            error!("符号链接创建失败: {}, 错误: {}", target.display(), e);
+            match e.kind() {
+                io::ErrorKind::PermissionDenied => Err(FileSystemError::PermissionDenied),
+                _ => Err(FileSystemError::IoError(e)),
+            }
+        }
+    }
+}
+
+// 批量处理使用的链接入口，按配置执行链接后的附加步骤
+pub(crate) fn create_link_with_config(source: &Path, target: &Path, config: &AppConfig) -> Result<LinkOutcome, FileSystemError> {
+    let target = with_default_extension(source, target.to_path_buf(), config);
+    let mode = LinkMode::parse(&config.link_mode).unwrap_or_default();
+    let outcome = create_link_with_mode(source, &target, mode, copy_forbidden_extension(source, config).is_none())?;
+    
+    if config.fsync_dirs {
+        if let Err(e) = sync_link_to_disk(&outcome) {
+            error!("同步到磁盘失败: {}, 错误: {}", outcome.target.display(), e);
+            return Err(FileSystemError::Other(format!("链接已创建但同步到磁盘失败: {}", e)));
+        }
+    }
+    
+    Ok(outcome)
+}
+
+// 与 create_link_with_config 做相同的检查但不写入任何文件，返回实际执行时的目标路径和链接方式，或者会遇到的错误
+// 目标目录可能尚未创建，文件系统和权限按最近的已存在上级目录检查
+pub(crate) fn preview_link_with_config(source: &Path, target: &Path, config: &AppConfig) -> Result<LinkOutcome, FileSystemError> {
+    let target = with_default_extension(source, target.to_path_buf(), config);
+    let mode = LinkMode::parse(&config.link_mode).unwrap_or_default();
+    let allow_copy = copy_forbidden_extension(source, config).is_none();
+    
+    if !source.exists() {
+        return Err(FileSystemError::SourceNotFound);
+    }
+    
+    let mut final_target = sanitize_path(&target);
+    if final_target.exists() {
+        return Err(FileSystemError::TargetExists);
+    }
+    
+    if let Some(existing_parent) = final_target.ancestors().skip(1).find(|ancestor| ancestor.exists()) {
+        if !existing_parent.is_dir() {
+            return Err(FileSystemError::Other(format!("目标路径的上级不是文件夹: {}", existing_parent.display())));
+        }
+        if mode == LinkMode::HardLink && !is_same_filesystem(source, existing_parent)? {
+            return Err(FileSystemError::DifferentFilesystems);
+        }
+        check_file_permissions(source, existing_parent)?;
+    }
+    
+    // 与 link_in_target_dir 相同：路径过长时尝试清理文件名后的短路径
+    if path_too_long(&final_target) {
+        let short_target = match (final_target.parent(), final_target.file_name()) {
+            (Some(parent), Some(filename)) => Some(parent.join(sanitize_filename(&filename.to_string_lossy()))),
+            _ => None,
+        };
+        match short_target {
+            Some(short_target) if !path_too_long(&short_target) => final_target = short_target,
+            _ => return Err(FileSystemError::Other("目标路径过长".to_string())),
+        }
+    }
+    
+    let action = match mode {
+        LinkMode::HardLink => LinkAction::HardLink,
+        LinkMode::Symlink => LinkAction::Symlink,
+        LinkMode::Copy if !allow_copy => return Err(copy_mode_forbidden(source)),
+        LinkMode::Copy => LinkAction::Copy,
+    };
+    Ok(LinkOutcome { target: final_target, action })
+}
+
+// 批量处理中的链接步骤：预览时只检查不写入，同一批次中已计划的目标按已存在处理，与实际执行时的冲突一致
+pub(crate) struct BatchLinker<'a> {
+    config: &'a AppConfig,
+    dry_run: bool,
+    planned_targets: Mutex<HashSet<PathBuf>>,
+    // 预览中按冲突策略将被覆盖的已存在目标
+    replaced_targets: Mutex<HashSet<PathBuf>>,
+    journal: BatchJournal,
+    // 开启时复制的文件要比较源文件和目标的 CRC32，硬链接和符号链接指向同一文件，不需要校验
+    verify_checksum: bool,
+    checksums: Mutex<HashMap<String, String>>,
+}
+
+impl<'a> BatchLinker<'a> {
+    pub(crate) fn new(config: &'a AppConfig, dry_run: bool) -> Self {
+        BatchLinker {
+            config,
+            dry_run,
+            planned_targets: Mutex::new(HashSet::new()),
+            replaced_targets: Mutex::new(HashSet::new()),
+            journal: BatchJournal::new(),
+            verify_checksum: false,
+            checksums: Mutex::new(HashMap::new()),
+        }
+    }
+    
+    pub(crate) fn with_checksum_verification(mut self, verify_checksum: bool) -> Self {
+        self.verify_checksum = verify_checksum;
+        self
+    }
+    
+    pub(crate) fn link(&self, source: &Path, target: &Path) -> Result<LinkOutcome, FileSystemError> {
+        if !self.dry_run {
+            let outcome = self.journal.link(source, target, self.config)?;
+            if self.verify_checksum && outcome.action == LinkAction::Copy {
+                self.verify_copy(source, &outcome)?;
+            }
+            return Ok(outcome);
+        }
+        
+        // 将被覆盖的目标预览时仍然存在，用同目录下不冲突的路径做相同的检查
+        let replaced = self.replaced_targets.lock().unwrap().contains(target);
+        let outcome = if replaced {
+            let free_target = unique_target_path(target)
+                .ok_or_else(|| FileSystemError::Other("无法生成唯一的文件名".to_string()))?;
+            let outcome = preview_link_with_config(source, &free_target, self.config)?;
+            LinkOutcome { target: target.to_path_buf(), action: outcome.action }
+        } else {
+            preview_link_with_config(source, target, self.config)?
+        };
+        if !self.planned_targets.lock().unwrap().insert(outcome.target.clone()) {
+            return Err(FileSystemError::TargetExists);
+        }
+        Ok(outcome)
+    }
+    
+    // 目标已存在时按冲突策略处理，见 resolve_target_conflict
+    pub(crate) fn resolve_conflict(&self, source: &Path, target: &Path, strategies: &[String]) -> Result<Option<PathBuf>, FileSystemError> {
+        let resolved = resolve_target_conflict(source, target, strategies, self.dry_run)?;
+        if self.dry_run && !strategies.is_empty() && resolved.as_deref() == Some(target) && target.exists() {
+            self.replaced_targets.lock().unwrap().insert(target.to_path_buf());
         }
+        Ok(resolved)
     }
     
-    // 检查源文件和目标文件是否在同一文件系统
-    if let Some(target_parent) = final_target.parent() {
-        if !is_same_filesystem(source, target_parent)? {
-            error!("源文件和目标文件不在同一文件系统上");
-            return Err(FileSystemError::DifferentFilesystems);
+    // 校验复制结果，不一致时删除目标文件并按失败处理
+    fn verify_copy(&self, source: &Path, outcome: &LinkOutcome) -> Result<(), FileSystemError> {
+        let source_crc = file_crc32(source)?;
+        let target_crc = file_crc32(&outcome.target)?;
+        if source_crc != target_crc {
+            error!("复制校验失败: {} ({}) -> {} ({})", source.display(), source_crc, outcome.target.display(), target_crc);
+            if let Err(e) = fs::remove_file(&outcome.target) {
+                error!("删除校验失败的目标文件失败: {}, 错误: {}", outcome.target.display(), e);
+            }
+            return Err(FileSystemError::Other(format!("复制校验失败: 源文件 CRC32 {}，目标 CRC32 {}", source_crc, target_crc)));
         }
         
-        // 检查文件权限
-        check_file_permissions(source, target_parent)?;
+        self.checksums.lock().unwrap().insert(source.to_string_lossy().to_string(), source_crc);
+        Ok(())
     }
     
-    // 检查路径长度（Windows路径限制）
-    let target_path_str = final_target.to_string_lossy();
-    if target_path_str.len() > 260 {
-        warn!("目标路径过长 ({} 字符)，尝试使用短路径", target_path_str.len());
-        
-        // 尝试使用相对路径或缩短路径
-        if let Some(parent) = final_target.parent() {
-            if let Some(filename) = final_target.file_name() {
-                let short_filename = sanitize_filename(&filename.to_string_lossy());
-                let short_target = parent.join(short_filename);
-                
-                if short_target.to_string_lossy().len() <= 260 {
-                    return create_hard_link_with_fallback(source, &short_target);
-                }
-            }
+    pub(crate) fn take_checksums(&self) -> HashMap<String, String> {
+        std::mem::take(&mut *self.checksums.lock().unwrap())
+    }
+    
+    // 保存本次创建的链接供撤销，预览时不保存
+    pub(crate) fn save_journal(&self, operation: &str, sidecars: &[SidecarLink], log: &CommandLog<'_>) {
+        if !self.dry_run {
+            self.journal.save(operation, sidecars, log);
         }
-        
-        return Err(FileSystemError::Other("目标路径过长".to_string()));
+    }
+}
+
+// 源文件扩展名在 copy_forbidden_extensions 中时返回该扩展名（小写），这类文件跨文件系统时只能失败，不能复制
+pub(crate) fn copy_forbidden_extension(source: &Path, config: &AppConfig) -> Option<String> {
+    let extension = source.extension()?.to_string_lossy().to_lowercase();
+    config.copy_forbidden_extensions.iter()
+        .any(|forbidden| forbidden.trim_start_matches('.').eq_ignore_ascii_case(&extension))
+        .then_some(extension)
+}
+
+// 批量处理中按配置链接视频的附属文件，附属文件失败只记录警告，不影响视频本身的结果
+pub(crate) fn link_sidecars_for_batch(source: &Path, outcome: &LinkOutcome, input_files: &HashSet<String>, config: &AppConfig) -> Vec<SidecarLink> {
+    if !config.link_sidecars {
+        return Vec::new();
+    }
+    
+    let (linked, failed) = link_sidecars(source, &outcome.target, &config.sidecar_extensions, input_files, config);
+    for failed_sidecar in &failed {
+        warn!("附属文件链接失败: {}, 错误: {}", failed_sidecar.path, failed_sidecar.error);
     }
     
-    // 创建硬链接
-    create_hard_link_with_fallback(source, final_target)
+    linked
+}
+
+// 将链接结果持久化到磁盘：复制的文件同步内容，并同步父目录的目录项
+pub(crate) fn sync_link_to_disk(outcome: &LinkOutcome) -> io::Result<()> {
+    if outcome.action == LinkAction::Copy {
+        fs::File::open(&outcome.target)?.sync_all()?;
+    }
+    
+    // 只有Unix支持直接打开目录并同步
+    #[cfg(unix)]
+    {
+        if let Some(parent) = outcome.target.parent() {
+            fs::File::open(parent)?.sync_all()?;
+        }
+    }
+    
+    Ok(())
 }
 
 // 创建硬链接，包含回退机制
-fn create_hard_link_with_fallback(source: &Path, target: &Path) -> Result<(), FileSystemError> {
+fn create_hard_link_with_fallback(source: &Path, target: &Path, allow_copy: bool) -> Result<LinkOutcome, FileSystemError> {
     match fs::hard_link(source, target) {
         Ok(_) => {
             info!("硬链接创建成功: {} -> {}", source.display(), target.display());
-            Ok(())
+            Ok(LinkOutcome { target: target.to_path_buf(), action: LinkAction::HardLink })
         }
         Err(e) => {
             error!("硬链接创建失败: {}, 错误: {}", target.display(), e);
             
             // 如果是路径相关错误，尝试复制文件作为回退
             match e.kind() {
+                io::ErrorKind::InvalidInput | 
+                io::ErrorKind::InvalidData if !allow_copy => {
+                    let extension = source.extension().map(|ext| ext.to_string_lossy().to_lowercase()).unwrap_or_default();
+                    warn!("硬链接失败，配置禁止复制 .{} 文件: {}", extension, source.display());
+                    Err(FileSystemError::CopyForbidden(extension))
+                }
                 io::ErrorKind::InvalidInput | 
                 io::ErrorKind::InvalidData => {
                     warn!("硬链接失败，尝试复制文件作为回退");
-                    match fs::copy(source, target) {
+                    match copy_or_clean_up(source, target) {
                         Ok(_) => {
                             info!("文件复制成功: {} -> {}", source.display(), target.display());
-                            Ok(())
+                            Ok(LinkOutcome { target: target.to_path_buf(), action: LinkAction::Copy })
                         }
                         Err(copy_err) => {
                             error!("文件复制也失败: {}", copy_err);
@@ -355,70 +1524,165 @@ fn create_hard_link_with_fallback(source: &Path, target: &Path) -> Result<(), Fi
                         }
                     }
                 }
+                // 源文件只读不会导致失败，EPERM 通常来自不可变属性或 protected_hardlinks 限制
+                io::ErrorKind::PermissionDenied if has_immutable_attribute(source) => {
+                    Err(FileSystemError::SourceImmutable)
+                }
+                io::ErrorKind::PermissionDenied => Err(FileSystemError::PermissionDenied),
                 _ => Err(FileSystemError::IoError(e))
             }
         }
     }
 }
 
+// 复制文件，失败时删除写了一半的目标文件，避免留下看似完整的截断文件
+// 目标在复制前已存在且大小与源文件一致时保留，说明复制尚未改动它
+pub(crate) fn copy_or_clean_up(source: &Path, target: &Path) -> io::Result<u64> {
+    let existed = target.exists();
+    
+    fs::copy(source, target).inspect_err(|_| {
+        let source_size = fs::metadata(source).map(|metadata| metadata.len()).ok();
+        let target_size = fs::metadata(target).map(|metadata| metadata.len()).ok();
+        let partial = target_size.is_some() && (!existed || target_size != source_size);
+        if partial {
+            warn!("复制失败，删除不完整的目标文件: {}", target.display());
+            if let Err(remove_err) = fs::remove_file(target) {
+                error!("删除不完整的目标文件失败: {}, 错误: {}", target.display(), remove_err);
+            }
+        }
+    })
+}
+
+// 自 dir 向上删除本次创建的空目录，直到 created_dir（包含）为止，非空目录保留
+pub(crate) fn remove_created_dirs(dir: &Path, created_dir: &Path) {
+    for ancestor in dir.ancestors() {
+        if !ancestor.starts_with(created_dir) || fs::remove_dir(ancestor).is_err() {
+            break;
+        }
+        info!("删除链接失败后留下的空目录: {}", ancestor.display());
+    }
+}
+
 #[command]
-pub async fn create_hard_link(source: String, target: String, log_store: State<'_, LogStore>) -> Result<bool, String> {
+pub async fn create_hard_link(
+    source: String,
+    target: String,
+    link_mode: Option<String>,
+    log_store: State<'_, LogStore>
+) -> Result<bool, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_CREATE_HARD_LINK);
+    
     let source_path = PathBuf::from(&source);
     let target_path = PathBuf::from(&target);
+    // 未指定链接方式时使用配置中的 link_mode
+    let mode = LinkMode::parse(link_mode.as_deref().unwrap_or(&read_app_config().link_mode))?;
     
-    add_log_entry(&log_store, LogLevel::INFO, format!("开始创建硬链接: {} -> {}", source, target), Some("硬链接创建".to_string()));
+    log.info(format!("开始创建硬链接: {} -> {}", source, target));
     
-    match create_hard_link_internal(&source_path, &target_path) {
+    match create_link_with_mode(&source_path, &target_path, mode, true) {
         Ok(_) => {
             info!("硬链接创建成功: {} -> {}", source, target);
-            add_log_entry(&log_store, LogLevel::INFO, format!("硬链接创建成功: {} -> {}", source, target), Some("硬链接创建".to_string()));
+            log.info(format!("硬链接创建成功: {} -> {}", source, target));
             Ok(true)
         },
         Err(e) => {
             error!("硬链接创建失败: {} -> {}, 错误: {}", source, target, e);
-            add_log_entry(&log_store, LogLevel::ERROR, format!("硬链接创建失败: {} -> {}, 错误: {}", source, target, e), Some("硬链接创建".to_string()));
-            Err(e.to_string())
+            log.error(format!("硬链接创建失败: {} -> {}, 错误: {}", source, target, e));
+            Err(e.into())
         }
     }
 }
 
 #[command]
-pub async fn batch_process_files(files: Vec<String>, output_dir: String, log_store: State<'_, LogStore>) -> Result<ProcessResult, String> {
-    use rayon::prelude::*;
+pub async fn batch_process_files(
+    files: Vec<String>,
+    output_dir: String,
+    scanned_sizes: Option<HashMap<String, u64>>,
+    link_mode: Option<String>,
+    dry_run: Option<bool>,
+    verify_checksum: Option<bool>,
+    app: AppHandle,
+    cancel: State<'_, BatchCancelFlag>,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
     use std::sync::{Arc, Mutex};
     
+    let log = CommandLog::new(&log_store, SOURCE_BATCH_PROCESS_FILES);
+    let cancel = cancel.inner().clone();
+    // 新的批量处理开始时清除上一次的停止请求
+    cancel.store(false, Ordering::Relaxed);
+    let dry_run = dry_run.unwrap_or(false);
+    let mut config = read_app_config();
+    // 指定的链接方式只对本次批量处理生效
+    if let Some(link_mode) = link_mode {
+        LinkMode::parse(&link_mode)?;
+        config.link_mode = link_mode;
+    }
+    
     info!("开始批量处理 {} 个文件到目录: {}", files.len(), output_dir);
-    add_log_entry(&log_store, LogLevel::INFO, format!("开始批量处理 {} 个文件到目录: {}", files.len(), output_dir), Some("批量处理".to_string()));
+    log.info(format!("开始批量处理 {} 个文件到目录: {}", files.len(), output_dir));
     
     // 清理输出目录路径
     let sanitized_output_dir = sanitize_path(&PathBuf::from(&output_dir));
     
-    // 创建输出目录（如果不存在）
-    if !sanitized_output_dir.exists() {
-        if let Err(e) = fs::create_dir_all(&sanitized_output_dir) {
-            error!("创建输出目录失败: {}", e);
-            return Err(format!("创建输出目录失败: {}", e));
+    // 创建输出目录（如果不存在），预览时只检查不创建
+    if dry_run {
+        if sanitized_output_dir.exists() && !sanitized_output_dir.is_dir() {
+            return Err(format!("输出路径已存在且不是文件夹: {}", sanitized_output_dir.display()).into());
         }
+    } else if let Err(e) = ensure_output_dir(Path::new(&output_dir)) {
+        error!("创建输出目录失败: {}", e);
+        return Err(e.into());
     }
     
     // 使用线程安全的容器收集结果
     let processed_files = Arc::new(Mutex::new(Vec::new()));
     let failed_files = Arc::new(Mutex::new(Vec::new()));
-    
-    // 并行处理文件
-    files.par_iter().for_each(|file_path| {
+    let skipped_files = Arc::new(Mutex::new(Vec::new()));
+    let manifest_entries = Arc::new(Mutex::new(Vec::new()));
+    let linked_sidecars = Arc::new(Mutex::new(Vec::new()));
+    let link_actions = Arc::new(Mutex::new(HashMap::new()));
+    let cancelled_files = Arc::new(Mutex::new(Vec::new()));
+    let linker = BatchLinker::new(&config, dry_run).with_checksum_verification(verify_checksum.unwrap_or(false));
+    // 输入列表中的文件会单独处理，不作为附属文件重复链接
+    let input_files: HashSet<String> = files.iter().cloned().collect();
+    
+    // 并行处理文件（可按源设备分组）
+    // 定期通过 throughput 事件报告处理速率和预计剩余时间
+    let tracker = ThroughputTracker::new(app, SOURCE_BATCH_PROCESS_FILES, &files);
+    
+    process_grouped_by_device(&files, &config, |file_path| {
+        // 收到停止请求后剩余的文件不再处理，记为已取消
+        if cancel.load(Ordering::Relaxed) {
+            cancelled_files.lock().unwrap().push(file_path.clone());
+            tracker.cancelled(Path::new(file_path));
+            return;
+        }
+        
         let source = PathBuf::from(file_path);
+        let mut pending = tracker.begin(&source);
+        
+        // 跳过空文件和未下载完成的文件
+        if let Some(reason) = incomplete_source_reason(&source, &config, scanned_sizes.as_ref()) {
+            let mut skipped = skipped_files.lock().unwrap();
+            skipped.push(FileError {
+                path: file_path.clone(),
+                error: reason,
+            });
+            warn!("跳过未完成的文件: {}", file_path);
+            return;
+        }
         
         // 获取文件名
         match source.file_name() {
             Some(file_name) => {
                 let sanitized_filename = sanitize_filename(&file_name.to_string_lossy());
-                let target = sanitized_output_dir.join(&sanitized_filename);
+                let target = place_subtitle_target(sanitized_output_dir.join(&sanitized_filename), &config);
                 
-                // 检查目标路径长度
+                // 检查目标路径长度，只有超过平台真实上限时才缩短文件名
                 let target_path_str = target.to_string_lossy();
-                if target_path_str.len() > 260 {
-                    warn!("目标路径过长: {} ({} 字符)", target_path_str, target_path_str.len());
+                if path_too_long(&target) {
+                    warn!("目标路径过长: {} ({} 字符)", target_path_str, path_len(&target));
                     
                     // 尝试缩短文件名
                     if let Some(file_stem) = target.file_stem() {
@@ -433,11 +1697,19 @@ pub async fn batch_process_files(files: Vec<String>, output_dir: String, log_sto
                             let short_filename = format!("{}.{}", short_stem, extension.to_string_lossy());
                             let short_target = sanitized_output_dir.join(short_filename);
                             
-                            if short_target.to_string_lossy().len() <= 260 {
-                                match create_hard_link_internal(&source, &short_target) {
-                                    Ok(_) => {
+                            if !path_too_long(&short_target) {
+                                match linker.link(&source, &short_target) {
+                                    Ok(outcome) => {
+                                        pending.succeeded(outcome.action);
+                                        if !dry_run {
+                                            linked_sidecars.lock().unwrap().extend(link_sidecars_for_batch(&source, &outcome, &input_files, &config));
+                                        }
+                                        if config.write_manifest && !dry_run {
+                                            manifest_entries.lock().unwrap().push(build_manifest_entry(&source, &outcome.target, &sanitized_output_dir, outcome.action.as_str()));
+                                        }
+                                        link_actions.lock().unwrap().insert(file_path.clone(), outcome.action.as_str().to_string());
                                         let mut processed = processed_files.lock().unwrap();
-                                        processed.push(file_path.clone());
+                                        processed.push(if dry_run { outcome.target.to_string_lossy().to_string() } else { file_path.clone() });
                                         return;
                                     },
                                     Err(e) => {
@@ -458,18 +1730,26 @@ pub async fn batch_process_files(files: Vec<String>, output_dir: String, log_sto
                     let mut failed = failed_files.lock().unwrap();
                     failed.push(FileError {
                         path: file_path.clone(),
-                        error: format!("目标路径过长: {} 字符", target_path_str.len()),
+                        error: format!("目标路径过长: {} 字符", path_len(&target)),
                     });
                     warn!("目标路径过长，无法处理: {}", file_path);
                     return;
                 }
                 
                 // 尝试创建硬链接
-                match create_hard_link_internal(&source, &target) {
-                    Ok(_) => {
+                match linker.link(&source, &target) {
+                    Ok(outcome) => {
+                        pending.succeeded(outcome.action);
+                        if !dry_run {
+                            linked_sidecars.lock().unwrap().extend(link_sidecars_for_batch(&source, &outcome, &input_files, &config));
+                        }
                         // 成功处理
+                        if config.write_manifest && !dry_run {
+                            manifest_entries.lock().unwrap().push(build_manifest_entry(&source, &outcome.target, &sanitized_output_dir, outcome.action.as_str()));
+                        }
+                        link_actions.lock().unwrap().insert(file_path.clone(), outcome.action.as_str().to_string());
                         let mut processed = processed_files.lock().unwrap();
-                        processed.push(file_path.clone());
+                        processed.push(if dry_run { outcome.target.to_string_lossy().to_string() } else { file_path.clone() });
                     },
                     Err(e) => {
                         // 处理失败
@@ -495,6 +1775,7 @@ pub async fn batch_process_files(files: Vec<String>, output_dir: String, log_sto
             }
         }
     });
+    tracker.finish();
     
     // 获取处理结果
     let processed = Arc::try_unwrap(processed_files)
@@ -507,45 +1788,205 @@ pub async fn batch_process_files(files: Vec<String>, output_dir: String, log_sto
         .into_inner()
         .unwrap();
     
+    let skipped = Arc::try_unwrap(skipped_files)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let mut manifest_entries = Arc::try_unwrap(manifest_entries)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let linked_sidecars = Arc::try_unwrap(linked_sidecars)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let link_actions = Arc::try_unwrap(link_actions)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let cancelled = Arc::try_unwrap(cancelled_files)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    if !cancelled.is_empty() {
+        warn!("批量处理已停止，{} 个文件未处理", cancelled.len());
+        log.warn(format!("批量处理已停止，{} 个文件未处理", cancelled.len()));
+    }
+    
+    if !linked_sidecars.is_empty() {
+        log.info(format!("已链接 {} 个附属文件", linked_sidecars.len()));
+    }
+    
+    if config.write_manifest && !dry_run {
+        manifest_entries.extend(linked_sidecars.iter().map(|link| {
+            build_manifest_entry(Path::new(&link.source), Path::new(&link.target), &sanitized_output_dir, &link.action)
+        }));
+        write_batch_manifest(&log, &sanitized_output_dir, manifest_entries, &config);
+    }
+    
     let success_count = processed.len();
     let failed_count = failed.len();
+    let skipped_count = skipped.len();
+    let cancelled_count = cancelled.len();
     let total_count = files.len();
     
-    info!("批量处理完成: 成功 {}, 失败 {}, 总计 {}", success_count, failed_count, total_count);
-    add_log_entry(&log_store, LogLevel::INFO, format!("批量处理完成: 成功 {}, 失败 {}, 总计 {}", success_count, failed_count, total_count), Some("批量处理".to_string()));
+    info!("批量处理完成: 成功 {}, 失败 {}, 跳过 {}, 总计 {}", success_count, failed_count, skipped_count, total_count);
+    log.info(format!("批量处理完成: 成功 {}, 失败 {}, 跳过 {}, 总计 {}", success_count, failed_count, skipped_count, total_count));
     
     // 如果有失败的文件，输出详细信息
     if failed_count > 0 {
         error!("处理失败的文件详情:");
-        add_log_entry(&log_store, LogLevel::WARN, format!("批量处理中有 {} 个文件失败", failed_count), Some("批量处理".to_string()));
+        log.warn(format!("批量处理中有 {} 个文件失败", failed_count));
         for failed_file in &failed {
             error!("  - {}: {}", failed_file.path, failed_file.error);
-            add_log_entry(&log_store, LogLevel::ERROR, format!("文件处理失败: {} - {}", failed_file.path, failed_file.error), Some("批量处理".to_string()));
+            log.error(format!("文件处理失败: {} - {}", failed_file.path, failed_file.error));
         }
     }
     
-    Ok(ProcessResult {
+    let result = ProcessResult {
         success: failed_count == 0,
-        message: format!("处理完成: 成功 {}/{}, 失败 {}", success_count, total_count, failed_count),
+        message: if dry_run {
+            format!("预览: 将处理 {}/{}, 失败 {}, 跳过 {}", success_count, total_count, failed_count, skipped_count)
+        } else if cancelled_count > 0 {
+            format!("已停止: 成功 {}/{}, 失败 {}, 跳过 {}, 取消 {}", success_count, total_count, failed_count, skipped_count, cancelled_count)
+        } else {
+            format!("处理完成: 成功 {}/{}, 失败 {}, 跳过 {}", success_count, total_count, failed_count, skipped_count)
+        },
         processed_files: processed,
         failed_files: failed,
-    })
+        skipped_files: skipped,
+        linked_sidecars,
+        warnings: Vec::new(),
+        link_actions,
+        cancelled_files: cancelled,
+        checksums: linker.take_checksums(),
+    };
+    linker.save_journal(SOURCE_BATCH_PROCESS_FILES, &result.linked_sidecars, &log);
+    if !dry_run {
+        record_operation(SOURCE_BATCH_PROCESS_FILES, &output_dir, &result, &config);
+    }
+    
+    Ok(result)
+}
+
+// 执行前检查计划中的源文件是否仍然存在且可读
+#[command]
+pub async fn precheck_sources(sources: Vec<String>) -> Result<Vec<SourceStatus>, AppError> {
+    use rayon::prelude::*;
+    
+    let statuses = sources.par_iter()
+        .map(|source| {
+            let path = Path::new(source);
+            match fs::metadata(path) {
+                Ok(metadata) if metadata.is_file() => {
+                    let readable = fs::File::open(path);
+                    SourceStatus {
+                        path: source.clone(),
+                        exists: true,
+                        readable: readable.is_ok(),
+                        size: Some(metadata.len()),
+                        error: readable.err().map(|e| format!("无法读取源文件: {}", e)),
+                    }
+                },
+                Ok(_) => SourceStatus {
+                    path: source.clone(),
+                    exists: true,
+                    readable: false,
+                    size: None,
+                    error: Some("路径是目录，不是文件".to_string()),
+                },
+                Err(e) => SourceStatus {
+                    path: source.clone(),
+                    exists: e.kind() != io::ErrorKind::NotFound,
+                    readable: false,
+                    size: None,
+                    error: Some(FileSystemError::from(e).to_string()),
+                },
+            }
+        })
+        .collect();
+    
+    Ok(statuses)
+}
+
+// 检查计划中的目标路径是否存在仅大小写不同的冲突（包括与磁盘上已有文件的冲突）
+// 目标文件系统区分大小写时直接返回空列表
+#[command]
+pub async fn check_case_collisions(targets: Vec<String>, output_dir: String) -> Result<Vec<CaseCollision>, AppError> {
+    let output_path = PathBuf::from(&output_dir);
+    let probe_dir = output_path.ancestors()
+        .find(|ancestor| ancestor.is_dir())
+        .ok_or("输出目录无法解析到任何已存在的位置")?;
+    
+    if !is_case_insensitive(probe_dir).map_err(|e| format!("检测文件系统大小写敏感性失败: {}", e))? {
+        return Ok(Vec::new());
+    }
+    
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for target in &targets {
+        groups.entry(target.to_lowercase()).or_default().push(target.clone());
+    }
+    
+    let planned: HashSet<&String> = targets.iter().collect();
+    let mut collisions = Vec::new();
+    
+    for (key, mut group) in groups {
+        group.sort();
+        group.dedup();
+        
+        // 同一文件夹中已存在、名称仅大小写不同且不在计划内的文件
+        let mut existing: Vec<String> = Path::new(&group[0]).parent()
+            .and_then(|parent| fs::read_dir(parent).ok())
+            .map(|entries| {
+                entries.filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path().to_string_lossy().to_string())
+                    .filter(|path| path.to_lowercase() == key && !planned.contains(path))
+                    .collect()
+            })
+            .unwrap_or_default();
+        existing.sort();
+        
+        if group.len() > 1 || !existing.is_empty() {
+            collisions.push(CaseCollision {
+                key,
+                targets: group,
+                existing,
+            });
+        }
+    }
+    
+    collisions.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(collisions)
+}
+
+// 在目录中创建一个大写名称的临时文件，再用小写名称访问，判断文件系统是否大小写不敏感
+fn is_case_insensitive(dir: &Path) -> io::Result<bool> {
+    let probe_name = format!(".CASE-PROBE-{}", uuid::Uuid::new_v4().simple());
+    let probe = dir.join(&probe_name);
+    fs::write(&probe, b"")?;
+    let insensitive = dir.join(probe_name.to_lowercase()).exists();
+    let _ = fs::remove_file(&probe);
+    Ok(insensitive)
 }
 
 // 检查文件是否可以被硬链接（预检查）
 #[command]
-pub async fn check_hardlink_capability(source_dir: String, target_dir: String) -> Result<bool, String> {
+pub async fn check_hardlink_capability(source_dir: String, target_dir: String) -> Result<bool, AppError> {
     let source_path = PathBuf::from(&source_dir);
     let target_path = PathBuf::from(&target_dir);
     
     if !source_path.exists() {
-        return Err("源目录不存在".to_string());
+        return Err(AppError::NotFound("源目录不存在".to_string()));
     }
     
     // 确保目标目录存在
     if !target_path.exists() {
         if let Err(e) = fs::create_dir_all(&target_path) {
-            return Err(format!("无法创建目标目录: {}", e));
+            return Err(format!("无法创建目标目录: {}", e).into());
         }
     }
     
@@ -553,11 +1994,11 @@ pub async fn check_hardlink_capability(source_dir: String, target_dir: String) -
     match is_same_filesystem(&source_path, &target_path) {
         Ok(same) => {
             if !same {
-                return Err("源目录和目标目录不在同一文件系统上，无法创建硬链接".to_string());
+                return Err(AppError::DifferentFilesystems);
             }
         },
         Err(e) => {
-            return Err(format!("检查文件系统失败: {}", e));
+            return Err(format!("检查文件系统失败: {}", e).into());
         }
     }
     
@@ -565,7 +2006,7 @@ pub async fn check_hardlink_capability(source_dir: String, target_dir: String) -
     match check_file_permissions(&source_path, &target_path) {
         Ok(_) => {},
         Err(e) => {
-            return Err(format!("权限检查失败: {}", e));
+            return Err(format!("权限检查失败: {}", e).into());
         }
     }
     
@@ -574,12 +2015,12 @@ pub async fn check_hardlink_capability(source_dir: String, target_dir: String) -
 
 // 获取文件系统信息
 #[command]
-pub async fn get_filesystem_info(path: String) -> Result<HashMap<String, String>, String> {
+pub async fn get_filesystem_info(path: String) -> Result<HashMap<String, String>, AppError> {
     let path_buf = PathBuf::from(&path);
     let mut info = HashMap::new();
     
     if !path_buf.exists() {
-        return Err("路径不存在".to_string());
+        return Err(AppError::NotFound("路径不存在".to_string()));
     }
     
     // 获取基本信息
@@ -621,118 +2062,237 @@ pub async fn get_filesystem_info(path: String) -> Result<HashMap<String, String>
             }
         },
         Err(e) => {
-            return Err(format!("获取文件元数据失败: {}", e));
+            return Err(format!("获取文件元数据失败: {}", e).into());
         }
     }
     
     Ok(info)
 }
 
-// 处理文件冲突
+// 冲突处理实际执行的操作
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictAction {
+    // 目标不存在，没有冲突，不做任何操作，由调用方自行链接
+    NoConflict,
+    // 目标不存在，直接链接到原目标路径（仅 link_with_conflict_strategy）
+    Linked,
+    Skipped,
+    Overwritten,
+    Renamed,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConflictResolution {
+    pub action: ConflictAction,
+    // 最终的目标路径，跳过时为已存在的目标
+    pub target: String,
+    // 选中的策略，目标不存在时为空
+    pub strategy: Option<String>,
+}
+
+// 冲突处理策略，可用逗号串联，按顺序取第一个条件成立的策略
+// skip_if_same_size、replace_if_larger 是条件策略，条件不成立时继续看下一个；skip、overwrite、rename 无条件执行
+// 例如 "skip_if_same_size,replace_if_larger,rename"：大小相同跳过，源文件更大时覆盖，否则重命名
+const CONFLICT_STRATEGIES: [&str; 5] = ["skip", "overwrite", "rename", "skip_if_same_size", "replace_if_larger"];
+
+// 处理文件冲突：目标不存在时不做任何操作并返回 no_conflict，存在时按策略跳过、覆盖或重命名后链接
+// compare_modified 为 true 时，skip_if_same_size 还要求修改时间相同（精确到秒）；所有条件策略都不成立时跳过
 #[command]
 pub async fn handle_file_conflict(
     source: String,
     target: String,
-    strategy: String
-) -> Result<bool, String> {
-    let source_path = PathBuf::from(&source);
-    let target_path = PathBuf::from(&target);
-    
+    strategy: String,
+    compare_modified: Option<bool>
+) -> Result<ConflictResolution, AppError> {
+    let strategies = parse_conflict_strategies(&strategy)?;
+    resolve_file_conflict(Path::new(&source), Path::new(&target), &strategies, compare_modified.unwrap_or(false), false)
+}
+
+// 与 handle_file_conflict 相同，但目标不存在时直接链接并返回 linked，返回结果总能说明文件是否已链接
+#[command]
+pub async fn link_with_conflict_strategy(
+    source: String,
+    target: String,
+    strategy: String,
+    compare_modified: Option<bool>
+) -> Result<ConflictResolution, AppError> {
+    let strategies = parse_conflict_strategies(&strategy)?;
+    resolve_file_conflict(Path::new(&source), Path::new(&target), &strategies, compare_modified.unwrap_or(false), true)
+}
+
+// link_when_free 为 false 时目标不存在不做任何操作，为 true 时直接链接
+fn resolve_file_conflict(
+    source_path: &Path,
+    target_path: &Path,
+    strategies: &[String],
+    compare_modified: bool,
+    link_when_free: bool
+) -> Result<ConflictResolution, AppError> {
     if !target_path.exists() {
         // 如果目标文件不存在，则不存在冲突
-        return Ok(false);
+        let (action, final_target) = if link_when_free {
+            let outcome = create_hard_link_internal(source_path, target_path)?;
+            (ConflictAction::Linked, outcome.target)
+        } else {
+            (ConflictAction::NoConflict, target_path.to_path_buf())
+        };
+        return Ok(ConflictResolution {
+            action,
+            target: final_target.to_string_lossy().to_string(),
+            strategy: None,
+        });
     }
     
-    match strategy.as_str() {
-        "skip" => {
-            // 跳过此文件
-            info!("跳过已存在的文件: {}", target_path.display());
-            Ok(true)
-        },
-        "overwrite" => {
+    let chosen = choose_conflict_strategy(strategies, source_path, target_path, compare_modified)?;
+    
+    let (action, final_target) = match chosen {
+        Some("overwrite") | Some("replace_if_larger") => {
             // 覆盖目标文件
             info!("覆盖已存在的文件: {}", target_path.display());
-            if let Err(e) = fs::remove_file(&target_path) {
+            if let Err(e) = fs::remove_file(target_path) {
                 error!("删除已存在的文件失败: {}", e);
-                return Err(format!("删除已存在的文件失败: {}", e));
+                return Err(format!("删除已存在的文件失败: {}", e).into());
             }
             
             // 创建硬链接
-            match create_hard_link_internal(&source_path, &target_path) {
-                Ok(_) => Ok(true),
-                Err(e) => Err(e.to_string())
-            }
+            let outcome = create_hard_link_internal(source_path, target_path)?;
+            (ConflictAction::Overwritten, outcome.target)
         },
-        "rename" => {
+        Some("rename") => {
             // 自动重命名目标文件
-            let file_stem = target_path.file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("file");
-            
-            let extension = target_path.extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or("");
-            
-            // 生成新的文件名 (name_1.ext, name_2.ext, ...)
-            let mut counter = 1;
-            let mut new_target_path;
-            
-            loop {
-                let new_name = if extension.is_empty() {
-                    format!("{}_{}", file_stem, counter)
-                } else {
-                    format!("{}_{}.{}", file_stem, counter, extension)
-                };
-                
-                new_target_path = target_path.with_file_name(new_name);
-                
-                if !new_target_path.exists() {
-                    break;
-                }
-                
-                counter += 1;
-                if counter > 100 {
-                    // 防止无限循环
-                    return Err("无法生成唯一的文件名".to_string());
-                }
-            }
+            let new_target_path = unique_target_path(target_path)
+                .ok_or("无法生成唯一的文件名")?;
             
             info!("重命名目标文件: {} -> {}", target_path.display(), new_target_path.display());
             
             // 创建硬链接
-            match create_hard_link_internal(&source_path, &new_target_path) {
-                Ok(_) => Ok(true),
-                Err(e) => Err(e.to_string())
+            let outcome = create_hard_link_internal(source_path, &new_target_path)?;
+            (ConflictAction::Renamed, outcome.target)
+        },
+        _ => {
+            // 跳过此文件
+            info!("跳过已存在的文件: {}", target_path.display());
+            (ConflictAction::Skipped, target_path.to_path_buf())
+        },
+    };
+    
+    Ok(ConflictResolution {
+        action,
+        target: final_target.to_string_lossy().to_string(),
+        strategy: chosen.map(str::to_string),
+    })
+}
+
+// 解析逗号串联的冲突处理策略，拒绝未知或空的策略
+pub(crate) fn parse_conflict_strategies(strategy: &str) -> Result<Vec<String>, AppError> {
+    let strategies: Vec<String> = strategy.split(',').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect();
+    if let Some(unknown) = strategies.iter().find(|s| !CONFLICT_STRATEGIES.contains(&s.as_str())) {
+        return Err(format!("不支持的冲突处理策略: {}，可选值: {}", unknown, CONFLICT_STRATEGIES.join(", ")).into());
+    }
+    if strategies.is_empty() {
+        return Err("没有指定冲突处理策略".into());
+    }
+    Ok(strategies)
+}
+
+// 批量处理的冲突处理策略，默认 fail 保持原有行为：目标已存在时按失败处理，返回空列表
+pub(crate) fn batch_conflict_strategies(strategy: Option<&str>) -> Result<Vec<String>, AppError> {
+    match strategy.map(str::trim) {
+        None | Some("") | Some("fail") => Ok(Vec::new()),
+        Some(strategy) => parse_conflict_strategies(strategy),
+    }
+}
+
+// 目标已存在时按顺序选出第一个条件成立的策略，都不成立时返回 None（跳过）
+fn choose_conflict_strategy<'s>(strategies: &'s [String], source: &Path, target: &Path, compare_modified: bool) -> Result<Option<&'s str>, String> {
+    let source_metadata = fs::metadata(source)
+        .map_err(|e| format!("获取源文件元数据失败: {}", e))?;
+    let target_metadata = fs::metadata(target)
+        .map_err(|e| format!("获取目标文件元数据失败: {}", e))?;
+    let same_modified = || -> bool {
+        let seconds = |metadata: &fs::Metadata| metadata.modified().ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs());
+        matches!((seconds(&source_metadata), seconds(&target_metadata)), (Some(a), Some(b)) if a == b)
+    };
+    
+    Ok(strategies.iter().map(String::as_str).find(|strategy| match *strategy {
+        "skip_if_same_size" => source_metadata.len() == target_metadata.len()
+            && (!compare_modified || same_modified()),
+        "replace_if_larger" => source_metadata.len() > target_metadata.len(),
+        _ => true,
+    }))
+}
+
+// 批量处理中目标已存在时按冲突策略处理，返回要链接的目标，跳过时返回 None；没有策略时原样返回，由链接步骤报告目标已存在
+// 覆盖时先删除已存在的目标，预览时不删除
+pub(crate) fn resolve_target_conflict(source: &Path, target: &Path, strategies: &[String], dry_run: bool) -> Result<Option<PathBuf>, FileSystemError> {
+    if strategies.is_empty() || !target.exists() {
+        return Ok(Some(target.to_path_buf()));
+    }
+    
+    match choose_conflict_strategy(strategies, source, target, false).map_err(FileSystemError::Other)? {
+        Some("overwrite") | Some("replace_if_larger") => {
+            if !dry_run {
+                info!("覆盖已存在的文件: {}", target.display());
+                fs::remove_file(target)
+                    .map_err(|e| FileSystemError::Other(format!("删除已存在的文件失败: {}", e)))?;
             }
+            Ok(Some(target.to_path_buf()))
         },
-        _ => Err(format!("不支持的冲突处理策略: {}", strategy))
+        Some("rename") => unique_target_path(target)
+            .map(Some)
+            .ok_or_else(|| FileSystemError::Other("无法生成唯一的文件名".to_string())),
+        _ => Ok(None),
     }
 }
 
+// 为已存在的目标生成不冲突的路径 (name_1.ext, name_2.ext, ...)，尝试100次后放弃
+pub(crate) fn unique_target_path(target_path: &Path) -> Option<PathBuf> {
+    let file_stem = target_path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("file");
+    
+    let extension = target_path.extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("");
+    
+    (1..=100).map(|counter| {
+        let new_name = if extension.is_empty() {
+            format!("{}_{}", file_stem, counter)
+        } else {
+            format!("{}_{}.{}", file_stem, counter, extension)
+        };
+        target_path.with_file_name(new_name)
+    })
+    .find(|candidate| !candidate.exists())
+}
+
 // 检查路径是否为目录
 #[command]
-pub async fn is_directory(path: String) -> Result<bool, String> {
+pub async fn is_directory(path: String) -> Result<bool, AppError> {
     let path_buf = PathBuf::from(&path);
     match fs::metadata(&path_buf) {
         Ok(metadata) => Ok(metadata.is_dir()),
-        Err(e) => Err(format!("无法获取路径信息: {}", e))
+        Err(e) => Err(format!("无法获取路径信息: {}", e).into())
     }
 }
 
 // 获取单个文件信息
 #[command]
-pub async fn get_file_info(path: String) -> Result<FileInfo, String> {
+pub async fn get_file_info(path: String) -> Result<FileInfo, AppError> {
     let path_buf = PathBuf::from(&path);
     
     if !path_buf.exists() {
-        return Err("文件不存在".to_string());
+        return Err(AppError::NotFound("文件不存在".to_string()));
     }
     
     let metadata = fs::metadata(&path_buf)
         .map_err(|e| format!("无法获取文件元数据: {}", e))?;
     
     if metadata.is_dir() {
-        return Err("路径是目录，不是文件".to_string());
+        return Err("路径是目录，不是文件".into());
     }
     
     let file_name = path_buf.file_name()
@@ -745,88 +2305,360 @@ pub async fn get_file_info(path: String) -> Result<FileInfo, String> {
         .unwrap_or("")
         .to_lowercase();
     
-    let is_video = matches!(extension.as_str(), "mkv" | "mp4" | "avi" | "mov");
-    let is_subtitle = matches!(extension.as_str(), "ass" | "srt" | "vtt");
+    let (kind, probed_container) = classify_file(&path_buf, &extension, &read_app_config());
+    
+    if kind == FileKind::Other {
+        return Err(if extension.is_empty() {
+            "文件没有扩展名：未开启接受无扩展名文件，或文件头不是已知的视频容器".into()
+        } else {
+            "不支持的文件类型".into()
+        });
+    }
+    
+    Ok(FileInfo {
+        path: path_buf.to_string_lossy().to_string(),
+        name: file_name,
+        size: metadata.len(),
+        file_type: extension,
+        is_video: kind == FileKind::Video,
+        is_subtitle: kind == FileKind::Subtitle,
+        kind,
+        modified: modified_secs(&metadata),
+        probed_container,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PathSanitizationResult {
+    // 原路径 -> 清理后的路径
+    pub paths: HashMap<String, String>,
+    // 当前生效的字符替换规则，按顺序应用
+    pub rules: Vec<CharReplacementRule>,
+}
+
+// 测试路径清理功能，同时返回生效的字符替换规则
+#[command]
+pub async fn test_path_sanitization(paths: Vec<String>) -> Result<PathSanitizationResult, AppError> {
+    let mut sanitized = HashMap::new();
+    
+    for path in paths {
+        let original_path = PathBuf::from(&path);
+        let sanitized_path = sanitize_path(&original_path);
+        sanitized.insert(path, sanitized_path.to_string_lossy().to_string());
+    }
+    
+    Ok(PathSanitizationResult {
+        paths: sanitized,
+        rules: CHAR_REPLACEMENTS.read().unwrap().clone(),
+    })
+}
+
+// 预览文件处理结果
+#[command]
+pub async fn preview_file_processing(
+    files: Vec<String>, 
+    output_dir: String,
+    rename_map: HashMap<String, String>,
+    anime_metadata: Option<AniListResponse>
+) -> Result<HashMap<String, String>, AppError> {
+    let config = read_app_config();
+    let sanitized_output_dir = sanitize_path(&PathBuf::from(&output_dir));
+    
+    Ok(files.into_iter()
+        .map(|file_path| {
+            let target = preview_target(&file_path, &sanitized_output_dir, &rename_map, anime_metadata.as_ref(), &config);
+            (file_path, target.to_string_lossy().to_string())
+        })
+        .collect())
+}
+
+// 按重命名映射和配置计算单个文件的目标路径，预览和两阶段处理的计划共用
+pub(crate) fn preview_target(
+    file_path: &str,
+    output_dir: &Path,
+    rename_map: &HashMap<String, String>,
+    anime_metadata: Option<&AniListResponse>,
+    config: &AppConfig
+) -> PathBuf {
+    let source = PathBuf::from(file_path);
+    
+    // 获取新文件名（如果存在）
+    let target_filename = match rename_map.get(file_path) {
+        Some(new_name) => {
+            let cleaned_name = new_name.replace('\\', "/");
+            if cleaned_name.contains('/') {
+                let parts: Vec<String> = cleaned_name.split('/')
+                    .map(|part| sanitize_filename(part))
+                    .collect();
+                parts.join("/")
+            } else {
+                sanitize_filename(&cleaned_name)
+            }
+        },
+        None => {
+            match source.file_name() {
+                Some(name) => sanitize_filename(&name.to_string_lossy()),
+                None => "invalid_filename".to_string(),
+            }
+        }
+    };
+    
+    // 与批量处理一致：特别篇的季度文件夹替换为第 0 季文件夹
+    let target_filename = match specials_folder_for(&source, config) {
+        Some(specials_folder) if target_filename.split('/').count() >= 3 => {
+            let mut parts: Vec<String> = target_filename.split('/').map(|part| part.to_string()).collect();
+            parts[1] = specials_folder;
+            parts.join("/")
+        }
+        _ => target_filename,
+    };
+    
+    let file_root = resolve_file_library_root(output_dir, &source, anime_metadata, config);
+    with_default_extension(&source, place_subtitle_target(file_root.join(&target_filename), config), config)
+}
+
+// 单个示例文件在给定配置下的整理结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FilePreview {
+    pub source: String,
+    pub target: Option<String>,
+    pub error: Option<String>,
+}
+
+// 用尚未保存的配置对示例文件执行命名和文件夹计算，返回各自的目标路径，供设置界面即时预览
+// 不读取也不写入已保存的配置，不访问文件系统；提供元数据时按元数据标题和播出季度分组
+#[command]
+pub async fn preview_config_effect(
+    sample_files: Vec<String>,
+    config: AppConfig,
+    metadata: Option<AniListResponse>
+) -> Result<Vec<FilePreview>, AppError> {
+    let output_dir = sanitize_path(&PathBuf::from(&config.output_directory));
+    
+    Ok(sample_files.into_iter()
+        .map(|file_path| {
+            let source = PathBuf::from(&file_path);
+            let parsed = source.file_name()
+                .ok_or_else(|| "无效的文件名".to_string())
+                .and_then(|name| parse_filename_with_config(&name.to_string_lossy(), &config));
+            
+            match parsed {
+                Ok(parsed) => {
+                    let target = build_matched_target(&output_dir, &source, metadata.as_ref(), &parsed, None, &config);
+                    FilePreview {
+                        source: file_path,
+                        target: Some(place_subtitle_target(target, &config).to_string_lossy().to_string()),
+                        error: None,
+                    }
+                }
+                Err(e) => FilePreview {
+                    source: file_path,
+                    target: None,
+                    error: Some(e),
+                },
+            }
+        })
+        .collect())
+}
+
+// 批量替换后多个源文件指向同一目标
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MapCollision {
+    pub target: String,
+    pub sources: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MapTransformResult {
+    pub rename_map: HashMap<String, String>,
+    // 替换后文件名变为空的源文件，这些条目保留原值
+    pub emptied: Vec<String>,
+    pub collisions: Vec<MapCollision>,
+}
+
+// 对重命名映射中的所有目标名执行查找替换，结果重新清理非法字符
+#[command]
+pub async fn apply_map_transform(
+    rename_map: HashMap<String, String>,
+    find: String,
+    replace: String,
+    is_regex: bool
+) -> Result<MapTransformResult, AppError> {
+    if find.is_empty() {
+        return Err("查找内容不能为空".into());
+    }
+    
+    let pattern = if is_regex {
+        Some(regex::Regex::new(&find).map_err(|e| format!("无效的正则表达式: {}", e))?)
+    } else {
+        None
+    };
+    
+    let mut transformed = HashMap::new();
+    let mut emptied = Vec::new();
     
-    if !is_video && !is_subtitle {
-        return Err("不支持的文件类型".to_string());
+    for (source, target) in rename_map {
+        let replaced = match &pattern {
+            Some(pattern) => pattern.replace_all(&target, replace.as_str()).to_string(),
+            None => target.replace(&find, &replace),
+        };
+        
+        // 替换掉文件名主体后只剩扩展名（例如 ".mkv"）同样视为空
+        let segments: Vec<&str> = replaced.split(['/', '\\']).collect();
+        let filename = segments.last().map(|name| name.trim()).unwrap_or_default();
+        if filename.trim_matches('.').is_empty() || filename.starts_with('.') {
+            emptied.push(source.clone());
+            transformed.insert(source, target);
+            continue;
+        }
+        
+        let cleaned = segments.iter()
+            .filter(|segment| !segment.trim().is_empty())
+            .map(|segment| sanitize_filename(&segment.split_whitespace().collect::<Vec<_>>().join(" ")))
+            .collect::<Vec<_>>()
+            .join("/");
+        transformed.insert(source, cleaned);
     }
     
-    Ok(FileInfo {
-        path: path_buf.to_string_lossy().to_string(),
-        name: file_name,
-        size: metadata.len(),
-        file_type: extension,
-        is_video,
-        is_subtitle,
+    let mut by_target: HashMap<&String, Vec<String>> = HashMap::new();
+    for (source, target) in &transformed {
+        by_target.entry(target).or_default().push(source.clone());
+    }
+    let mut collisions: Vec<MapCollision> = by_target.into_iter()
+        .filter(|(_, sources)| sources.len() > 1)
+        .map(|(target, mut sources)| {
+            sources.sort();
+            MapCollision { target: target.clone(), sources }
+        })
+        .collect();
+    collisions.sort_by(|a, b| a.target.cmp(&b.target));
+    emptied.sort();
+    
+    Ok(MapTransformResult {
+        rename_map: transformed,
+        emptied,
+        collisions,
     })
 }
 
-// 测试路径清理功能
-#[command]
-pub async fn test_path_sanitization(paths: Vec<String>) -> Result<HashMap<String, String>, String> {
-    let mut result = HashMap::new();
-    
-    for path in paths {
-        let original_path = PathBuf::from(&path);
-        let sanitized_path = sanitize_path(&original_path);
-        result.insert(path, sanitized_path.to_string_lossy().to_string());
+// 计算本次处理的媒体库根目录：输出目录加上可选的分组文件夹
+// 按播出季度分组时，所有目标都放在 "2023 Fall" 这样的文件夹下
+pub(crate) fn resolve_library_root(output_dir: &Path, anime_metadata: Option<&AniListResponse>, config: &AppConfig) -> PathBuf {
+    match anime_metadata.and_then(|anime| broadcast_season_folder(anime, config)) {
+        Some(folder) => output_dir.join(sanitize_filename(&folder)),
+        None => output_dir.to_path_buf(),
     }
-    
-    Ok(result)
 }
 
-// 预览文件处理结果
-#[command]
-pub async fn preview_file_processing(
-    files: Vec<String>, 
-    output_dir: String,
-    rename_map: HashMap<String, String>
-) -> Result<HashMap<String, String>, String> {
-    let mut result = HashMap::new();
-    let sanitized_output_dir = sanitize_path(&PathBuf::from(&output_dir));
-    
-    for file_path in files {
-        let source = PathBuf::from(&file_path);
-        
-        // 获取新文件名（如果存在）
-        let target_filename = match rename_map.get(&file_path) {
-            Some(new_name) => {
-                let cleaned_name = new_name.replace('\\', "/");
-                if cleaned_name.contains('/') {
-                    let parts: Vec<String> = cleaned_name.split('/')
-                        .map(|part| sanitize_filename(part))
-                        .collect();
-                    parts.join("/")
-                } else {
-                    sanitize_filename(&cleaned_name)
-                }
-            },
-            None => {
-                match source.file_name() {
-                    Some(name) => sanitize_filename(&name.to_string_lossy()),
-                    None => "invalid_filename".to_string(),
-                }
-            }
-        };
-        
-        let target = sanitized_output_dir.join(&target_filename);
-        result.insert(file_path, target.to_string_lossy().to_string());
+// 未识别出字幕组的文件在按字幕组分层时放入的文件夹
+const UNKNOWN_GROUP_FOLDER: &str = "Unknown";
+
+// 按配置将字幕组保留为最上层文件夹时，返回该文件对应的字幕组文件夹名；未开启时返回 None
+pub(crate) fn release_group_folder_for(source: &Path, config: &AppConfig) -> Option<String> {
+    if !config.group_top_level {
+        return None;
     }
     
-    Ok(result)
+    let group = parse_path_internal(source).ok()
+        .and_then(|parsed| parsed.group)
+        .map(|group| sanitize_filename(group.trim()))
+        .filter(|group| !group.is_empty());
+    Some(group.unwrap_or_else(|| UNKNOWN_GROUP_FOLDER.to_string()))
+}
+
+// 单个文件的媒体库根目录：字幕组文件夹（可选）在最外层，播出季度分组在其下
+pub(crate) fn resolve_file_library_root(output_dir: &Path, source: &Path, anime_metadata: Option<&AniListResponse>, config: &AppConfig) -> PathBuf {
+    let root = match release_group_folder_for(source, config) {
+        Some(group_folder) => output_dir.join(group_folder),
+        None => output_dir.to_path_buf(),
+    };
+    resolve_library_root(&root, anime_metadata, config)
 }
 
 // 添加新的批量处理函数，支持自定义命名和季度文件夹
 // 生成季度文件夹名称
-fn generate_season_folder_name(template: &str, season: u32) -> String {
-    let mut folder_name = template.to_string();
+pub(crate) fn generate_season_folder_name(template: &str, season: u32, separators: &[String]) -> String {
+    let mut folder_name = replace_padded_number(template, "season", Some(season));
     folder_name = folder_name.replace("{season}", &season.to_string());
-    folder_name = folder_name.replace("{season:02}", &format!("{:02}", season));
-    folder_name = folder_name.replace("{season:03}", &format!("{:03}", season));
     
     // 清理文件夹名称
-    sanitize_filename(&folder_name)
+    sanitize_filename(&normalize_rendered_name(&folder_name, separators))
+}
+
+// 按配置将特别篇（类型为 OVA、SP 等，见 season_for_anime_type，或季度为 0）放入第 0 季文件夹（例如 Jellyfin 要求的 "Season 00"），不管解析出的季度是多少
+// 未开启或不是特别篇时返回 None
+pub(crate) fn specials_folder_for(source: &Path, config: &AppConfig) -> Option<String> {
+    if !config.route_specials {
+        return None;
+    }
+    
+    let file_name = source.file_name()?.to_string_lossy().to_string();
+    let parsed = parse_filename_internal(&file_name).ok()?;
+    parsed.is_special
+        .then(|| generate_season_folder_name(&config.specials_folder_template, 0, &config.template_separators))
+}
+
+// 重命名目标必须是输出目录下的相对路径，拒绝绝对路径、盘符以及 ".." 这类可能逃出输出目录的值
+pub(crate) fn invalid_rename_targets(files: &[String], rename_map: &HashMap<String, String>) -> Vec<FileError> {
+    use std::path::Component;
+    
+    files.iter()
+        .filter_map(|file_path| {
+            let new_name = rename_map.get(file_path)?;
+            let normalized = new_name.replace('\\', "/");
+            // 非Windows平台上 Path 不会把 "C:" 解析为前缀，单独检查盘符
+            let has_drive = normalized.len() >= 2
+                && normalized.as_bytes()[0].is_ascii_alphabetic()
+                && normalized.as_bytes()[1] == b':';
+            
+            let error = Path::new(&normalized).components()
+                .find_map(|component| match component {
+                    Component::RootDir | Component::Prefix(_) => Some("重命名目标不能是绝对路径"),
+                    Component::ParentDir => Some("重命名目标不能包含 \"..\""),
+                    _ => None,
+                })
+                .or(has_drive.then_some("重命名目标不能包含盘符"))?;
+            
+            Some(FileError {
+                path: file_path.clone(),
+                error: format!("{}: {}", error, new_name),
+            })
+        })
+        .collect()
+}
+
+// 按配置检查重命名是否改变了扩展名：硬链接无法改变实际的容器格式，改扩展名只会让文件名与内容不符
+// 返回 (按 error 策略拒绝的文件, 按 warn 策略需要提示的文件)
+pub(crate) fn check_extension_changes(files: &[String], rename_map: &HashMap<String, String>, config: &AppConfig) -> (Vec<FileError>, Vec<FileError>) {
+    if config.extension_change_policy == "allow" {
+        return (Vec::new(), Vec::new());
+    }
+    
+    let extension_of = |path: &str| {
+        Path::new(path).extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default()
+    };
+    
+    let changes: Vec<FileError> = files.iter()
+        .filter_map(|file_path| {
+            let new_name = rename_map.get(file_path)?;
+            let (old_ext, new_ext) = (extension_of(file_path), extension_of(new_name));
+            // 无扩展名的源文件按配置补上默认扩展名不算改变
+            let default_added = old_ext.is_empty()
+                && new_ext == config.extensionless_default_extension.trim().trim_start_matches('.').to_lowercase();
+            (old_ext != new_ext && !default_added).then(|| FileError {
+                path: file_path.clone(),
+                error: format!("重命名改变了扩展名: .{} -> .{}", old_ext, new_ext),
+            })
+        })
+        .collect();
+    
+    if config.extension_change_policy == "error" {
+        (changes, Vec::new())
+    } else {
+        (Vec::new(), changes)
+    }
 }
 
 // 新的批量处理函数，支持季度文件夹
@@ -834,35 +2666,168 @@ fn generate_season_folder_name(template: &str, season: u32) -> String {
 pub async fn batch_process_with_season_folders(
     files: Vec<String>, 
     output_dir: String,
-    rename_map: HashMap<String, String>,
+    mut rename_map: HashMap<String, String>,
     create_season_folders: bool,
     season_folder_template: String,
+    scanned_sizes: Option<HashMap<String, u64>>,
+    anime_metadata: Option<AniListResponse>,
+    conflict_strategy: Option<String>,
+    app: AppHandle,
+    cancel: State<'_, BatchCancelFlag>,
     log_store: State<'_, LogStore>
-) -> Result<ProcessResult, String> {
-    use rayon::prelude::*;
+) -> Result<ProcessResult, AppError> {
     use std::sync::{Arc, Mutex};
     
+    let log = CommandLog::new(&log_store, SOURCE_BATCH_PROCESS_WITH_SEASON_FOLDERS);
+    let conflict_strategies = batch_conflict_strategies(conflict_strategy.as_deref())?;
+    let cancel = cancel.inner().clone();
+    // 新的批量处理开始时清除上一次的停止请求
+    cancel.store(false, Ordering::Relaxed);
+    
     info!("开始批量处理文件，季度文件夹: {}, 模板: {}", create_season_folders, season_folder_template);
-    add_log_entry(&log_store, LogLevel::INFO, format!("开始批量处理文件，季度文件夹: {}, 模板: {}", create_season_folders, season_folder_template), Some("季度文件夹处理".to_string()));
+    log.info(format!("开始批量处理文件，季度文件夹: {}, 模板: {}", create_season_folders, season_folder_template));
     
     // 清理输出目录路径
     let sanitized_output_dir = sanitize_path(&PathBuf::from(&output_dir));
     
     // 创建输出目录（如果不存在）
-    if !sanitized_output_dir.exists() {
-        if let Err(e) = fs::create_dir_all(&sanitized_output_dir) {
-            error!("创建输出目录失败: {}", e);
-            return Err(format!("创建输出目录失败: {}", e));
-        }
+    if let Err(e) = ensure_output_dir(Path::new(&output_dir)) {
+        error!("创建输出目录失败: {}", e);
+        return Err(e.into());
     }
     
     // 使用线程安全的容器收集结果
     let processed_files = Arc::new(Mutex::new(Vec::new()));
     let failed_files = Arc::new(Mutex::new(Vec::new()));
+    let skipped_files = Arc::new(Mutex::new(Vec::new()));
+    let manifest_entries = Arc::new(Mutex::new(Vec::new()));
+    let linked_sidecars = Arc::new(Mutex::new(Vec::new()));
+    let link_actions = Arc::new(Mutex::new(HashMap::new()));
+    let cancelled_files = Arc::new(Mutex::new(Vec::new()));
+    let journal = BatchJournal::new();
+    let config = read_app_config();
+    // 输入列表中的文件会单独处理，不作为附属文件重复链接
+    let input_files: HashSet<String> = files.iter().cloned().collect();
+    
+    // 拒绝绝对路径或包含 .. 的重命名目标，再按配置拒绝或提示改变了扩展名的重命名
+    let mut rejected = invalid_rename_targets(&files, &rename_map);
+    let valid_files: Vec<String> = files.iter()
+        .filter(|file_path| !rejected.iter().any(|issue| &issue.path == *file_path))
+        .cloned()
+        .collect();
+    let (extension_rejected, mut warnings) = check_extension_changes(&valid_files, &rename_map, &config);
+    rejected.extend(extension_rejected);
+    for issue in rejected.iter().chain(&warnings) {
+        warn!("{}: {}", issue.path, issue.error);
+        log.warn(format!("{}: {}", issue.path, issue.error));
+    }
+    let rejected_paths: HashSet<String> = rejected.iter().map(|issue| issue.path.clone()).collect();
+    let files_to_process: Vec<String> = files.iter()
+        .filter(|file_path| !rejected_paths.contains(*file_path))
+        .cloned()
+        .collect();
+    failed_files.lock().unwrap().extend(rejected);
+    
+    // 同一集有多个文件时按配置的策略保留其中之一，或为重复的目标名追加序号
+    let (duplicate_groups, duplicate_dropped) = apply_duplicate_resolution(&files_to_process, &mut rename_map, &config);
+    for group in &duplicate_groups {
+        info!("重复集数 {} S{:02}E{:02}: 保留 {}, 舍弃 {:?}", group.anime_title, group.season, group.episode, group.winner, group.dropped);
+        log.info(format!("重复集数 {} S{:02}E{:02}: 保留 {}, 舍弃 {} 个文件", group.anime_title, group.season, group.episode, group.winner, group.dropped.len()));
+    }
+    let files_to_process: Vec<String> = files_to_process.into_iter()
+        .filter(|file_path| !duplicate_dropped.iter().any(|dropped| &dropped.path == file_path))
+        .collect();
+    skipped_files.lock().unwrap().extend(duplicate_dropped);
     
-    // 并行处理文件
-    files.par_iter().for_each(|file_path| {
+    // 输出目录中其他位置已有内容相同的文件时，按配置跳过或提示
+    let (existing_skipped, existing_warnings) = check_existing_in_output(&files_to_process, &sanitized_output_dir, &config);
+    for issue in existing_skipped.iter().chain(&existing_warnings) {
+        warn!("{}: {}", issue.path, issue.error);
+        log.warn(format!("{}: {}", issue.path, issue.error));
+    }
+    let files_to_process: Vec<String> = files_to_process.into_iter()
+        .filter(|file_path| !existing_skipped.iter().any(|skipped| &skipped.path == file_path))
+        .collect();
+    skipped_files.lock().unwrap().extend(existing_skipped);
+    warnings.extend(existing_warnings);
+    
+    // 每个文件的媒体库根目录（按字幕组分层时各不相同）
+    let file_root = |source: &Path| resolve_file_library_root(&sanitized_output_dir, source, anime_metadata.as_ref(), &config);
+    
+    // 记录处理前尚不存在的动漫文件夹，处理完成后为新建的文件夹写入 tvshow.nfo
+    let new_anime_folders: Vec<PathBuf> = match &anime_metadata {
+        Some(_) if config.write_series_nfo => {
+            let mut folders: Vec<PathBuf> = rename_map.iter()
+                .filter_map(|(file_path, new_name)| {
+                    let cleaned_name = new_name.replace('\\', "/");
+                    let (anime_name, _) = cleaned_name.split_once('/')?;
+                    Some(file_root(Path::new(file_path)).join(sanitize_filename(anime_name)))
+                })
+                .filter(|folder| !folder.exists())
+                .collect();
+            folders.sort();
+            folders.dedup();
+            folders
+        }
+        _ => Vec::new(),
+    };
+    
+    // 记录处理前尚不存在的季度文件夹 (动漫文件夹, 季度文件夹, 季度)，处理完成后为新建的季度写入 season.nfo 和季度海报
+    let new_season_folders: Vec<(PathBuf, PathBuf, u32)> = match &anime_metadata {
+        Some(_) if config.write_season_assets && create_season_folders => {
+            let mut folders: Vec<(PathBuf, PathBuf, u32)> = rename_map.iter()
+                .filter_map(|(file_path, new_name)| {
+                    let cleaned_name = new_name.replace('\\', "/");
+                    let parts: Vec<String> = cleaned_name.split('/').map(sanitize_filename).collect();
+                    if parts.len() < 2 {
+                        return None;
+                    }
+                    let source = Path::new(file_path);
+                    let (season_folder, season) = match specials_folder_for(source, &config) {
+                        Some(specials_folder) => (specials_folder, 0),
+                        None if parts.len() >= 3 => {
+                            let season = extract_season_from_path(&parts[1]);
+                            (generate_season_folder_name(&season_folder_template, season, &config.template_separators), season)
+                        }
+                        None => return None,
+                    };
+                    let anime_folder = file_root(source).join(&parts[0]);
+                    Some((anime_folder.clone(), anime_folder.join(season_folder), season))
+                })
+                .filter(|(_, season_folder, _)| !season_folder.exists())
+                .collect();
+            folders.sort();
+            folders.dedup();
+            folders
+        }
+        _ => Vec::new(),
+    };
+    
+    // 并行处理文件（可按源设备分组）
+    // 定期通过 throughput 事件报告处理速率和预计剩余时间
+    let tracker = ThroughputTracker::new(app, SOURCE_BATCH_PROCESS_WITH_SEASON_FOLDERS, &files_to_process);
+    
+    process_grouped_by_device(&files_to_process, &config, |file_path| {
+        // 收到停止请求后剩余的文件不再处理，记为已取消
+        if cancel.load(Ordering::Relaxed) {
+            cancelled_files.lock().unwrap().push(file_path.clone());
+            tracker.cancelled(Path::new(file_path));
+            return;
+        }
+        
         let source = PathBuf::from(file_path);
+        let mut pending = tracker.begin(&source);
+        
+        // 跳过空文件和未下载完成的文件
+        if let Some(reason) = incomplete_source_reason(&source, &config, scanned_sizes.as_ref()) {
+            let mut skipped = skipped_files.lock().unwrap();
+            skipped.push(FileError {
+                path: file_path.clone(),
+                error: reason,
+            });
+            warn!("跳过未完成的文件: {}", file_path);
+            return;
+        }
         
         // 获取新文件名（如果存在）
         let target_filename = match rename_map.get(file_path) {
@@ -893,6 +2858,8 @@ pub async fn batch_process_with_season_folders(
             }
         };
         
+        let library_root = file_root(&source);
+        
         // 构建目标路径，处理季度文件夹
         let target = if target_filename.contains('/') {
             // 解析路径结构：动漫名/季度/文件名 或 动漫名/文件名
@@ -900,8 +2867,19 @@ pub async fn batch_process_with_season_folders(
             if path_parts.len() >= 2 {
                 let anime_name = path_parts[0];
                 
+                // 特别篇优先放入第 0 季文件夹，再按正常季度计算
+                let specials_folder = if create_season_folders {
+                    specials_folder_for(&source, &config)
+                } else {
+                    None
+                };
+                
                 // 检查是否需要创建季度文件夹
-                if create_season_folders && path_parts.len() >= 3 {
+                if let Some(specials_folder) = specials_folder {
+                    let file_name = path_parts.last().unwrap();
+                    let full_path = format!("{}/{}/{}", anime_name, specials_folder, file_name);
+                    library_root.join(full_path)
+                } else if create_season_folders && path_parts.len() >= 3 {
                     // 有季度信息且需要创建季度文件夹
                     let season_info = path_parts[1];
                     let file_name = path_parts.last().unwrap();
@@ -910,22 +2888,25 @@ pub async fn batch_process_with_season_folders(
                     let season_number = extract_season_from_path(season_info);
                     
                     // 勾选时，为所有季度（包括第1季）都创建季度子文件夹
-                    let season_folder = generate_season_folder_name(&season_folder_template, season_number);
+                    let season_folder = generate_season_folder_name(&season_folder_template, season_number, &config.template_separators);
                     let full_path = format!("{}/{}/{}", anime_name, season_folder, file_name);
-                    sanitized_output_dir.join(full_path)
+                    library_root.join(full_path)
                 } else {
                     // 不创建季度文件夹，直接使用动漫文件夹
                     let file_name = path_parts.last().unwrap();
                     let full_path = format!("{}/{}", anime_name, file_name);
-                    sanitized_output_dir.join(full_path)
+                    library_root.join(full_path)
                 }
             } else {
-                sanitized_output_dir.join(&target_filename)
+                library_root.join(&target_filename)
             }
         } else {
-            sanitized_output_dir.join(&target_filename)
+            library_root.join(&target_filename)
         };
         
+        let target = place_subtitle_target(target, &config);
+        let created_dir = first_missing_dir(&target);
+        
         // 确保目标目录存在
         if let Some(parent) = target.parent() {
             if !parent.exists() {
@@ -941,21 +2922,49 @@ pub async fn batch_process_with_season_folders(
             }
         }
         
-        // 检查目标路径长度
+        // 检查目标路径长度（超过平台真实上限）
         let target_path_str = target.to_string_lossy();
-        if target_path_str.len() > 260 {
-            warn!("目标路径过长: {} ({} 字符)", target_path_str, target_path_str.len());
+        if path_too_long(&target) {
+            warn!("目标路径过长: {} ({} 字符)", target_path_str, path_len(&target));
             let mut failed = failed_files.lock().unwrap();
             failed.push(FileError {
                 path: file_path.clone(),
-                error: format!("目标路径过长: {} 字符", target_path_str.len()),
+                error: format!("目标路径过长: {} 字符", path_len(&target)),
             });
             return;
         }
         
+        // 目标已存在时按冲突策略跳过、覆盖或重命名
+        let target = match resolve_target_conflict(&source, &target, &conflict_strategies, false) {
+            Ok(Some(target)) => target,
+            Ok(None) => {
+                skipped_files.lock().unwrap().push(FileError {
+                    path: file_path.clone(),
+                    error: format!("目标文件已存在，跳过: {}", target.display()),
+                });
+                info!("目标文件已存在，跳过: {}", target.display());
+                return;
+            },
+            Err(e) => {
+                failed_files.lock().unwrap().push(FileError {
+                    path: file_path.clone(),
+                    error: e.to_string(),
+                });
+                warn!("文件处理失败: {}, 错误: {}", file_path, e);
+                return;
+            }
+        };
+        
         // 尝试创建硬链接
-        match create_hard_link_internal(&source, &target) {
-            Ok(_) => {
+        match create_link_with_config(&source, &target, &config) {
+            Ok(outcome) => {
+                pending.succeeded(outcome.action);
+                journal.record(&source, &outcome, created_dir);
+                linked_sidecars.lock().unwrap().extend(link_sidecars_for_batch(&source, &outcome, &input_files, &config));
+                if config.write_manifest {
+                    manifest_entries.lock().unwrap().push(build_manifest_entry(&source, &outcome.target, &library_root, outcome.action.as_str()));
+                }
+                link_actions.lock().unwrap().insert(file_path.clone(), outcome.action.as_str().to_string());
                 let mut processed = processed_files.lock().unwrap();
                 processed.push(file_path.clone());
                 info!("文件处理成功: {} -> {}", file_path, target.display());
@@ -966,10 +2975,12 @@ pub async fn batch_process_with_season_folders(
                     path: file_path.clone(),
                     error: e.to_string(),
                 });
+                
                 warn!("文件处理失败: {}, 错误: {}", file_path, e);
             }
         }
     });
+    tracker.finish();
     
     // 获取处理结果
     let processed = Arc::try_unwrap(processed_files)
@@ -982,27 +2993,102 @@ pub async fn batch_process_with_season_folders(
         .into_inner()
         .unwrap();
     
+    let skipped = Arc::try_unwrap(skipped_files)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let mut manifest_entries = Arc::try_unwrap(manifest_entries)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let linked_sidecars = Arc::try_unwrap(linked_sidecars)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let link_actions = Arc::try_unwrap(link_actions)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let cancelled = Arc::try_unwrap(cancelled_files)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    if !cancelled.is_empty() {
+        warn!("批量处理已停止，{} 个文件未处理", cancelled.len());
+        log.warn(format!("批量处理已停止，{} 个文件未处理", cancelled.len()));
+    }
+    
+    if !linked_sidecars.is_empty() {
+        log.info(format!("已链接 {} 个附属文件", linked_sidecars.len()));
+    }
+    
+    if config.write_manifest {
+        manifest_entries.extend(linked_sidecars.iter().map(|link| {
+            build_manifest_entry(Path::new(&link.source), Path::new(&link.target), &file_root(Path::new(&link.source)), &link.action)
+        }));
+        write_batch_manifest(&log, &sanitized_output_dir, manifest_entries, &config);
+    }
+    
+    if let Some(anime) = &anime_metadata {
+        for folder in new_anime_folders.iter().filter(|folder| folder.is_dir()) {
+            match write_series_nfo(anime, folder, false, &config) {
+                Ok(true) => log.info(format!("已生成剧集NFO: {}", folder.display())),
+                Ok(false) => {},
+                Err(e) => log.warn(format!("生成剧集NFO失败: {} - {}", folder.display(), e)),
+            }
+        }
+    }
+    
+    if let Some(anime) = &anime_metadata {
+        for (anime_folder, season_folder, season) in new_season_folders.iter().filter(|(_, folder, _)| folder.is_dir()) {
+            match write_season_assets(anime, anime_folder, season_folder, *season, false, &config).await {
+                Ok(_) => log.info(format!("已生成季度资源: {}", season_folder.display())),
+                Err(e) => log.warn(format!("生成季度资源失败: {} - {}", season_folder.display(), e)),
+            }
+        }
+    }
+    
     let success_count = processed.len();
     let failed_count = failed.len();
+    let skipped_count = skipped.len();
+    let cancelled_count = cancelled.len();
     let total_count = files.len();
     
-    info!("批量处理完成: 成功 {}, 失败 {}, 总计 {}", success_count, failed_count, total_count);
-    add_log_entry(&log_store, LogLevel::INFO, format!("季度文件夹处理完成: 成功 {}, 失败 {}, 总计 {}", success_count, failed_count, total_count), Some("季度文件夹处理".to_string()));
+    info!("批量处理完成: 成功 {}, 失败 {}, 跳过 {}, 总计 {}", success_count, failed_count, skipped_count, total_count);
+    log.info(format!("季度文件夹处理完成: 成功 {}, 失败 {}, 跳过 {}, 总计 {}", success_count, failed_count, skipped_count, total_count));
     
     if failed_count > 0 {
-        add_log_entry(&log_store, LogLevel::WARN, format!("季度文件夹处理中有 {} 个文件失败", failed_count), Some("季度文件夹处理".to_string()));
+        log.warn(format!("季度文件夹处理中有 {} 个文件失败", failed_count));
     }
     
-    Ok(ProcessResult {
+    let result = ProcessResult {
         success: failed_count == 0,
-        message: format!("处理完成: 成功 {}/{}, 失败 {}", success_count, total_count, failed_count),
+        message: if cancelled_count > 0 {
+            format!("已停止: 成功 {}/{}, 失败 {}, 跳过 {}, 取消 {}", success_count, total_count, failed_count, skipped_count, cancelled_count)
+        } else {
+            format!("处理完成: 成功 {}/{}, 失败 {}, 跳过 {}", success_count, total_count, failed_count, skipped_count)
+        },
         processed_files: processed,
         failed_files: failed,
-    })
+        skipped_files: skipped,
+        linked_sidecars,
+        warnings,
+        link_actions,
+        cancelled_files: cancelled,
+        checksums: HashMap::new(),
+    };
+    journal.save(SOURCE_BATCH_PROCESS_WITH_SEASON_FOLDERS, &result.linked_sidecars, &log);
+    record_operation(SOURCE_BATCH_PROCESS_WITH_SEASON_FOLDERS, &output_dir, &result, &config);
+    
+    Ok(result)
 }
 
 // 从路径中提取季度信息
-fn extract_season_from_path(path_part: &str) -> u32 {
+pub(crate) fn extract_season_from_path(path_part: &str) -> u32 {
     // 尝试从路径部分提取季度数字
     let season_patterns = [
         r"Season\s*(\d+)",
@@ -1032,33 +3118,130 @@ fn extract_season_from_path(path_part: &str) -> u32 {
 pub async fn batch_process_with_rename(
     files: Vec<String>, 
     output_dir: String,
-    rename_map: HashMap<String, String>,
+    mut rename_map: HashMap<String, String>,
+    scanned_sizes: Option<HashMap<String, u64>>,
+    dry_run: Option<bool>,
+    conflict_strategy: Option<String>,
+    app: AppHandle,
     log_store: State<'_, LogStore>
-) -> Result<ProcessResult, String> {
-    use rayon::prelude::*;
+) -> Result<ProcessResult, AppError> {
     use std::sync::{Arc, Mutex};
     
+    let log = CommandLog::new(&log_store, SOURCE_BATCH_PROCESS_WITH_RENAME);
+    let dry_run = dry_run.unwrap_or(false);
+    let conflict_strategies = batch_conflict_strategies(conflict_strategy.as_deref())?;
+    
     info!("开始批量处理并重命名 {} 个文件到目录: {}", files.len(), output_dir);
-    add_log_entry(&log_store, LogLevel::INFO, format!("开始批量处理并重命名 {} 个文件到目录: {}", files.len(), output_dir), Some("批量重命名".to_string()));
+    log.info(format!("开始批量处理并重命名 {} 个文件到目录: {}", files.len(), output_dir));
     
     // 清理输出目录路径
     let sanitized_output_dir = sanitize_path(&PathBuf::from(&output_dir));
     
-    // 创建输出目录（如果不存在）
-    if !sanitized_output_dir.exists() {
-        if let Err(e) = fs::create_dir_all(&sanitized_output_dir) {
-            error!("创建输出目录失败: {}", e);
-            return Err(format!("创建输出目录失败: {}", e));
+    // 创建输出目录（如果不存在），预览时只检查不创建
+    if dry_run {
+        if sanitized_output_dir.exists() && !sanitized_output_dir.is_dir() {
+            return Err(format!("输出路径已存在且不是文件夹: {}", sanitized_output_dir.display()).into());
         }
+    } else if let Err(e) = ensure_output_dir(Path::new(&output_dir)) {
+        error!("创建输出目录失败: {}", e);
+        return Err(e.into());
     }
     
     // 使用线程安全的容器收集结果
     let processed_files = Arc::new(Mutex::new(Vec::new()));
     let failed_files = Arc::new(Mutex::new(Vec::new()));
+    let skipped_files = Arc::new(Mutex::new(Vec::new()));
+    let manifest_entries = Arc::new(Mutex::new(Vec::new()));
+    let linked_sidecars = Arc::new(Mutex::new(Vec::new()));
+    let link_actions = Arc::new(Mutex::new(HashMap::new()));
+    let config = read_app_config();
+    let linker = BatchLinker::new(&config, dry_run);
+    // 输入列表中的文件会单独处理，不作为附属文件重复链接
+    let input_files: HashSet<String> = files.iter().cloned().collect();
+    
+    // 拒绝绝对路径或包含 .. 的重命名目标，再按配置拒绝或提示改变了扩展名的重命名
+    let mut rejected = invalid_rename_targets(&files, &rename_map);
+    let valid_files: Vec<String> = files.iter()
+        .filter(|file_path| !rejected.iter().any(|issue| &issue.path == *file_path))
+        .cloned()
+        .collect();
+    let (extension_rejected, mut warnings) = check_extension_changes(&valid_files, &rename_map, &config);
+    rejected.extend(extension_rejected);
+    for issue in rejected.iter().chain(&warnings) {
+        warn!("{}: {}", issue.path, issue.error);
+        log.warn(format!("{}: {}", issue.path, issue.error));
+    }
+    let rejected_paths: HashSet<String> = rejected.iter().map(|issue| issue.path.clone()).collect();
+    let files_to_process: Vec<String> = files.iter()
+        .filter(|file_path| !rejected_paths.contains(*file_path))
+        .cloned()
+        .collect();
+    failed_files.lock().unwrap().extend(rejected);
+    
+    // 同一集有多个文件时按配置的策略保留其中之一，或为重复的目标名追加序号
+    let (duplicate_groups, duplicate_dropped) = apply_duplicate_resolution(&files_to_process, &mut rename_map, &config);
+    for group in &duplicate_groups {
+        info!("重复集数 {} S{:02}E{:02}: 保留 {}, 舍弃 {:?}", group.anime_title, group.season, group.episode, group.winner, group.dropped);
+        log.info(format!("重复集数 {} S{:02}E{:02}: 保留 {}, 舍弃 {} 个文件", group.anime_title, group.season, group.episode, group.winner, group.dropped.len()));
+    }
+    let files_to_process: Vec<String> = files_to_process.into_iter()
+        .filter(|file_path| !duplicate_dropped.iter().any(|dropped| &dropped.path == file_path))
+        .collect();
+    skipped_files.lock().unwrap().extend(duplicate_dropped);
+    
+    // 输出目录中其他位置已有内容相同的文件时，按配置跳过或提示
+    let (existing_skipped, existing_warnings) = check_existing_in_output(&files_to_process, &sanitized_output_dir, &config);
+    for issue in existing_skipped.iter().chain(&existing_warnings) {
+        warn!("{}: {}", issue.path, issue.error);
+        log.warn(format!("{}: {}", issue.path, issue.error));
+    }
+    let files_to_process: Vec<String> = files_to_process.into_iter()
+        .filter(|file_path| !existing_skipped.iter().any(|skipped| &skipped.path == file_path))
+        .collect();
+    skipped_files.lock().unwrap().extend(existing_skipped);
+    warnings.extend(existing_warnings);
+    
+    // 并行处理文件（可按源设备分组）
+    // 定期通过 throughput 事件报告处理速率和预计剩余时间
+    let tracker = ThroughputTracker::new(app, SOURCE_BATCH_PROCESS_WITH_RENAME, &files_to_process);
+    
+    // 目标已存在时按冲突策略跳过、覆盖或重命名，跳过或处理失败时记录结果并返回 None
+    let resolve_conflict = |file_path: &String, source: &Path, target: &Path| -> Option<PathBuf> {
+        match linker.resolve_conflict(source, target, &conflict_strategies) {
+            Ok(Some(target)) => Some(target),
+            Ok(None) => {
+                skipped_files.lock().unwrap().push(FileError {
+                    path: file_path.clone(),
+                    error: format!("目标文件已存在，跳过: {}", target.display()),
+                });
+                info!("目标文件已存在，跳过: {}", target.display());
+                None
+            },
+            Err(e) => {
+                failed_files.lock().unwrap().push(FileError {
+                    path: file_path.clone(),
+                    error: e.to_string(),
+                });
+                warn!("文件处理失败: {}, 错误: {}", file_path, e);
+                None
+            }
+        }
+    };
     
-    // 并行处理文件
-    files.par_iter().for_each(|file_path| {
+    process_grouped_by_device(&files_to_process, &config, |file_path| {
         let source = PathBuf::from(file_path);
+        let mut pending = tracker.begin(&source);
+        
+        // 跳过空文件和未下载完成的文件
+        if let Some(reason) = incomplete_source_reason(&source, &config, scanned_sizes.as_ref()) {
+            let mut skipped = skipped_files.lock().unwrap();
+            skipped.push(FileError {
+                path: file_path.clone(),
+                error: reason,
+            });
+            warn!("跳过未完成的文件: {}", file_path);
+            return;
+        }
         
         // 获取新文件名（如果存在）
         let target_filename = match rename_map.get(file_path) {
@@ -1101,11 +3284,12 @@ pub async fn batch_process_with_rename(
         } else {
             sanitized_output_dir.join(&target_filename)
         };
+        let target = place_subtitle_target(target, &config);
         
-        // 检查目标路径长度
+        // 检查目标路径长度，只有超过平台真实上限时才缩短文件名
         let target_path_str = target.to_string_lossy();
-        if target_path_str.len() > 260 {
-            warn!("目标路径过长: {} ({} 字符)", target_path_str, target_path_str.len());
+        if path_too_long(&target) {
+            warn!("目标路径过长: {} ({} 字符)", target_path_str, path_len(&target));
             
             // 尝试缩短文件名
             if let Some(file_stem) = target.file_stem() {
@@ -1120,11 +3304,22 @@ pub async fn batch_process_with_rename(
                     let short_filename = format!("{}.{}", short_stem, extension.to_string_lossy());
                     let short_target = sanitized_output_dir.join(short_filename);
                     
-                    if short_target.to_string_lossy().len() <= 260 {
-                        match create_hard_link_internal(&source, &short_target) {
-                            Ok(_) => {
+                    if !path_too_long(&short_target) {
+                        let Some(short_target) = resolve_conflict(file_path, &source, &short_target) else {
+                            return;
+                        };
+                        match linker.link(&source, &short_target) {
+                            Ok(outcome) => {
+                                pending.succeeded(outcome.action);
+                                if !dry_run {
+                                    linked_sidecars.lock().unwrap().extend(link_sidecars_for_batch(&source, &outcome, &input_files, &config));
+                                }
+                                if config.write_manifest && !dry_run {
+                                    manifest_entries.lock().unwrap().push(build_manifest_entry(&source, &outcome.target, &sanitized_output_dir, outcome.action.as_str()));
+                                }
+                                link_actions.lock().unwrap().insert(file_path.clone(), outcome.action.as_str().to_string());
                                 let mut processed = processed_files.lock().unwrap();
-                                processed.push(file_path.clone());
+                                processed.push(if dry_run { outcome.target.to_string_lossy().to_string() } else { file_path.clone() });
                                 return;
                             },
                             Err(e) => {
@@ -1145,18 +3340,30 @@ pub async fn batch_process_with_rename(
             let mut failed = failed_files.lock().unwrap();
             failed.push(FileError {
                 path: file_path.clone(),
-                error: format!("目标路径过长: {} 字符", target_path_str.len()),
+                error: format!("目标路径过长: {} 字符", path_len(&target)),
             });
             warn!("目标路径过长，无法处理: {}", file_path);
             return;
         }
         
+        let Some(target) = resolve_conflict(file_path, &source, &target) else {
+            return;
+        };
+        
         // 尝试创建硬链接
-        match create_hard_link_internal(&source, &target) {
-            Ok(_) => {
+        match linker.link(&source, &target) {
+            Ok(outcome) => {
+                pending.succeeded(outcome.action);
+                if !dry_run {
+                    linked_sidecars.lock().unwrap().extend(link_sidecars_for_batch(&source, &outcome, &input_files, &config));
+                }
                 // 成功处理
+                if config.write_manifest && !dry_run {
+                    manifest_entries.lock().unwrap().push(build_manifest_entry(&source, &outcome.target, &sanitized_output_dir, outcome.action.as_str()));
+                }
+                link_actions.lock().unwrap().insert(file_path.clone(), outcome.action.as_str().to_string());
                 let mut processed = processed_files.lock().unwrap();
-                processed.push(file_path.clone());
+                processed.push(if dry_run { outcome.target.to_string_lossy().to_string() } else { file_path.clone() });
             },
             Err(e) => {
                 // 处理失败
@@ -1170,6 +3377,7 @@ pub async fn batch_process_with_rename(
             }
         }
     });
+    tracker.finish();
     
     // 获取处理结果
     let processed = Arc::try_unwrap(processed_files)
@@ -1182,26 +3390,217 @@ pub async fn batch_process_with_rename(
         .into_inner()
         .unwrap();
     
+    let skipped = Arc::try_unwrap(skipped_files)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let mut manifest_entries = Arc::try_unwrap(manifest_entries)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let linked_sidecars = Arc::try_unwrap(linked_sidecars)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    let link_actions = Arc::try_unwrap(link_actions)
+        .unwrap()
+        .into_inner()
+        .unwrap();
+    
+    if !linked_sidecars.is_empty() {
+        log.info(format!("已链接 {} 个附属文件", linked_sidecars.len()));
+    }
+    
+    if config.write_manifest && !dry_run {
+        manifest_entries.extend(linked_sidecars.iter().map(|link| {
+            build_manifest_entry(Path::new(&link.source), Path::new(&link.target), &sanitized_output_dir, &link.action)
+        }));
+        write_batch_manifest(&log, &sanitized_output_dir, manifest_entries, &config);
+    }
+    
     let success_count = processed.len();
     let failed_count = failed.len();
+    let skipped_count = skipped.len();
     let total_count = files.len();
     
-    info!("批量处理完成: 成功 {}, 失败 {}, 总计 {}", success_count, failed_count, total_count);
-    add_log_entry(&log_store, LogLevel::INFO, format!("批量重命名完成: 成功 {}, 失败 {}, 总计 {}", success_count, failed_count, total_count), Some("批量重命名".to_string()));
+    info!("批量处理完成: 成功 {}, 失败 {}, 跳过 {}, 总计 {}", success_count, failed_count, skipped_count, total_count);
+    log.info(format!("批量重命名完成: 成功 {}, 失败 {}, 跳过 {}, 总计 {}", success_count, failed_count, skipped_count, total_count));
     
     // 如果有失败的文件，输出详细信息
     if failed_count > 0 {
         error!("处理失败的文件详情:");
-        add_log_entry(&log_store, LogLevel::WARN, format!("批量重命名中有 {} 个文件失败", failed_count), Some("批量重命名".to_string()));
+        log.warn(format!("批量重命名中有 {} 个文件失败", failed_count));
         for failed_file in &failed {
             error!("  - {}: {}", failed_file.path, failed_file.error);
         }
     }
     
-    Ok(ProcessResult {
+    let result = ProcessResult {
         success: failed_count == 0,
-        message: format!("处理完成: 成功 {}/{}, 失败 {}", success_count, total_count, failed_count),
+        message: if dry_run {
+            format!("预览: 将处理 {}/{}, 失败 {}, 跳过 {}", success_count, total_count, failed_count, skipped_count)
+        } else {
+            format!("处理完成: 成功 {}/{}, 失败 {}, 跳过 {}", success_count, total_count, failed_count, skipped_count)
+        },
         processed_files: processed,
         failed_files: failed,
-    })
+        skipped_files: skipped,
+        linked_sidecars,
+        warnings,
+        link_actions,
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    linker.save_journal(SOURCE_BATCH_PROCESS_WITH_RENAME, &result.linked_sidecars, &log);
+    if !dry_run {
+        record_operation(SOURCE_BATCH_PROCESS_WITH_RENAME, &output_dir, &result, &config);
+    }
+    
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // 每个测试使用独立的临时目录
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("anime-file-manager-{}-{}", name, uuid::Uuid::new_v4().simple()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+    
+    fn set_readonly(path: &Path, readonly: bool) {
+        let mut permissions = fs::metadata(path).unwrap().permissions();
+        permissions.set_readonly(readonly);
+        fs::set_permissions(path, permissions).unwrap();
+    }
+    
+    #[test]
+    fn hard_link_read_only_source() {
+        let dir = test_dir("readonly-source");
+        let source = dir.join("source.mkv");
+        fs::write(&source, b"episode").unwrap();
+        set_readonly(&source, true);
+        let permissions_before = fs::metadata(&source).unwrap().permissions();
+        
+        let outcome = create_hard_link_internal(&source, &dir.join("linked").join("target.mkv"));
+        
+        let permissions_after = fs::metadata(&source).unwrap().permissions();
+        set_readonly(&source, false);
+        fs::remove_dir_all(&dir).unwrap();
+        
+        let outcome = outcome.expect("只读的源文件应当可以链接");
+        assert_eq!(outcome.action, LinkAction::HardLink);
+        assert_eq!(permissions_after, permissions_before);
+        assert!(permissions_after.readonly());
+    }
+    
+    #[test]
+    fn failed_copy_leaves_no_target_or_created_dirs() {
+        let dir = test_dir("failed-copy");
+        // 目录作为源文件时复制必然失败，与目标目录的权限无关
+        let source = dir.join("source.mkv");
+        fs::create_dir(&source).unwrap();
+        let created = dir.join("Show");
+        let target = created.join("Season 1").join("target.mkv");
+        
+        let result = create_link_with_mode(&source, &target, LinkMode::Copy, true);
+        
+        let target_exists = target.exists();
+        let created_exists = created.exists();
+        fs::remove_dir_all(&dir).unwrap();
+        
+        assert!(result.is_err());
+        assert!(!target_exists, "复制失败后不应留下目标文件");
+        assert!(!created_exists, "复制失败后应删除本次创建的目录");
+    }
+    
+    #[cfg(unix)]
+    #[test]
+    fn copy_into_read_only_dir_leaves_no_partial_file() {
+        // root 不受目录权限限制，无法模拟写入失败
+        if unsafe { libc::geteuid() } == 0 {
+            return;
+        }
+        
+        let dir = test_dir("readonly-target");
+        let source = dir.join("source.mkv");
+        fs::write(&source, vec![7u8; 64 * 1024]).unwrap();
+        let target_dir = dir.join("output");
+        fs::create_dir(&target_dir).unwrap();
+        set_readonly(&target_dir, true);
+        let target = target_dir.join("target.mkv");
+        
+        let result = copy_or_clean_up(&source, &target);
+        
+        let target_exists = target.exists();
+        let leftovers = fs::read_dir(&target_dir).unwrap().count();
+        set_readonly(&target_dir, false);
+        fs::remove_dir_all(&dir).unwrap();
+        
+        assert!(result.is_err());
+        assert!(!target_exists);
+        assert_eq!(leftovers, 0, "目标目录中不应留下临时或不完整的文件");
+    }
+    
+    fn rename_target_rejected(new_name: &str) -> bool {
+        let files = vec!["episode.mkv".to_string()];
+        let rename_map = HashMap::from([(files[0].clone(), new_name.to_string())]);
+        !invalid_rename_targets(&files, &rename_map).is_empty()
+    }
+    
+    #[test]
+    fn invalid_rename_targets_rejects_paths_outside_output_dir() {
+        for new_name in ["/etc/passwd", "C:\\x", "\\\\server\\share\\x", "../x", "a/../../x"] {
+            assert!(rename_target_rejected(new_name), "应当拒绝: {}", new_name);
+        }
+        assert!(!rename_target_rejected("Show/Season 1/x.mkv"));
+    }
+    
+    #[cfg(windows)]
+    #[test]
+    fn sanitize_filename_prefixes_reserved_names() {
+        assert_eq!(sanitize_filename("con.mkv"), "_con.mkv");
+        assert_eq!(sanitize_filename("NUL.ass"), "_NUL.ass");
+        assert_eq!(sanitize_filename("com1"), "_com1");
+        assert_eq!(sanitize_filename("Con.Season 1"), "_Con.Season 1");
+    }
+    
+    #[test]
+    fn sanitize_filename_keeps_names_starting_with_reserved_words() {
+        assert_eq!(sanitize_filename("console.mkv"), "console.mkv");
+        assert_eq!(sanitize_filename("COM10.mkv"), "COM10.mkv");
+    }
+    
+    #[test]
+    fn free_target_linked_only_when_requested() {
+        let dir = test_dir("conflict-free");
+        let source = dir.join("source.mkv");
+        fs::write(&source, b"episode").unwrap();
+        let target = dir.join("target.mkv");
+        let strategies = vec!["skip".to_string()];
+        
+        let unlinked = resolve_file_conflict(&source, &target, &strategies, false, false);
+        let created_without_linking = target.exists();
+        let linked = resolve_file_conflict(&source, &target, &strategies, false, true);
+        let created_with_linking = target.exists();
+        fs::remove_dir_all(&dir).unwrap();
+        
+        assert_eq!(unlinked.unwrap().action, ConflictAction::NoConflict);
+        assert!(!created_without_linking, "handle_file_conflict 在没有冲突时不应创建文件");
+        assert_eq!(linked.unwrap().action, ConflictAction::Linked);
+        assert!(created_with_linking);
+    }
+    
+    #[cfg(not(windows))]
+    #[test]
+    fn reserved_names_unchanged_outside_windows() {
+        assert_eq!(sanitize_filename("aux"), "aux");
+        assert_eq!(sanitize_filename("con.mkv"), "con.mkv");
+        assert_eq!(sanitize_path(Path::new("/mnt/aux/anime")), PathBuf::from("/mnt/aux/anime"));
+    }
 }
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use tauri::command;
+use tracing::warn;
+use crate::commands::config::{AppConfig, get_config_dir};
+use crate::commands::file_operations::ProcessResult;
+use crate::commands::errors::AppError;
+
+// 操作历史文件名，存放在配置目录中，每行一条 JSON 记录
+const HISTORY_FILE_NAME: &str = "operation_history.jsonl";
+
+lazy_static! {
+    // 多个批量操作可能同时结束，追加写入时串行化避免记录交错
+    static ref HISTORY_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OperationRecord {
+    pub id: String,
+    // 操作类型，与日志来源标签一致，例如 "batch_process_with_rename"
+    pub operation: String,
+    pub timestamp: String,
+    pub target: String,
+    pub success: bool,
+    pub processed_count: usize,
+    pub failed_count: usize,
+    pub skipped_count: usize,
+    // 执行时使用的配置快照
+    pub config: AppConfig,
+}
+
+pub(crate) fn get_history_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(HISTORY_FILE_NAME))
+}
+
+// 追加一条操作记录，写入失败只记录警告，不影响操作本身的结果
+pub(crate) fn record_operation(operation: &str, target: &str, result: &ProcessResult, config: &AppConfig) {
+    if !config.record_operation_history {
+        return;
+    }
+    
+    let record = OperationRecord {
+        id: uuid::Uuid::new_v4().to_string(),
+        operation: operation.to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+        target: target.to_string(),
+        success: result.success,
+        processed_count: result.processed_files.len(),
+        failed_count: result.failed_files.len(),
+        skipped_count: result.skipped_files.len(),
+        config: config.clone(),
+    };
+    
+    if let Err(e) = append_record(&record) {
+        warn!("写入操作历史失败: {}", e);
+    }
+}
+
+fn append_record(record: &OperationRecord) -> Result<(), String> {
+    let history_path = get_history_path()?;
+    if let Some(parent) = history_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    
+    let line = serde_json::to_string(record)
+        .map_err(|e| format!("序列化操作记录失败: {}", e))?;
+    
+    let _guard = HISTORY_LOCK.lock().unwrap();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history_path)
+        .map_err(|e| format!("打开操作历史文件失败: {}", e))?;
+    writeln!(file, "{}", line)
+        .map_err(|e| format!("写入操作历史文件失败: {}", e))
+}
+
+// 读取操作历史，最新的记录在前；无法解析的行直接跳过
+#[command]
+pub async fn get_operation_history(limit: Option<usize>) -> Result<Vec<OperationRecord>, AppError> {
+    let history_path = get_history_path()?;
+    if !history_path.exists() {
+        return Ok(Vec::new());
+    }
+    
+    let content = {
+        let _guard = HISTORY_LOCK.lock().unwrap();
+        fs::read_to_string(&history_path)
+            .map_err(|e| format!("读取操作历史失败: {}", e))?
+    };
+    
+    let mut records: Vec<OperationRecord> = content.lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    records.reverse();
+    
+    if let Some(limit) = limit {
+        records.truncate(limit);
+    }
+    
+    Ok(records)
+}
+
+// 清空操作历史，返回删除的记录数
+#[command]
+pub async fn clear_operation_history() -> Result<usize, AppError> {
+    let history_path = get_history_path()?;
+    let _guard = HISTORY_LOCK.lock().unwrap();
+    if !history_path.exists() {
+        return Ok(0);
+    }
+    
+    let count = fs::read_to_string(&history_path)
+        .map(|content| content.lines().filter(|line| !line.trim().is_empty()).count())
+        .unwrap_or(0);
+    
+    fs::remove_file(&history_path)
+        .map_err(|e| format!("删除操作历史失败: {}", e))?;
+    
+    Ok(count)
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use regex::Regex;
+use tauri::{command, State};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::file_operations::{FileError, FileKind, ProcessResult, classify_file};
+use crate::commands::history::record_operation;
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_DELETE_JUNK_FILES};
+use crate::commands::errors::AppError;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JunkFile {
+    pub path: String,
+    pub size: u64,
+    // 命中的规则
+    pub pattern: String,
+}
+
+// 媒体文件和已知附属文件（视频、字幕、图片、NFO 以及 sidecar_extensions 中的扩展名）永远不算垃圾文件
+fn is_protected(path: &Path, config: &AppConfig) -> bool {
+    let extension = path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    classify_file(path, &extension, config).0 != FileKind::Other
+        || config.sidecar_extensions.iter().any(|sidecar| sidecar.trim_start_matches('.').eq_ignore_ascii_case(&extension))
+}
+
+// 将通配规则转换为不区分大小写的正则：* 匹配任意字符，? 匹配单个字符
+// 不含 / 的规则匹配文件名，含 / 的规则匹配相对于根目录的路径，例如 "Screenshots/*"
+fn compile_pattern(pattern: &str) -> Result<Regex, String> {
+    let mut regex = String::from("(?i)^");
+    for c in pattern.trim().replace('\\', "/").chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).map_err(|e| format!("无效的垃圾文件规则 {}: {}", pattern, e))
+}
+
+// 返回文件命中的第一条规则
+fn matching_pattern<'a>(path: &Path, root: &Path, patterns: &'a [(String, Regex)]) -> Option<&'a str> {
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    let relative = path.strip_prefix(root).ok()?
+        .to_string_lossy()
+        .replace('\\', "/");
+    
+    patterns.iter()
+        .find(|(pattern, regex)| {
+            if pattern.contains('/') || pattern.contains('\\') {
+                regex.is_match(&relative)
+            } else {
+                regex.is_match(&file_name)
+            }
+        })
+        .map(|(pattern, _)| pattern.as_str())
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<(String, Regex)>, String> {
+    patterns.iter()
+        .filter(|pattern| !pattern.trim().is_empty())
+        .map(|pattern| Ok((pattern.trim().to_string(), compile_pattern(pattern)?)))
+        .collect()
+}
+
+// 扫描源目录中命中垃圾文件规则的文件（未提供规则时使用配置中的 junk_patterns），只报告不删除
+#[command]
+pub async fn find_junk_files(root: String, patterns: Option<Vec<String>>) -> Result<Vec<JunkFile>, AppError> {
+    let config = read_app_config();
+    let root_path = PathBuf::from(&root);
+    if !root_path.is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", root)));
+    }
+    
+    let patterns = compile_patterns(patterns.as_deref().unwrap_or(&config.junk_patterns))?;
+    
+    let mut junk = Vec::new();
+    for entry in WalkDir::new(&root_path).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() || is_protected(entry.path(), &config) {
+            continue;
+        }
+        if let Some(pattern) = matching_pattern(entry.path(), &root_path, &patterns) {
+            junk.push(JunkFile {
+                path: entry.path().to_string_lossy().to_string(),
+                size: entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+                pattern: pattern.to_string(),
+            });
+        }
+    }
+    junk.sort_by(|a, b| a.path.cmp(&b.path));
+    
+    info!("在 {} 中找到 {} 个垃圾文件", root, junk.len());
+    Ok(junk)
+}
+
+// 删除 find_junk_files 报告的文件；dry_run 为 true 时只返回将要删除的文件
+// 删除前重新检查：文件必须位于 root 中，且不是媒体或已知附属文件
+#[command]
+pub async fn delete_junk_files(
+    root: String,
+    files: Vec<String>,
+    dry_run: Option<bool>,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_DELETE_JUNK_FILES);
+    let dry_run = dry_run.unwrap_or(false);
+    let config = read_app_config();
+    let root_path = PathBuf::from(&root);
+    
+    if !root_path.is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", root)));
+    }
+    
+    info!("开始清理垃圾文件: {} 个 (预览: {})", files.len(), dry_run);
+    log.info(format!("开始清理垃圾文件: {} 个 (预览: {})", files.len(), dry_run));
+    
+    let mut processed = Vec::new();
+    let mut failed = Vec::new();
+    let mut skipped = Vec::new();
+    
+    for file_path in files {
+        let path = Path::new(&file_path);
+        if !path.starts_with(&root_path) {
+            skipped.push(FileError {
+                path: file_path,
+                error: "文件不在清理目录中".to_string(),
+            });
+            continue;
+        }
+        if is_protected(path, &config) {
+            skipped.push(FileError {
+                path: file_path,
+                error: "媒体文件或附属文件不会被删除".to_string(),
+            });
+            continue;
+        }
+        if !path.is_file() {
+            skipped.push(FileError {
+                path: file_path,
+                error: "文件不存在".to_string(),
+            });
+            continue;
+        }
+        
+        if dry_run {
+            processed.push(file_path);
+            continue;
+        }
+        
+        match fs::remove_file(path) {
+            Ok(_) => processed.push(file_path),
+            Err(e) => {
+                warn!("删除垃圾文件失败: {}, 错误: {}", file_path, e);
+                failed.push(FileError {
+                    path: file_path,
+                    error: format!("删除失败: {}", e),
+                });
+            }
+        }
+    }
+    
+    let success_count = processed.len();
+    let failed_count = failed.len();
+    let skipped_count = skipped.len();
+    
+    info!("垃圾文件清理完成: 成功 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count);
+    log.info(format!("垃圾文件清理完成: 成功 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count));
+    
+    let result = ProcessResult {
+        success: failed_count == 0,
+        message: if dry_run {
+            format!("预览: 将删除 {} 个文件", success_count)
+        } else {
+            format!("清理完成: 删除 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count)
+        },
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: skipped,
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    if !dry_run {
+        record_operation(SOURCE_DELETE_JUNK_FILES, &root, &result, &config);
+    }
+    
+    Ok(result)
+}
@@ -0,0 +1,1305 @@
+use std::path::{Path, PathBuf};
+use std::fs;
+use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
+use tracing::{info, warn, error};
+use walkdir::WalkDir;
+use chrono::{DateTime, Utc};
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::text_output::write_text_file;
+use crate::commands::history::record_operation;
+use crate::commands::file_operations::{
+    FileError, FileInfo, FileKind, ProcessResult, FileSystemError, LinkAction, LinkOutcome,
+    create_hard_link_internal, is_same_file, sanitize_path, scan_directory_internal, extract_season_from_path,
+    generate_season_folder_name, create_link_with_config, unique_target_path, copy_or_clean_up, copy_forbidden_extension,
+};
+use crate::commands::logs::{
+    LogStore, CommandLog, SOURCE_RELOCATE_LIBRARY, SOURCE_GENERATE_STRM, SOURCE_RESTRUCTURE_SEASONS,
+    SOURCE_RELINK_BY_HASH, SOURCE_MIRROR_LINK_TREE, SOURCE_REORGANIZE_FILE,
+};
+use crate::commands::auto_match::{build_matched_target, episode_title_for};
+use crate::commands::metadata::{AniListResponse, parse_filename_internal};
+use crate::commands::manifest::{MANIFEST_FILE_NAME, read_manifest, write_manifest};
+use crate::commands::template::{padded_token_base, template_tokens};
+use crate::commands::errors::AppError;
+
+// 最近处理过的动漫
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecentAnime {
+    pub title: String,
+    pub folder: String,
+    pub last_processed: String,
+    pub episode_count: usize,
+}
+
+// 将整个媒体库迁移到新的根目录
+// 同一文件系统内创建硬链接，跨文件系统时复制；remove_old 为 true 时在校验通过后删除旧文件
+#[command]
+pub async fn relocate_library(
+    old_root: String,
+    new_root: String,
+    remove_old: Option<bool>,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_RELOCATE_LIBRARY);
+    let remove_old = remove_old.unwrap_or(false);
+    
+    let old_root_path = PathBuf::from(&old_root);
+    let new_root_path = sanitize_path(&PathBuf::from(&new_root));
+    
+    if !old_root_path.is_dir() {
+        return Err(AppError::NotFound("原媒体库目录不存在".to_string()));
+    }
+    if new_root_path.starts_with(&old_root_path) {
+        return Err("新目录不能位于原媒体库目录内".into());
+    }
+    
+    info!("开始迁移媒体库: {} -> {}", old_root, new_root_path.display());
+    log.info(format!("开始迁移媒体库: {} -> {}", old_root, new_root_path.display()));
+    
+    fs::create_dir_all(&new_root_path)
+        .map_err(|e| format!("创建新媒体库目录失败: {}", e))?;
+    
+    let mut processed = Vec::new();
+    let mut failed = Vec::new();
+    // 原目标路径 -> 新目标路径，用于更新处理清单
+    let mut moved_targets: HashMap<String, String> = HashMap::new();
+    let old_manifest_path = old_root_path.join(MANIFEST_FILE_NAME);
+    let config = read_app_config();
+    
+    for entry in WalkDir::new(&old_root_path)
+        .into_iter()
+        .filter_map(|e| {
+            if let Err(err) = &e {
+                warn!("扫描媒体库时跳过条目: {}", err);
+            }
+            e.ok()
+        })
+    {
+        if !entry.file_type().is_file() || entry.path() == old_manifest_path {
+            continue;
+        }
+        
+        let source = entry.path();
+        let relative = match source.strip_prefix(&old_root_path) {
+            Ok(relative) => relative,
+            Err(_) => continue,
+        };
+        let target = new_root_path.join(relative);
+        
+        match relocate_file(source, &target, &config) {
+            Ok(outcome) => {
+                info!("迁移成功 ({}): {} -> {}", outcome.action.as_str(), source.display(), outcome.target.display());
+                moved_targets.insert(
+                    source.to_string_lossy().to_string(),
+                    outcome.target.to_string_lossy().to_string(),
+                );
+                processed.push(source.to_string_lossy().to_string());
+            }
+            Err(e) => {
+                warn!("迁移失败: {}, 错误: {}", source.display(), e);
+                failed.push(FileError {
+                    path: source.to_string_lossy().to_string(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    
+    // 只改写实际迁移成功的记录，保证清单与磁盘状态一致
+    match read_manifest(&old_root_path) {
+        Ok(entries) if !entries.is_empty() => {
+            let updated: Vec<_> = entries.into_iter()
+                .map(|mut entry| {
+                    if let Some(new_target) = moved_targets.get(&entry.target) {
+                        entry.target = new_target.clone();
+                    }
+                    entry
+                })
+                .collect();
+            if let Err(e) = write_manifest(&new_root_path, updated, false, &config) {
+                log.warn(format!("更新处理清单失败: {}", e));
+            }
+        }
+        Ok(_) => {}
+        Err(e) => log.warn(format!("读取原处理清单失败，未迁移清单: {}", e)),
+    }
+    
+    if remove_old {
+        for old_path in &processed {
+            if let Err(e) = fs::remove_file(old_path) {
+                error!("删除旧文件失败: {}, 错误: {}", old_path, e);
+                log.warn(format!("删除旧文件失败: {} - {}", old_path, e));
+            }
+        }
+        
+        // 全部迁移成功时旧清单也随之删除
+        if failed.is_empty() && old_manifest_path.exists() {
+            if let Err(e) = fs::remove_file(&old_manifest_path) {
+                log.warn(format!("删除旧处理清单失败: {}", e));
+            }
+        }
+        
+        remove_empty_dirs(&old_root_path);
+    }
+    
+    let success_count = processed.len();
+    let failed_count = failed.len();
+    let total_count = success_count + failed_count;
+    
+    info!("媒体库迁移完成: 成功 {}, 失败 {}, 总计 {}", success_count, failed_count, total_count);
+    log.info(format!("媒体库迁移完成: 成功 {}, 失败 {}, 总计 {}", success_count, failed_count, total_count));
+    for failed_file in &failed {
+        log.error(format!("文件迁移失败: {} - {}", failed_file.path, failed_file.error));
+    }
+    
+    let result = ProcessResult {
+        success: failed_count == 0,
+        message: format!("迁移完成: 成功 {}/{}, 失败 {}", success_count, total_count, failed_count),
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: Vec::new(),
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    record_operation(SOURCE_RELOCATE_LIBRARY, &new_root, &result, &config);
+    
+    Ok(result)
+}
+
+// 将整个目录树镜像到新位置：重建所有子目录并硬链接所有文件（不限于媒体文件），用于快照或备份
+// 目标已是同一文件的硬链接时跳过，已存在的其他文件不覆盖
+#[command]
+pub async fn mirror_link_tree(
+    source_root: String,
+    dest_root: String,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_MIRROR_LINK_TREE);
+    
+    let source_root_path = PathBuf::from(&source_root);
+    let dest_root_path = sanitize_path(&PathBuf::from(&dest_root));
+    
+    if !source_root_path.is_dir() {
+        return Err(AppError::NotFound("源目录不存在".to_string()));
+    }
+    if dest_root_path.starts_with(&source_root_path) {
+        return Err("目标目录不能位于源目录内".into());
+    }
+    
+    info!("开始镜像链接目录树: {} -> {}", source_root, dest_root_path.display());
+    log.info(format!("开始镜像链接目录树: {} -> {}", source_root, dest_root_path.display()));
+    
+    fs::create_dir_all(&dest_root_path)
+        .map_err(|e| format!("创建目标目录失败: {}", e))?;
+    
+    let mut processed = Vec::new();
+    let mut failed = Vec::new();
+    let mut skipped = Vec::new();
+    
+    for entry in WalkDir::new(&source_root_path)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| {
+            if let Err(err) = &e {
+                warn!("扫描源目录时跳过条目: {}", err);
+            }
+            e.ok()
+        })
+    {
+        let source = entry.path();
+        let relative = match source.strip_prefix(&source_root_path) {
+            Ok(relative) => relative,
+            Err(_) => continue,
+        };
+        let target = dest_root_path.join(relative);
+        
+        // 空目录也要重建，保证结构完全一致
+        if entry.file_type().is_dir() {
+            if let Err(e) = fs::create_dir_all(&target) {
+                failed.push(FileError {
+                    path: source.to_string_lossy().to_string(),
+                    error: format!("创建目录失败: {}", e),
+                });
+            }
+            continue;
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        
+        if target.exists() {
+            skipped.push(FileError {
+                path: source.to_string_lossy().to_string(),
+                error: if is_same_file(source, &target) {
+                    "已链接".to_string()
+                } else {
+                    format!("目标已存在其他文件: {}", target.display())
+                },
+            });
+            continue;
+        }
+        
+        match create_hard_link_internal(source, &target) {
+            Ok(_) => processed.push(source.to_string_lossy().to_string()),
+            Err(e) => {
+                warn!("镜像链接失败: {}, 错误: {}", source.display(), e);
+                failed.push(FileError {
+                    path: source.to_string_lossy().to_string(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    
+    let success_count = processed.len();
+    let failed_count = failed.len();
+    let skipped_count = skipped.len();
+    
+    info!("镜像链接完成: 成功 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count);
+    log.info(format!("镜像链接完成: 成功 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count));
+    for failed_file in &failed {
+        log.error(format!("镜像链接失败: {} - {}", failed_file.path, failed_file.error));
+    }
+    
+    let result = ProcessResult {
+        success: failed_count == 0,
+        message: format!("镜像完成: 成功 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count),
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: skipped,
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    record_operation(SOURCE_MIRROR_LINK_TREE, &dest_root, &result, &read_app_config());
+    
+    Ok(result)
+}
+
+// 单个文件重新整理的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProcessedFile {
+    pub previous_path: String,
+    pub final_path: String,
+    // 处理清单中记录的源文件，存在时从源文件重新链接
+    pub source: Option<String>,
+    // "moved" | "relinked" | "unchanged" | "skipped"
+    pub action: String,
+}
+
+// 按给定的元数据和集数重新计算单个媒体库文件的目标路径并移动到位
+// 文件所在媒体库的处理清单中记录了源文件且源文件仍存在时，从源文件重新链接；否则直接移动当前文件
+// 目标已存在时按 conflict_strategy 处理：skip 跳过、overwrite 覆盖、rename（默认）自动改名
+#[command]
+pub async fn reorganize_file(
+    current_path: String,
+    anime_info: AniListResponse,
+    episode: u32,
+    season: Option<u32>,
+    conflict_strategy: Option<String>,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessedFile, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_REORGANIZE_FILE);
+    let config = read_app_config();
+    let conflict_strategy = conflict_strategy.unwrap_or_else(|| "rename".to_string());
+    
+    let current = PathBuf::from(&current_path);
+    if !current.is_file() {
+        return Err(AppError::NotFound("文件不存在".to_string()));
+    }
+    
+    let file_name = current.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or("无效的文件路径")?;
+    let mut parsed = parse_filename_internal(&file_name)?;
+    parsed.episode_number = Some(episode);
+    if let Some(season) = season {
+        parsed.season = Some(season);
+        parsed.is_special = parsed.is_special || season == 0;
+    }
+    
+    // 文件位于带处理清单的媒体库中时以该库为根目录，否则使用配置的输出目录
+    let manifest_root = find_manifest_root(&current);
+    let library_root = manifest_root.clone()
+        .unwrap_or_else(|| PathBuf::from(&config.output_directory));
+    
+    let episode_title = if config.fetch_episode_titles {
+        episode_title_for(anime_info.id, Some(episode), &log).await
+    } else {
+        None
+    };
+    let mut target = sanitize_path(&build_matched_target(&library_root, &current, Some(&anime_info), &parsed, episode_title, &config));
+    
+    if target == current {
+        log.info(format!("文件已在正确位置: {}", current.display()));
+        return Ok(ProcessedFile {
+            previous_path: current_path.clone(),
+            final_path: current_path,
+            source: None,
+            action: "unchanged".to_string(),
+        });
+    }
+    
+    if target.exists() {
+        match conflict_strategy.as_str() {
+            "skip" => {
+                log.info(format!("目标已存在，跳过: {}", target.display()));
+                return Ok(ProcessedFile {
+                    previous_path: current_path.clone(),
+                    final_path: current_path,
+                    source: None,
+                    action: "skipped".to_string(),
+                });
+            }
+            "overwrite" => {
+                fs::remove_file(&target)
+                    .map_err(|e| format!("删除已存在的文件失败: {}", e))?;
+            }
+            "rename" => {
+                target = unique_target_path(&target).ok_or("无法生成唯一的文件名")?;
+            }
+            _ => return Err(format!("不支持的冲突处理策略: {}", conflict_strategy).into()),
+        }
+    }
+    
+    let manifest_entries = match &manifest_root {
+        Some(root) => read_manifest(root).unwrap_or_else(|e| {
+            log.warn(format!("读取处理清单失败，按普通文件移动: {}", e));
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let source = manifest_entries.iter()
+        .find(|entry| Path::new(&entry.target) == current)
+        .map(|entry| PathBuf::from(&entry.source))
+        .filter(|source| source.is_file());
+    
+    info!("重新整理文件: {} -> {}", current.display(), target.display());
+    let action = match &source {
+        Some(source) => {
+            create_link_with_config(source, &target, &config).map_err(|e| e.to_string())?;
+            if let Err(e) = fs::remove_file(&current) {
+                log.warn(format!("已重新链接，但删除旧文件失败: {} - {}", current.display(), e));
+            }
+            "relinked"
+        }
+        None => {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("创建目标目录失败: {}", e))?;
+            }
+            fs::rename(&current, &target)
+                .map_err(|e| format!("移动文件失败: {}", e))?;
+            "moved"
+        }
+    };
+    log.info(format!("重新整理完成: {} -> {}", current.display(), target.display()));
+    
+    // 旧位置的季度/动漫文件夹变空时一并清理，不越过媒体库根目录
+    for dir in current.ancestors().skip(1) {
+        if dir == library_root || !dir.starts_with(&library_root) || fs::remove_dir(dir).is_err() {
+            break;
+        }
+    }
+    
+    if let Some(root) = &manifest_root {
+        if manifest_entries.iter().any(|entry| Path::new(&entry.target) == current) {
+            let updated: Vec<_> = manifest_entries.into_iter()
+                .map(|mut entry| {
+                    if Path::new(&entry.target) == current {
+                        entry.target = target.to_string_lossy().to_string();
+                        entry.episode = Some(episode);
+                    }
+                    entry
+                })
+                .collect();
+            if let Err(e) = write_manifest(root, updated, false, &config) {
+                log.warn(format!("更新处理清单失败: {}", e));
+            }
+        }
+    }
+    
+    let final_path = target.to_string_lossy().to_string();
+    let result = ProcessResult {
+        success: true,
+        message: format!("重新整理完成: {}", final_path),
+        processed_files: vec![current_path.clone()],
+        failed_files: Vec::new(),
+        skipped_files: Vec::new(),
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    record_operation(SOURCE_REORGANIZE_FILE, &final_path, &result, &config);
+    
+    Ok(ProcessedFile {
+        previous_path: current_path,
+        final_path,
+        source: source.map(|source| source.to_string_lossy().to_string()),
+        action: action.to_string(),
+    })
+}
+
+// 向上查找包含处理清单的媒体库根目录
+fn find_manifest_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|ancestor| ancestor.join(MANIFEST_FILE_NAME).is_file())
+        .map(Path::to_path_buf)
+}
+
+// 迁移单个文件：优先硬链接，跨文件系统时复制，并校验目标大小
+fn relocate_file(source: &Path, target: &Path, config: &AppConfig) -> Result<LinkOutcome, FileSystemError> {
+    let outcome = match create_hard_link_internal(source, target) {
+        Err(FileSystemError::DifferentFilesystems) => {
+            if let Some(extension) = copy_forbidden_extension(source, config) {
+                return Err(FileSystemError::CopyForbidden(extension));
+            }
+            let final_target = sanitize_path(target);
+            copy_or_clean_up(source, &final_target)?;
+            LinkOutcome { target: final_target, action: LinkAction::Copy }
+        }
+        other => other?,
+    };
+    
+    let source_size = fs::metadata(source)?.len();
+    let target_size = fs::metadata(&outcome.target)?.len();
+    if source_size != target_size {
+        let _ = fs::remove_file(&outcome.target);
+        return Err(FileSystemError::Other(format!("校验失败: 源文件 {} 字节, 目标文件 {} 字节", source_size, target_size)));
+    }
+    
+    Ok(outcome)
+}
+
+// 自底向上删除空目录（保留根目录本身）
+fn remove_empty_dirs(root: &Path) {
+    for entry in WalkDir::new(root)
+        .min_depth(1)
+        .contents_first(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_dir() {
+            // 非空目录删除会失败，直接忽略
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+}
+
+// 为媒体库中的每个视频生成 .strm 文件，并为每部动漫生成按集数排序的 .m3u 播放列表
+#[command]
+pub async fn generate_strm(root: String, log_store: State<'_, LogStore>) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_GENERATE_STRM);
+    let root_path = PathBuf::from(&root);
+    
+    if !root_path.is_dir() {
+        return Err(AppError::NotFound("媒体库目录不存在".to_string()));
+    }
+    
+    log.info(format!("开始生成 strm 和播放列表: {}", root));
+    
+    let config = read_app_config();
+    let (files, _) = scan_directory_internal(&root, &config);
+    
+    let mut processed = Vec::new();
+    let mut failed = Vec::new();
+    // 动漫文件夹 -> 该动漫的视频文件
+    let mut series: HashMap<PathBuf, Vec<FileInfo>> = HashMap::new();
+    
+    for file in files.into_iter().filter(|file| file.is_video) {
+        let video_path = PathBuf::from(&file.path);
+        let absolute_path = video_path.canonicalize().unwrap_or_else(|_| video_path.clone());
+        let strm_path = video_path.with_extension("strm");
+        
+        match write_text_file(&strm_path, &format!("{}\n", absolute_path.to_string_lossy()), &config) {
+            Ok(_) => processed.push(strm_path.to_string_lossy().to_string()),
+            Err(e) => failed.push(FileError {
+                path: strm_path.to_string_lossy().to_string(),
+                error: format!("写入 strm 失败: {}", e),
+            }),
+        }
+        
+        // 位于媒体库根目录下一级文件夹中的视频归为同一部动漫，直接放在根目录的视频归入根目录
+        let series_dir = video_path.strip_prefix(&root_path)
+            .ok()
+            .and_then(|relative| {
+                let mut components = relative.components();
+                let first = components.next()?;
+                components.next()?;
+                Some(root_path.join(first))
+            })
+            .unwrap_or_else(|| root_path.clone());
+        
+        series.entry(series_dir).or_default().push(file);
+    }
+    
+    for (series_dir, mut videos) in series {
+        videos.sort_by_cached_key(|video| episode_sort_key(&root_path, video));
+        
+        let series_name = series_dir.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "playlist".to_string());
+        let playlist_path = series_dir.join(format!("{}.m3u", series_name));
+        
+        let mut playlist = String::from("#EXTM3U\n");
+        for video in &videos {
+            let video_path = PathBuf::from(&video.path);
+            let absolute_path = video_path.canonicalize().unwrap_or(video_path);
+            let title = absolute_path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            playlist.push_str(&format!("#EXTINF:-1,{}\n{}\n", title, absolute_path.to_string_lossy()));
+        }
+        
+        match write_text_file(&playlist_path, &playlist, &config) {
+            Ok(_) => processed.push(playlist_path.to_string_lossy().to_string()),
+            Err(e) => failed.push(FileError {
+                path: playlist_path.to_string_lossy().to_string(),
+                error: format!("写入播放列表失败: {}", e),
+            }),
+        }
+    }
+    
+    let success_count = processed.len();
+    let failed_count = failed.len();
+    
+    info!("strm 生成完成: 成功 {}, 失败 {}", success_count, failed_count);
+    log.info(format!("strm 生成完成: 成功 {}, 失败 {}", success_count, failed_count));
+    
+    Ok(ProcessResult {
+        success: failed_count == 0,
+        message: format!("生成完成: 成功 {}, 失败 {}", success_count, failed_count),
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: Vec::new(),
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    })
+}
+
+// 播放列表排序键：季度、集数、文件名
+// 季度优先取文件名解析结果，其次取所在的季度文件夹
+fn episode_sort_key(root: &Path, video: &FileInfo) -> (u32, u32, String) {
+    let parsed = parse_filename_internal(&video.name).ok();
+    
+    let season = parsed.as_ref()
+        .and_then(|p| p.season)
+        .or_else(|| {
+            Path::new(&video.path).parent()
+                .filter(|parent| *parent != root)
+                .and_then(|parent| parent.file_name())
+                .map(|name| extract_season_from_path(&name.to_string_lossy()))
+        })
+        .unwrap_or(1);
+    let episode = parsed.and_then(|p| p.episode_number).unwrap_or(u32::MAX);
+    
+    (season, episode, video.name.clone())
+}
+
+// 按新的季度文件夹模板重命名媒体库中已有的季度文件夹
+// old_template 为空时使用当前配置中的模板识别旧文件夹；新名称已存在时将内容合并过去
+#[command]
+pub async fn restructure_seasons(
+    root: String,
+    new_template: String,
+    old_template: Option<String>,
+    dry_run: Option<bool>,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_RESTRUCTURE_SEASONS);
+    let dry_run = dry_run.unwrap_or(false);
+    let config = read_app_config();
+    let root_path = PathBuf::from(&root);
+    
+    if !root_path.is_dir() {
+        return Err(AppError::NotFound("媒体库目录不存在".to_string()));
+    }
+    
+    let old_template = old_template.unwrap_or_else(|| config.season_folder_template.clone());
+    let season_patterns = season_folder_patterns(&old_template);
+    
+    info!("开始调整季度文件夹: {} -> {} (预览: {})", old_template, new_template, dry_run);
+    log.info(format!("开始调整季度文件夹: {} -> {} (预览: {})", old_template, new_template, dry_run));
+    
+    let mut processed = Vec::new();
+    let mut failed = Vec::new();
+    let mut skipped = Vec::new();
+    // 实际移动过的路径（旧 -> 新），用于更新处理清单
+    let mut moves: Vec<(PathBuf, PathBuf)> = Vec::new();
+    
+    // 媒体库结构为 根目录/动漫文件夹/季度文件夹
+    for season_dir in WalkDir::new(&root_path)
+        .min_depth(2)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .map(|e| e.into_path())
+    {
+        let folder_name = match season_dir.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => continue,
+        };
+        let season = match match_season_folder(&folder_name, &season_patterns) {
+            Some(season) => season,
+            None => continue,
+        };
+        
+        let new_name = generate_season_folder_name(&new_template, season, &config.template_separators);
+        if new_name == folder_name {
+            continue;
+        }
+        
+        let new_dir = season_dir.with_file_name(&new_name);
+        let plan = format!("{} -> {}", season_dir.display(), new_dir.display());
+        
+        if dry_run {
+            if new_dir.exists() {
+                processed.push(format!("{} (合并)", plan));
+            } else {
+                processed.push(plan);
+            }
+            continue;
+        }
+        
+        if !new_dir.exists() {
+            match fs::rename(&season_dir, &new_dir) {
+                Ok(_) => {
+                    info!("季度文件夹已重命名: {}", plan);
+                    moves.push((season_dir, new_dir));
+                    processed.push(plan);
+                }
+                Err(e) => failed.push(FileError {
+                    path: season_dir.to_string_lossy().to_string(),
+                    error: format!("重命名失败: {}", e),
+                }),
+            }
+            continue;
+        }
+        
+        // 新名称已存在：逐个移动内容，同名条目保留在原文件夹并记为跳过
+        let (merged, conflicts) = merge_into_folder(&season_dir, &new_dir, &mut failed);
+        moves.extend(merged);
+        skipped.extend(conflicts);
+        
+        if fs::remove_dir(&season_dir).is_ok() {
+            processed.push(format!("{} (合并)", plan));
+        } else {
+            log.warn(format!("合并后原文件夹仍有内容，未删除: {}", season_dir.display()));
+        }
+    }
+    
+    if !dry_run && !moves.is_empty() {
+        update_manifest_paths(&log, &root_path, &moves, &config);
+    }
+    
+    let success_count = processed.len();
+    let failed_count = failed.len();
+    let skipped_count = skipped.len();
+    
+    info!("季度文件夹调整完成: 成功 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count);
+    log.info(format!("季度文件夹调整完成: 成功 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count));
+    for failed_file in &failed {
+        log.error(format!("季度文件夹调整失败: {} - {}", failed_file.path, failed_file.error));
+    }
+    
+    let result = ProcessResult {
+        success: failed_count == 0,
+        message: if dry_run {
+            format!("预览: 将调整 {} 个季度文件夹", success_count)
+        } else {
+            format!("调整完成: 成功 {}, 失败 {}, 跳过 {}", success_count, failed_count, skipped_count)
+        },
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: skipped,
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    if !dry_run {
+        record_operation(SOURCE_RESTRUCTURE_SEASONS, &root, &result, &config);
+    }
+    
+    Ok(result)
+}
+
+// 没有视频文件的季度文件夹类型
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanSeasonKind {
+    // 没有任何文件
+    Empty,
+    // 只剩字幕
+    SubtitlesOnly,
+    // 只剩图片、NFO等其他文件
+    NoVideo,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrphanSeason {
+    pub path: String,
+    pub kind: OrphanSeasonKind,
+    pub file_count: usize,
+    pub removed: bool,
+}
+
+// 查找媒体库中没有视频文件的季度文件夹，empty_only 为 true 时只列出完全为空的文件夹
+// prune_empty 为 true 时删除完全为空的季度文件夹，其余类型只报告不处理
+#[command]
+pub async fn find_orphan_seasons(
+    root: String,
+    empty_only: Option<bool>,
+    prune_empty: Option<bool>
+) -> Result<Vec<OrphanSeason>, AppError> {
+    let root_path = PathBuf::from(&root);
+    if !root_path.is_dir() {
+        return Err(AppError::NotFound("媒体库目录不存在".to_string()));
+    }
+    
+    let empty_only = empty_only.unwrap_or(false);
+    let prune_empty = prune_empty.unwrap_or(false);
+    let config = read_app_config();
+    let patterns = season_folder_patterns(&config.season_folder_template);
+    
+    let season_folders: Vec<PathBuf> = WalkDir::new(&root_path)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_dir())
+        .filter(|entry| match_season_folder(&entry.file_name().to_string_lossy(), &patterns).is_some())
+        .map(|entry| entry.into_path())
+        .collect();
+    
+    let mut orphans = Vec::new();
+    for folder in season_folders {
+        let kinds: Vec<FileKind> = WalkDir::new(&folder)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| {
+                entry.path().extension()
+                    .map(|ext| FileKind::from_extension(&ext.to_string_lossy(), &config))
+                    .unwrap_or_default()
+            })
+            .collect();
+        
+        let kind = if kinds.is_empty() {
+            OrphanSeasonKind::Empty
+        } else if kinds.contains(&FileKind::Video) {
+            continue;
+        } else if kinds.iter().all(|kind| *kind == FileKind::Subtitle) {
+            OrphanSeasonKind::SubtitlesOnly
+        } else {
+            OrphanSeasonKind::NoVideo
+        };
+        
+        if empty_only && kind != OrphanSeasonKind::Empty {
+            continue;
+        }
+        
+        let removed = if prune_empty && kind == OrphanSeasonKind::Empty {
+            remove_empty_dirs(&folder);
+            match fs::remove_dir(&folder) {
+                Ok(_) => true,
+                Err(e) => {
+                    warn!("删除空季度文件夹失败: {}, 错误: {}", folder.display(), e);
+                    false
+                }
+            }
+        } else {
+            false
+        };
+        
+        orphans.push(OrphanSeason {
+            path: folder.to_string_lossy().to_string(),
+            kind,
+            file_count: kinds.len(),
+            removed,
+        });
+    }
+    
+    orphans.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(orphans)
+}
+
+// 根据旧模板生成识别季度文件夹的正则，并附带常见的季度文件夹命名
+fn season_folder_patterns(template: &str) -> Vec<regex::Regex> {
+    let mut patterns = Vec::new();
+    
+    let mut template_pattern = regex::escape(template);
+    for token in template_tokens(template).iter().filter(|token| padded_token_base(token).as_deref() == Some("{season}")) {
+        template_pattern = template_pattern.replace(&regex::escape(token), r"(\d+)");
+    }
+    template_pattern = template_pattern.replace(&regex::escape("{season}"), r"(\d+)");
+    if template_pattern.contains(r"(\d+)") {
+        if let Ok(re) = regex::Regex::new(&format!("^(?i){}$", template_pattern)) {
+            patterns.push(re);
+        }
+    }
+    
+    for pattern in [r"^(?i)season\s*(\d+)$", r"^(?i)s(\d+)$", r"^第(\d+)季$"] {
+        if let Ok(re) = regex::Regex::new(pattern) {
+            patterns.push(re);
+        }
+    }
+    
+    patterns
+}
+
+// 判断文件夹名是否为季度文件夹，返回季度编号
+fn match_season_folder(folder_name: &str, patterns: &[regex::Regex]) -> Option<u32> {
+    patterns.iter()
+        .filter_map(|re| re.captures(folder_name))
+        .find_map(|caps| caps.get(1)?.as_str().parse().ok())
+}
+
+// 将 from 中的条目移动到 into，返回成功移动的路径和因同名而跳过的条目
+fn merge_into_folder(from: &Path, into: &Path, failed: &mut Vec<FileError>) -> (Vec<(PathBuf, PathBuf)>, Vec<FileError>) {
+    let mut merged = Vec::new();
+    let mut conflicts = Vec::new();
+    
+    let entries = match fs::read_dir(from) {
+        Ok(entries) => entries,
+        Err(e) => {
+            failed.push(FileError {
+                path: from.to_string_lossy().to_string(),
+                error: format!("读取文件夹失败: {}", e),
+            });
+            return (merged, conflicts);
+        }
+    };
+    
+    for entry in entries.filter_map(|e| e.ok()) {
+        let source = entry.path();
+        let target = into.join(entry.file_name());
+        
+        if target.exists() {
+            conflicts.push(FileError {
+                path: source.to_string_lossy().to_string(),
+                error: format!("目标已存在: {}", target.display()),
+            });
+            continue;
+        }
+        
+        match fs::rename(&source, &target) {
+            Ok(_) => merged.push((source, target)),
+            Err(e) => failed.push(FileError {
+                path: source.to_string_lossy().to_string(),
+                error: format!("移动失败: {}", e),
+            }),
+        }
+    }
+    
+    (merged, conflicts)
+}
+
+// 按移动记录改写处理清单中的目标路径
+fn update_manifest_paths(log: &CommandLog, root: &Path, moves: &[(PathBuf, PathBuf)], config: &AppConfig) {
+    let entries = match read_manifest(root) {
+        Ok(entries) if !entries.is_empty() => entries,
+        Ok(_) => return,
+        Err(e) => {
+            log.warn(format!("读取处理清单失败，未更新清单: {}", e));
+            return;
+        }
+    };
+    
+    let updated: Vec<_> = entries.into_iter()
+        .map(|mut entry| {
+            let target = PathBuf::from(&entry.target);
+            let moved = moves.iter().find_map(|(old, new)| {
+                target.strip_prefix(old).ok().map(|rest| new.join(rest))
+            });
+            if let Some(new_target) = moved {
+                entry.target = new_target.to_string_lossy().to_string();
+            }
+            entry
+        })
+        .collect();
+    
+    if let Err(e) = write_manifest(root, updated, false, config) {
+        log.warn(format!("更新处理清单失败: {}", e));
+    }
+}
+
+// 计算媒体库状态的指纹：对所有文件的 (相对路径, 大小, 修改时间) 求 SHA-256
+// 相对路径统一使用 "/" 分隔、修改时间精确到秒，同一状态在不同运行和平台上得到相同结果，供同步或备份工具判断是否有变化
+#[command]
+pub async fn library_signature(root: String) -> Result<String, AppError> {
+    use rayon::prelude::*;
+    use sha2::{Digest, Sha256};
+    
+    let root_path = PathBuf::from(&root);
+    if !root_path.is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", root)));
+    }
+    
+    let paths: Vec<PathBuf> = WalkDir::new(&root_path)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .collect();
+    
+    // 并行读取元数据并分别求每个文件的摘要，再按相对路径排序后合并
+    let mut entries: Vec<(String, [u8; 32])> = paths.par_iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(&root_path).ok()?
+                .components()
+                .map(|component| component.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            let metadata = fs::metadata(path).ok()?;
+            let modified = metadata.modified().ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0);
+            
+            let digest = Sha256::digest(format!("{}\0{}\0{}", relative, metadata.len(), modified).as_bytes());
+            Some((relative, digest.into()))
+        })
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    
+    let mut hasher = Sha256::new();
+    for (_, digest) in &entries {
+        hasher.update(digest);
+    }
+    
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+// 根据处理清单列出最近处理过的动漫，按最后处理时间倒序
+#[command]
+pub async fn recent_anime(limit: usize) -> Result<Vec<RecentAnime>, AppError> {
+    let config = read_app_config();
+    let output_dir = PathBuf::from(&config.output_directory);
+    let entries = read_manifest(&output_dir)?;
+    let season_patterns = season_folder_patterns(&config.season_folder_template);
+    
+    // 动漫文件夹 -> (标题, 最后处理时间, 集数)
+    let mut series: HashMap<PathBuf, (String, DateTime<Utc>, usize)> = HashMap::new();
+    
+    for entry in entries {
+        let Ok(timestamp) = DateTime::parse_from_rfc3339(&entry.timestamp) else {
+            continue;
+        };
+        let timestamp = timestamp.with_timezone(&Utc);
+        let Some(mut folder) = Path::new(&entry.target).parent().map(Path::to_path_buf) else {
+            continue;
+        };
+        
+        // 位于季度文件夹中时取上一级的动漫文件夹
+        let in_season_folder = folder.file_name()
+            .and_then(|name| match_season_folder(&name.to_string_lossy(), &season_patterns))
+            .is_some();
+        if in_season_folder {
+            if let Some(parent) = folder.parent() {
+                folder = parent.to_path_buf();
+            }
+        }
+        
+        let title = entry.anime_title.clone()
+            .or_else(|| folder.file_name().map(|name| name.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        
+        let record = series.entry(folder).or_insert((title, timestamp, 0));
+        record.2 += 1;
+        if timestamp > record.1 {
+            record.1 = timestamp;
+        }
+    }
+    
+    let mut recent: Vec<RecentAnime> = series.into_iter()
+        .filter(|(folder, _)| folder.is_dir())
+        .map(|(folder, (title, last_processed, episode_count))| RecentAnime {
+            title,
+            folder: folder.to_string_lossy().to_string(),
+            last_processed: last_processed.to_rfc3339(),
+            episode_count,
+        })
+        .collect();
+    
+    recent.sort_by(|a, b| b.last_processed.cmp(&a.last_processed));
+    recent.truncate(limit);
+    
+    Ok(recent)
+}
+
+// 在系统文件管理器中显示文件或文件夹，文件会被选中
+// Windows 使用 explorer /select，macOS 使用 open -R，Linux 通过 FileManager1 接口选中文件，不支持时用 xdg-open 打开所在文件夹
+#[command]
+pub async fn reveal_in_explorer(path: String) -> Result<(), AppError> {
+    let path = PathBuf::from(&path);
+    if !path.exists() {
+        return Err(AppError::NotFound(format!("路径不存在: {}", path.display())));
+    }
+    
+    match tauri_plugin_opener::reveal_item_in_dir(&path) {
+        Ok(()) => Ok(()),
+        Err(e) if cfg!(target_os = "linux") => {
+            warn!("文件管理器无法选中文件，改为打开所在文件夹: {}, 错误: {}", path.display(), e);
+            let folder = if path.is_dir() { path.as_path() } else { path.parent().unwrap_or(&path) };
+            open_folder_with_xdg(folder)
+        }
+        Err(e) => Err(AppError::Other(format!("打开文件管理器失败: {} - {}", path.display(), e))),
+    }
+}
+
+fn open_folder_with_xdg(folder: &Path) -> Result<(), AppError> {
+    let status = std::process::Command::new("xdg-open")
+        .arg(folder)
+        .status()
+        .map_err(|e| AppError::Other(format!("打开文件管理器失败: 无法运行 xdg-open - {}", e)))?;
+    if !status.success() {
+        return Err(AppError::Other(format!("打开文件管理器失败: xdg-open 退出码 {}", status.code().unwrap_or(-1))));
+    }
+    Ok(())
+}
+
+// 源文件被下载工具移动后，为失效的目标重新建立链接
+// 目标仍存在（复制的文件）时按大小和内容哈希在候选目录中查找同一文件；
+// 目标是失效的符号链接时按原文件名查找，候选唯一时才重建
+#[command]
+pub async fn relink_by_hash(
+    broken_targets: Vec<String>,
+    candidate_dirs: Vec<String>,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_RELINK_BY_HASH);
+    let config = read_app_config();
+    
+    info!("开始按内容重新链接 {} 个目标，候选目录 {} 个", broken_targets.len(), candidate_dirs.len());
+    log.info(format!("开始按内容重新链接 {} 个目标，候选目录 {} 个", broken_targets.len(), candidate_dirs.len()));
+    
+    // 候选文件按大小和文件名建立索引
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    let mut by_name: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for dir in &candidate_dirs {
+        for entry in WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|e| {
+                if let Err(err) = &e {
+                    warn!("扫描候选目录时跳过条目: {}", err);
+                }
+                e.ok()
+            })
+            .filter(|entry| entry.file_type().is_file())
+        {
+            if let Ok(metadata) = entry.metadata() {
+                by_size.entry(metadata.len()).or_default().push(entry.path().to_path_buf());
+            }
+            by_name.entry(entry.file_name().to_string_lossy().to_string())
+                .or_default()
+                .push(entry.path().to_path_buf());
+        }
+    }
+    
+    let mut hash_cache: HashMap<PathBuf, u64> = HashMap::new();
+    let mut processed = Vec::new();
+    let mut failed = Vec::new();
+    // 目标路径 -> 新的源文件路径，用于更新处理清单
+    let mut relinked: Vec<(PathBuf, PathBuf)> = Vec::new();
+    
+    for target_str in &broken_targets {
+        let target = PathBuf::from(target_str);
+        let found = match find_relink_source(&target, &by_size, &by_name, &mut hash_cache) {
+            Ok(found) => found,
+            Err(e) => {
+                warn!("未找到匹配的源文件: {}, 原因: {}", target_str, e);
+                failed.push(FileError {
+                    path: target_str.clone(),
+                    error: e,
+                });
+                continue;
+            }
+        };
+        
+        match replace_with_link(&found, &target) {
+            Ok(_) => {
+                info!("重新链接成功: {} -> {}", found.display(), target.display());
+                processed.push(target_str.clone());
+                relinked.push((target, found));
+            }
+            Err(e) => {
+                warn!("重新链接失败: {}, 错误: {}", target_str, e);
+                failed.push(FileError {
+                    path: target_str.clone(),
+                    error: format!("找到源文件 {} 但重新链接失败: {}", found.display(), e),
+                });
+            }
+        }
+    }
+    
+    update_manifest_sources(&log, &relinked, &config);
+    
+    let success_count = processed.len();
+    let failed_count = failed.len();
+    
+    info!("重新链接完成: 恢复 {}, 未恢复 {}", success_count, failed_count);
+    log.info(format!("重新链接完成: 恢复 {}, 未恢复 {}", success_count, failed_count));
+    for failed_file in &failed {
+        log.warn(format!("未恢复: {} - {}", failed_file.path, failed_file.error));
+    }
+    
+    let result = ProcessResult {
+        success: failed_count == 0,
+        message: format!("重新链接完成: 恢复 {}/{}, 未恢复 {}", success_count, broken_targets.len(), failed_count),
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: Vec::new(),
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    record_operation(SOURCE_RELINK_BY_HASH, &candidate_dirs.join(";"), &result, &config);
+    
+    Ok(result)
+}
+
+// 为失效的目标查找新的源文件
+fn find_relink_source(
+    target: &Path,
+    by_size: &HashMap<u64, Vec<PathBuf>>,
+    by_name: &HashMap<String, Vec<PathBuf>>,
+    hash_cache: &mut HashMap<PathBuf, u64>
+) -> Result<PathBuf, String> {
+    let link_metadata = fs::symlink_metadata(target)
+        .map_err(|_| "目标不存在".to_string())?;
+    
+    // 失效的符号链接无法读取内容，只能按原文件名匹配
+    if link_metadata.file_type().is_symlink() && fs::metadata(target).is_err() {
+        let original = fs::read_link(target)
+            .map_err(|e| format!("读取符号链接失败: {}", e))?;
+        let name = original.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or("符号链接指向的路径无效")?;
+        return match by_name.get(&name).map(|paths| paths.as_slice()) {
+            Some([single]) => Ok(single.clone()),
+            Some(paths) if paths.len() > 1 => Err(format!("候选目录中有 {} 个同名文件，无法确定", paths.len())),
+            _ => Err(format!("候选目录中没有名为 {} 的文件", name)),
+        };
+    }
+    
+    let size = fs::metadata(target)
+        .map_err(|e| format!("读取目标信息失败: {}", e))?
+        .len();
+    let candidates = by_size.get(&size)
+        .filter(|paths| !paths.is_empty())
+        .ok_or("候选目录中没有大小相同的文件")?;
+    
+    let target_hash = file_content_hash(target)
+        .map_err(|e| format!("计算目标哈希失败: {}", e))?;
+    
+    for candidate in candidates {
+        if candidate == target {
+            continue;
+        }
+        let hash = match hash_cache.get(candidate) {
+            Some(hash) => *hash,
+            None => match file_content_hash(candidate) {
+                Ok(hash) => {
+                    hash_cache.insert(candidate.clone(), hash);
+                    hash
+                }
+                Err(e) => {
+                    warn!("计算候选文件哈希失败: {}, 错误: {}", candidate.display(), e);
+                    continue;
+                }
+            },
+        };
+        if hash == target_hash {
+            return Ok(candidate.clone());
+        }
+    }
+    
+    Err("候选目录中没有内容相同的文件".to_string())
+}
+
+// 计算文件内容哈希，仅用于同一次运行内的比较
+pub(crate) fn file_content_hash(path: &Path) -> std::io::Result<u64> {
+    use std::hash::Hasher;
+    use std::io::Read;
+    
+    let mut file = fs::File::open(path)?;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.write(&buffer[..read]);
+    }
+    Ok(hasher.finish())
+}
+
+// 先在临时路径创建硬链接，再原子地替换目标，失败时原目标保持不变
+fn replace_with_link(source: &Path, target: &Path) -> std::io::Result<()> {
+    let file_name = target.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = target.with_file_name(format!(".{}.relink-{}", file_name, uuid::Uuid::new_v4().simple()));
+    
+    fs::hard_link(source, &temp)?;
+    if let Err(e) = fs::rename(&temp, target) {
+        let _ = fs::remove_file(&temp);
+        return Err(e);
+    }
+    Ok(())
+}
+
+// 在目标所在媒体库的处理清单中更新源文件路径
+fn update_manifest_sources(log: &CommandLog, relinked: &[(PathBuf, PathBuf)], config: &AppConfig) {
+    // 媒体库根目录 -> 该库中需要更新的 (目标, 新源文件)
+    let mut by_root: HashMap<PathBuf, Vec<&(PathBuf, PathBuf)>> = HashMap::new();
+    for pair in relinked {
+        if let Some(root) = find_manifest_root(&pair.0) {
+            by_root.entry(root).or_default().push(pair);
+        }
+    }
+    
+    for (root, pairs) in by_root {
+        let entries = match read_manifest(&root) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log.warn(format!("读取处理清单失败，未更新清单: {}", e));
+                continue;
+            }
+        };
+        
+        let updated: Vec<_> = entries.into_iter()
+            .map(|mut entry| {
+                let target = PathBuf::from(&entry.target);
+                if let Some((_, source)) = pairs.iter().find(|(relinked_target, _)| *relinked_target == target) {
+                    entry.source = source.to_string_lossy().to_string();
+                    entry.action = LinkAction::HardLink.as_str().to_string();
+                }
+                entry
+            })
+            .collect();
+        
+        if let Err(e) = write_manifest(&root, updated, false, config) {
+            log.warn(format!("更新处理清单失败: {}", e));
+        }
+    }
+}
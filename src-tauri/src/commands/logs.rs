@@ -59,6 +59,68 @@ pub fn add_log_entry(store: &LogStore, level: LogLevel, message: String, source:
     logs.push_back(entry);
 }
 
+// 各命令的日志来源标签，与命令名保持一致，便于按来源筛选
+pub const SOURCE_SCAN_DIRECTORY: &str = "scan_directory";
+pub const SOURCE_DIFF_SCANS: &str = "diff_scans";
+pub const SOURCE_CREATE_HARD_LINK: &str = "create_hard_link";
+pub const SOURCE_BATCH_PROCESS_FILES: &str = "batch_process_files";
+pub const SOURCE_BATCH_PROCESS_WITH_RENAME: &str = "batch_process_with_rename";
+pub const SOURCE_BATCH_PROCESS_WITH_SEASON_FOLDERS: &str = "batch_process_with_season_folders";
+pub const SOURCE_RELOAD_CHAR_MAP: &str = "reload_char_map";
+pub const SOURCE_SEARCH_ALL: &str = "search_all";
+pub const SOURCE_RELOCATE_LIBRARY: &str = "relocate_library";
+pub const SOURCE_GENERATE_STRM: &str = "generate_strm";
+pub const SOURCE_BATCH_AUTO_MATCH: &str = "batch_auto_match";
+pub const SOURCE_LINK_WITH_SIDECARS: &str = "link_with_sidecars";
+pub const SOURCE_RESTRUCTURE_SEASONS: &str = "restructure_seasons";
+pub const SOURCE_BATCH_COPY_FILES: &str = "batch_copy_files";
+pub const SOURCE_RELINK_BY_HASH: &str = "relink_by_hash";
+pub const SOURCE_MIRROR_LINK_TREE: &str = "mirror_link_tree";
+pub const SOURCE_FIND_UNIDENTIFIED: &str = "find_unidentified";
+pub const SOURCE_SCAN_DIRECTORY_WITH_PROGRESS: &str = "scan_directory_with_progress";
+pub const SOURCE_REORGANIZE_FILE: &str = "reorganize_file";
+pub const SOURCE_EXPORT_METADATA: &str = "export_metadata";
+pub const SOURCE_BATCH_LINK_TRANSACTIONAL: &str = "batch_link_transactional";
+pub const SOURCE_EXECUTE_PLAN: &str = "execute_plan";
+pub const SOURCE_DELETE_JUNK_FILES: &str = "delete_junk_files";
+pub const SOURCE_AUTO_ORGANIZE_FOLDER: &str = "auto_organize_folder";
+pub const SOURCE_UNDO_LAST_BATCH: &str = "undo_last_batch";
+pub const SOURCE_SCAN_DIRECTORY_GROUPED: &str = "scan_directory_grouped";
+pub const SOURCE_WATCH: &str = "watch";
+
+// 绑定命令来源的日志记录器，保证同一命令写入的日志来源标签一致
+#[derive(Clone, Copy)]
+pub struct CommandLog<'a> {
+    store: &'a LogStore,
+    source: &'static str,
+}
+
+impl<'a> CommandLog<'a> {
+    pub fn new(store: &'a LogStore, source: &'static str) -> Self {
+        Self { store, source }
+    }
+    
+    pub fn source(&self) -> &'static str {
+        self.source
+    }
+    
+    pub fn info(&self, message: impl Into<String>) {
+        add_log_entry(self.store, LogLevel::INFO, message.into(), Some(self.source.to_string()));
+    }
+    
+    pub fn warn(&self, message: impl Into<String>) {
+        add_log_entry(self.store, LogLevel::WARN, message.into(), Some(self.source.to_string()));
+    }
+    
+    pub fn error(&self, message: impl Into<String>) {
+        add_log_entry(self.store, LogLevel::ERROR, message.into(), Some(self.source.to_string()));
+    }
+    
+    pub fn debug(&self, message: impl Into<String>) {
+        add_log_entry(self.store, LogLevel::DEBUG, message.into(), Some(self.source.to_string()));
+    }
+}
+
 #[tauri::command]
 pub fn get_logs(log_store: State<LogStore>) -> Result<Vec<LogEntry>, String> {
     let logs = log_store.lock().map_err(|e| format!("获取日志失败: {}", e))?;
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use chrono::Utc;
+use crate::commands::config::AppConfig;
+use crate::commands::metadata::parse_filename_internal;
+use crate::commands::text_output::{strip_bom, write_text_file};
+
+// 处理清单文件名，写入输出目录根部
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub source: String,
+    pub target: String,
+    pub action: String,
+    pub anime_title: Option<String>,
+    pub episode: Option<u32>,
+    pub timestamp: String,
+}
+
+// 根据源文件和最终目标路径构建一条清单记录
+pub fn build_manifest_entry(source: &Path, target: &Path, output_dir: &Path, action: &str) -> ManifestEntry {
+    let file_name = target.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let parsed = parse_filename_internal(&file_name).ok();
+    
+    // 目标位于动漫文件夹中时，优先使用文件夹名作为动漫标题
+    let folder_title = target.strip_prefix(output_dir)
+        .ok()
+        .and_then(|relative| {
+            let mut components = relative.components();
+            let first = components.next()?;
+            components.next()?;
+            Some(first.as_os_str().to_string_lossy().to_string())
+        });
+    
+    let anime_title = folder_title.or_else(|| {
+        parsed.as_ref()
+            .map(|p| p.anime_title.clone())
+            .filter(|title| !title.is_empty())
+    });
+    
+    ManifestEntry {
+        source: source.to_string_lossy().to_string(),
+        target: target.to_string_lossy().to_string(),
+        action: action.to_string(),
+        anime_title,
+        episode: parsed.and_then(|p| p.episode_number),
+        timestamp: Utc::now().to_rfc3339(),
+    }
+}
+
+// 读取输出目录中的处理清单，不存在时返回空列表
+pub fn read_manifest(output_dir: &Path) -> Result<Vec<ManifestEntry>, String> {
+    let manifest_path = output_dir.join(MANIFEST_FILE_NAME);
+    if !manifest_path.exists() {
+        return Ok(Vec::new());
+    }
+    
+    let content = std::fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("读取处理清单失败: {}", e))?;
+    let content = strip_bom(&content);
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    
+    serde_json::from_str(content)
+        .map_err(|e| format!("处理清单格式错误: {}", e))
+}
+
+// 将清单写入输出目录，append 为 true 时追加到已有清单之后
+pub fn write_manifest(output_dir: &Path, entries: Vec<ManifestEntry>, append: bool, config: &AppConfig) -> Result<usize, String> {
+    let manifest_path = output_dir.join(MANIFEST_FILE_NAME);
+    
+    let mut all_entries: Vec<ManifestEntry> = if append {
+        read_manifest(output_dir).map_err(|e| format!("{}，未写入新记录", e))?
+    } else {
+        Vec::new()
+    };
+    
+    all_entries.extend(entries);
+    
+    let manifest_json = serde_json::to_string_pretty(&all_entries)
+        .map_err(|e| format!("序列化处理清单失败: {}", e))?;
+    
+    write_text_file(&manifest_path, &manifest_json, config)
+        .map_err(|e| format!("写入处理清单失败: {}", e))?;
+    
+    Ok(all_entries.len())
+}
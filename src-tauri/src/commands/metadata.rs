@@ -1,6 +1,14 @@
 use serde::{Deserialize, Serialize};
-use tauri::command;
+use tauri::{command, State};
 use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use lazy_static::lazy_static;
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::template::{absolute_episode, episode_offset_for, normalize_rendered_name, replace_padded_number};
+use crate::commands::errors::AppError;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AnimeInfo {
@@ -11,59 +19,206 @@ pub struct AnimeInfo {
     pub season: Option<u32>,
     pub year: Option<u32>,
     pub format: Option<String>,
+    // 加到集数上得到 {episode_abs} 的偏移，未提供时使用配置 episode_offsets 中该系列该季的偏移
+    #[serde(default)]
+    pub episode_offset: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ParsedFilename {
     pub anime_title: String,
+    #[serde(default)]
+    pub title_clean: String,
     pub episode_number: Option<u32>,
     pub season: Option<u32>,
     pub group: Option<String>,
     pub resolution: Option<String>,
     pub video_codec: Option<String>,
     pub audio_codec: Option<String>,
+    // OVA、OAD、SP 等特别篇，或季度为 0
+    #[serde(default)]
+    pub is_special: bool,
+    // Anitomy 识别出的类型（OVA、SP、Movie 等，保留原文），未识别时为空
+    #[serde(default)]
+    pub anime_type: Option<String>,
+    // 标题来源：anitomy / fallback（备用提取）/ folder（所在文件夹）/ none（未解析出标题）
+    #[serde(default)]
+    pub title_source: String,
+    // 从所在文件夹名补全的字段：title / season / episode，其余字段来自文件名
+    #[serde(default)]
+    pub folder_fields: Vec<String>,
+    // 集数来源：episode / episode_alt / volume（Anitomy 字段）、regex（集数正则）、anime_type（SP01 这类特别篇编号）、folder（所在文件夹），未解析出集数时为空
+    #[serde(default)]
+    pub episode_source: String,
+    // 版本号，如 v2 中的 "2"
+    #[serde(default)]
+    pub release_version: Option<String>,
+    // 文件名中的 CRC32 校验值
+    #[serde(default)]
+    pub file_checksum: Option<String>,
+    // 解析是否可信：标题为空或需要备用提取时为 false，界面据此提示人工确认
+    #[serde(default)]
+    pub confident: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// Anitomy 给出的一个集数候选
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpisodeCandidate {
+    // episode / episode_alt / volume
+    pub field: String,
+    pub raw: String,
+    pub value: Option<u32>,
+    // 数值是否在合理范围内（不是年份、不超过集数上限）
+    pub plausible: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AniListResponse {
     pub id: u32,
     pub title: AniListTitle,
     pub format: Option<String>,
     pub episodes: Option<u32>,
+    pub season: Option<String>,
     #[serde(rename = "seasonYear")]
     pub season_year: Option<u32>,
     #[serde(rename = "coverImage")]
     pub cover_image: Option<AniListCoverImage>,
+    pub description: Option<String>,
+    pub genres: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AniListTitle {
     pub romaji: Option<String>,
     pub english: Option<String>,
     pub native: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// 命名时实际采用的标题字段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleField {
+    Romaji,
+    English,
+    Native,
+    Parsed,
+}
+
+impl AniListTitle {
+    // AniList 可能返回 null 或空字符串，两者都视为没有该标题
+    pub fn get(&self, field: TitleField) -> Option<&str> {
+        let title = match field {
+            TitleField::Romaji => self.romaji.as_deref(),
+            TitleField::English => self.english.as_deref(),
+            TitleField::Native => self.native.as_deref(),
+            TitleField::Parsed => None,
+        };
+        title.map(str::trim).filter(|title| !title.is_empty())
+    }
+}
+
+// 选出命名和匹配使用的标题：prefer_romaji 时按 romaji → english → native，否则按 english → romaji → native，
+// 都为空时使用文件名解析出的标题；同时返回实际采用的字段
+pub fn best_title(anime: Option<&AniListResponse>, prefer_romaji: bool, parsed_title: &str) -> (String, TitleField) {
+    let order = if prefer_romaji {
+        [TitleField::Romaji, TitleField::English, TitleField::Native]
+    } else {
+        [TitleField::English, TitleField::Romaji, TitleField::Native]
+    };
+    
+    anime
+        .and_then(|anime| order.iter().find_map(|field| anime.title.get(*field).map(|title| (title.to_string(), *field))))
+        .unwrap_or_else(|| (parsed_title.trim().to_string(), TitleField::Parsed))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AniListCoverImage {
     pub large: Option<String>,
     pub medium: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AniListRelation {
+    pub id: u32,
+    pub relation_type: String,
+    pub title: AniListTitle,
+    pub format: Option<String>,
+    pub episodes: Option<u32>,
+    pub season_year: Option<u32>,
+}
+
+// 归为特别篇的 Anitomy 类型
+const SPECIAL_ANIME_TYPES: [&str; 5] = ["OVA", "OAD", "SP", "SPECIAL", "SPECIALS"];
+
+// 集数候选字段，按默认优先级排列
+const EPISODE_FIELDS: [&str; 3] = ["episode", "episode_alt", "volume"];
+
+// 超过该值的数字不太可能是集数（多为年份、分辨率或校验码）
+const MAX_PLAUSIBLE_EPISODE: u32 = 1500;
+
+// 系列导航关心的关联类型
+const SERIES_RELATION_TYPES: [&str; 3] = ["SEQUEL", "PREQUEL", "SIDE_STORY"];
+
+lazy_static! {
+    // AniList ID -> (集数 -> 单集标题)，同一部动漫只查询一次
+    static ref EPISODE_TITLE_CACHE: RwLock<HashMap<u32, HashMap<u32, String>>> = RwLock::new(HashMap::new());
+    // (当前并发上限, 信号量)，所有元数据网络请求共用
+    static ref METADATA_LIMITER: Mutex<(usize, Arc<Semaphore>)> = Mutex::new((0, Arc::new(Semaphore::new(0))));
+    // 备用标题提取：标题之后常见的集数、季度和画质标记，从最早出现的标记处截断
+    static ref FALLBACK_TITLE_STOP: regex::Regex = regex::Regex::new(
+        r"(?i)(\s-\s*\d{1,4}\b|第\s*\d+\s*[话話集]|\b(S\d{1,2}(E\d{1,4})?|EP?\s?\d{1,4}|\d{3,4}[pi]|[xh]\.?26[45]|hevc|avc|web-?(dl|rip)|bd-?rip|blu-?ray|10-?bit|aac|flac)\b)"
+    ).unwrap();
+    // 方括号、圆括号和全角括号中的内容（字幕组、画质等）
+    // 只表示季度的文件夹名，例如 "Season 2"、"S02"、"第2季"
+    static ref SEASON_ONLY_FOLDER: regex::Regex = regex::Regex::new(r"(?i)^\s*(?:season\s*|s)(\d{1,2})\s*$|^\s*第\s*(\d{1,2})\s*季\s*$").unwrap();
+    static ref FALLBACK_TITLE_BRACKETS: regex::Regex = regex::Regex::new(r"\[[^\]]*\]|\([^)]*\)|【[^】]*】").unwrap();
+    // 搜索词中常见的发布信息：分辨率、编码、片源等，评分前去掉
+    static ref RELEASE_NOISE: regex::Regex = regex::Regex::new(
+        r"(?i)\b(\d{3,4}[pi]|\d{3,4}x\d{3,4}|[xh]\.?26[45]|hevc|avc|web-?(dl|rip)|bd-?rip|blu-?ray|10-?bit|8-?bit|aac|flac|dual[ -]?audio)\b"
+    ).unwrap();
+    // Anitomy 未识别类型时兜底匹配的特别篇编号，例如 "SP01"、"OVA 2"
+    static ref SPECIAL_EPISODE_MARKER: regex::Regex = regex::Regex::new(r"(?i)\b(SP|OVA|OAD)\s?(\d{1,3})\b").unwrap();
+}
+
+// 类型对应的季度：特别篇类型归入第 0 季，TV、Movie、ONA 等返回 None，沿用解析出的季度
+pub fn season_for_anime_type(anime_type: &str) -> Option<u32> {
+    SPECIAL_ANIME_TYPES.contains(&anime_type.trim().to_uppercase().as_str()).then_some(0)
+}
+
 #[command]
-pub async fn parse_anime_filename(filename: String) -> Result<ParsedFilename, String> {
+pub async fn parse_anime_filename(filename: String) -> Result<ParsedFilename, AppError> {
+    parse_filename_internal(&filename).map_err(AppError::Parse)
+}
+
+// 文件名解析的核心函数，供其他模块同步调用
+pub fn parse_filename_internal(filename: &str) -> Result<ParsedFilename, String> {
+    parse_filename_with_config(filename, &read_app_config())
+}
+
+// 按给定配置解析文件名（集数正则和标题清理规则来自配置），用于预览尚未保存的配置
+pub fn parse_filename_with_config(filename: &str, config: &AppConfig) -> Result<ParsedFilename, String> {
     use anitomy::{Anitomy, ElementCategory};
     
     let mut anitomy = Anitomy::new();
-    let elements = anitomy.parse(&filename).map_err(|e| format!("Anitomy解析失败: {:?}", e))?;
+    let elements = anitomy.parse(filename).map_err(|e| format!("Anitomy解析失败: {:?}", e))?;
     
     let mut parsed = ParsedFilename {
         anime_title: String::new(),
+        title_clean: String::new(),
         episode_number: None,
         season: None,
         group: None,
         resolution: None,
         video_codec: None,
         audio_codec: None,
+        is_special: false,
+        anime_type: None,
+        title_source: "anitomy".to_string(),
+        folder_fields: Vec::new(),
+        episode_source: String::new(),
+        release_version: None,
+        file_checksum: None,
+        confident: true,
     };
     
     // 正确使用Elements API获取各个元素
@@ -71,10 +226,9 @@ pub async fn parse_anime_filename(filename: String) -> Result<ParsedFilename, St
         parsed.anime_title = title.to_string();
     }
     
-    if let Some(ep_str) = elements.get(ElementCategory::EpisodeNumber) {
-        if let Ok(ep) = ep_str.parse::<u32>() {
-            parsed.episode_number = Some(ep);
-        }
+    if let Some(candidate) = choose_episode_candidate(&episode_candidates(&elements)) {
+        parsed.episode_number = candidate.value;
+        parsed.episode_source = candidate.field.clone();
     }
     
     if let Some(season_str) = elements.get(ElementCategory::AnimeSeason) {
@@ -91,6 +245,14 @@ pub async fn parse_anime_filename(filename: String) -> Result<ParsedFilename, St
         parsed.resolution = Some(resolution.to_string());
     }
     
+    if let Some(version) = elements.get(ElementCategory::ReleaseVersion) {
+        parsed.release_version = Some(version.to_string());
+    }
+    
+    if let Some(checksum) = elements.get(ElementCategory::FileChecksum) {
+        parsed.file_checksum = Some(checksum.to_string());
+    }
+    
     // 处理视频编码
     if let Some(video_term) = elements.get(ElementCategory::VideoTerm) {
         let value = video_term.to_lowercase();
@@ -106,22 +268,493 @@ pub async fn parse_anime_filename(filename: String) -> Result<ParsedFilename, St
         parsed.audio_codec = Some(audio_term.to_uppercase());
     }
     
-    // 如果Anitomy没有解析出标题，使用备用方法
+    // 特别篇类型，TV、Movie、ONA 等不算
+    if let Some(anime_type) = elements.get(ElementCategory::AnimeType) {
+        parsed.anime_type = Some(anime_type.to_string());
+    } else if let Some(caps) = SPECIAL_EPISODE_MARKER.captures(filename) {
+        parsed.anime_type = Some(caps[1].to_uppercase());
+        if parsed.episode_number.is_none() {
+            parsed.episode_number = caps[2].parse().ok();
+            parsed.episode_source = "anime_type".to_string();
+        }
+    }
+    parsed.is_special = parsed.anime_type.as_deref().and_then(season_for_anime_type) == Some(0);
+    if parsed.season == Some(0) {
+        parsed.is_special = true;
+    }
+    
+    // 如果Anitomy没有解析出标题，按配置使用备用方法；关闭时保持为空，让解析失败显式可见
     if parsed.anime_title.is_empty() {
-        parsed.anime_title = extract_anime_title(&filename);
+        if config.fallback_title_extractor {
+            parsed.anime_title = extract_anime_title(filename);
+        }
+        parsed.title_source = if parsed.anime_title.is_empty() { "none" } else { "fallback" }.to_string();
+        parsed.confident = false;
+    }
+    
+    // Anitomy没有解析出集数时，尝试用户配置的正则
+    if parsed.episode_number.is_none() {
+        if let Some(pattern) = config.episode_regex.as_deref().filter(|pattern| !pattern.trim().is_empty()) {
+            match episode_from_regex(filename, pattern) {
+                Ok(Some(episode)) => {
+                    parsed.episode_number = Some(episode);
+                    parsed.episode_source = "regex".to_string();
+                }
+                Ok(None) => {},
+                Err(e) => tracing::warn!("集数正则无效，已忽略: {}", e),
+            }
+        }
     }
     
+    parsed.title_clean = clean_title(&parsed.anime_title, config)
+        .last()
+        .map(|step| step.result.clone())
+        .unwrap_or_else(|| parsed.anime_title.clone());
+    
     Ok(parsed)
 }
 
+// 收集 Anitomy 解析出的所有类似集数的元素：EpisodeNumber（范围时有多个）、EpisodeNumberAlt、VolumeNumber
+fn episode_candidates(elements: &anitomy::Elements) -> Vec<EpisodeCandidate> {
+    use anitomy::ElementCategory;
+    
+    let categories = [ElementCategory::EpisodeNumber, ElementCategory::EpisodeNumberAlt, ElementCategory::VolumeNumber];
+    EPISODE_FIELDS.iter().zip(categories)
+        .flat_map(|(field, category)| {
+            elements.get_all(category).into_iter().map(move |raw| {
+                let value = raw.trim().parse::<u32>().ok();
+                EpisodeCandidate {
+                    field: field.to_string(),
+                    raw: raw.to_string(),
+                    value,
+                    plausible: value.is_some_and(is_plausible_episode),
+                }
+            })
+        })
+        .collect()
+}
+
+// 四位数的年份和过大的数字不当作集数
+fn is_plausible_episode(value: u32) -> bool {
+    value <= MAX_PLAUSIBLE_EPISODE && !(1900..=2100).contains(&value)
+}
+
+// 按字段优先级选择第一个合理的候选；EpisodeNumber 有多个值（如 01-12 合集）时取第一个
+fn choose_episode_candidate(candidates: &[EpisodeCandidate]) -> Option<&EpisodeCandidate> {
+    candidates.iter().find(|candidate| candidate.plausible)
+}
+
+// 解析文件名并返回所有集数候选，供调试和同批文件比较
+pub(crate) fn filename_episode_candidates(filename: &str) -> Result<Vec<EpisodeCandidate>, String> {
+    let mut anitomy = anitomy::Anitomy::new();
+    let elements = anitomy.parse(filename).map_err(|e| format!("Anitomy解析失败: {:?}", e))?;
+    Ok(episode_candidates(&elements))
+}
+
+// 同一批文件中各候选字段的统计
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EpisodeFieldScore {
+    pub field: String,
+    // 该字段给出合理集数的文件数
+    pub files_with_value: usize,
+    // 这些文件中不同集数的个数，同一部剧的各集应当互不相同
+    pub distinct_values: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EpisodeFieldDetection {
+    // 选出的字段，所有文件都没有合理候选时为空
+    pub field: Option<String>,
+    pub scores: Vec<EpisodeFieldScore>,
+    // 文件 -> 按选出的字段得到的集数，该文件缺少此字段时退回单文件的默认选择
+    pub episodes: HashMap<String, Option<u32>>,
+}
+
+// 比较同一批文件（通常是同一文件夹中的各集）的集数候选，选出最能区分各集的字段：
+// 不同集数最多的字段胜出，相同时按 episode、episode_alt、volume 的顺序
 #[command]
-pub async fn search_anilist(query: String) -> Result<Vec<AniListResponse>, String> {
-    let client = reqwest::Client::new();
+pub async fn detect_episode_field(files: Vec<String>) -> Result<EpisodeFieldDetection, AppError> {
+    let candidates: Vec<(String, Vec<EpisodeCandidate>)> = files.into_iter()
+        .map(|file| {
+            let file_name = std::path::Path::new(&file).file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| file.clone());
+            let candidates = filename_episode_candidates(&file_name).unwrap_or_default();
+            (file, candidates)
+        })
+        .collect();
     
-    let graphql_query = r#"
-    query ($search: String) {
-        Page(page: 1, perPage: 10) {
-            media(search: $search, type: ANIME) {
+    let scores: Vec<EpisodeFieldScore> = EPISODE_FIELDS.iter()
+        .map(|field| {
+            let values: Vec<u32> = candidates.iter()
+                .filter_map(|(_, file_candidates)| {
+                    file_candidates.iter()
+                        .find(|candidate| candidate.field == *field && candidate.plausible)
+                        .and_then(|candidate| candidate.value)
+                })
+                .collect();
+            let distinct: std::collections::HashSet<u32> = values.iter().copied().collect();
+            EpisodeFieldScore {
+                field: field.to_string(),
+                files_with_value: values.len(),
+                distinct_values: distinct.len(),
+            }
+        })
+        .collect();
+    
+    // max_by 在相等时返回最后一个，因此倒序遍历使优先级高的字段胜出
+    let field = scores.iter()
+        .rev()
+        .filter(|score| score.distinct_values > 0)
+        .max_by_key(|score| (score.distinct_values, score.files_with_value))
+        .map(|score| score.field.clone());
+    
+    let episodes = candidates.into_iter()
+        .map(|(file, file_candidates)| {
+            let from_field = field.as_ref().and_then(|field| {
+                file_candidates.iter().find(|candidate| candidate.field == *field && candidate.plausible)
+            });
+            let episode = from_field.or_else(|| choose_episode_candidate(&file_candidates))
+                .and_then(|candidate| candidate.value);
+            (file, episode)
+        })
+        .collect();
+    
+    tracing::info!("集数字段检测结果: {:?}", field);
+    Ok(EpisodeFieldDetection { field, scores, episodes })
+}
+
+// 按完整路径解析：配置开启时，文件名缺少标题（或标题只是备用提取的猜测、纯数字）、季度或集数时，
+// 从所在文件夹名补全，例如 "某动漫/01.mkv"；父文件夹只表示季度时（"Season 2"）再向上取一级作为标题来源
+pub fn parse_path_with_config(path: &std::path::Path, config: &AppConfig) -> Result<ParsedFilename, String> {
+    let file_name = path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or("无效的文件路径")?;
+    let mut parsed = parse_filename_with_config(&file_name, config)?;
+    if !config.folder_context_parsing {
+        return Ok(parsed);
+    }
+    
+    let title_missing = parsed.title_source != "anitomy"
+        || parsed.anime_title.chars().all(|c| c.is_ascii_digit() || c.is_whitespace());
+    if !title_missing && parsed.season.is_some() && parsed.episode_number.is_some() {
+        return Ok(parsed);
+    }
+    
+    for folder in path.ancestors().skip(1).take(2) {
+        let folder_name = match folder.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => break,
+        };
+        
+        if let Some(captures) = SEASON_ONLY_FOLDER.captures(&folder_name) {
+            let season = captures.get(1).or_else(|| captures.get(2))
+                .and_then(|season| season.as_str().parse().ok());
+            if parsed.season.is_none() && season.is_some() {
+                parsed.season = season;
+                parsed.folder_fields.push("season".to_string());
+            }
+            continue;
+        }
+        
+        let folder_parsed = match parse_filename_with_config(&folder_name, config) {
+            Ok(folder_parsed) => folder_parsed,
+            Err(_) => break,
+        };
+        if title_missing && !folder_parsed.anime_title.is_empty() {
+            parsed.anime_title = folder_parsed.anime_title;
+            parsed.title_clean = folder_parsed.title_clean;
+            parsed.title_source = "folder".to_string();
+            parsed.folder_fields.push("title".to_string());
+        }
+        if parsed.season.is_none() && folder_parsed.season.is_some() {
+            parsed.season = folder_parsed.season;
+            parsed.folder_fields.push("season".to_string());
+        }
+        if parsed.episode_number.is_none() && folder_parsed.episode_number.is_some() {
+            parsed.episode_number = folder_parsed.episode_number;
+            parsed.episode_source = "folder".to_string();
+            parsed.folder_fields.push("episode".to_string());
+        }
+        break;
+    }
+    
+    if parsed.season == Some(0) {
+        parsed.is_special = true;
+    }
+    
+    Ok(parsed)
+}
+
+pub fn parse_path_internal(path: &std::path::Path) -> Result<ParsedFilename, String> {
+    parse_path_with_config(path, &read_app_config())
+}
+
+// 按完整路径解析文件名，文件名信息不全时由所在文件夹补全
+#[command]
+pub async fn parse_anime_path(path: String) -> Result<ParsedFilename, AppError> {
+    parse_path_internal(std::path::Path::new(&path)).map_err(AppError::Parse)
+}
+
+// 用带命名分组 ep 的正则从文件名中提取集数，正则无效或缺少 ep 分组时返回错误
+pub fn episode_from_regex(filename: &str, pattern: &str) -> Result<Option<u32>, String> {
+    let re = regex::Regex::new(pattern)
+        .map_err(|e| format!("无效的正则表达式: {}", e))?;
+    if !re.capture_names().any(|name| name == Some("ep")) {
+        return Err("正则中缺少命名分组 (?P<ep>...)".to_string());
+    }
+    
+    Ok(re.captures(filename)
+        .and_then(|caps| caps.name("ep"))
+        .and_then(|ep| ep.as_str().trim().parse().ok()))
+}
+
+// 测试集数正则，便于在保存到配置前反复调整
+#[command]
+pub async fn test_episode_regex(filename: String, regex: String) -> Result<Option<u32>, AppError> {
+    episode_from_regex(&filename, &regex).map_err(AppError::Parse)
+}
+
+// 标题清理中的一步及其结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TitleCleanStep {
+    pub step: String,
+    pub result: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParseDebug {
+    pub filename: String,
+    pub parsed: ParsedFilename,
+    pub title_steps: Vec<TitleCleanStep>,
+    // Anitomy 给出的所有集数候选，parsed.episode_source 为最终采用的来源
+    pub episode_candidates: Vec<EpisodeCandidate>,
+}
+
+// 按配置依次清理解析出的标题，返回每个已启用步骤的结果，最后一步即为 {title_clean}
+pub fn clean_title(title: &str, config: &AppConfig) -> Vec<TitleCleanStep> {
+    let mut steps = Vec::new();
+    let mut current = title.to_string();
+    
+    if config.title_replace_underscores {
+        current = current.replace('_', " ");
+        steps.push(TitleCleanStep { step: "replace_underscores".to_string(), result: current.clone() });
+    }
+    
+    if config.title_replace_dots {
+        // 只替换夹在两个单词之间的点，保留 "2.5" 这样的数字和末尾的缩写点
+        let chars: Vec<char> = current.chars().collect();
+        current = chars.iter()
+            .enumerate()
+            .map(|(i, c)| {
+                let between_words = *c == '.'
+                    && i > 0
+                    && i + 1 < chars.len()
+                    && chars[i - 1].is_alphanumeric()
+                    && chars[i + 1].is_alphanumeric()
+                    && !(chars[i - 1].is_ascii_digit() && chars[i + 1].is_ascii_digit());
+                if between_words { ' ' } else { *c }
+            })
+            .collect();
+        steps.push(TitleCleanStep { step: "replace_dots".to_string(), result: current.clone() });
+    }
+    
+    if config.title_strip_brackets {
+        // 去除包裹整个标题的括号，例如 "[Show Name]" -> "Show Name"
+        let pairs = [('[', ']'), ('(', ')'), ('【', '】'), ('「', '」')];
+        loop {
+            let trimmed = current.trim();
+            let stripped = pairs.iter().find_map(|(open, close)| {
+                trimmed.strip_prefix(*open)?.strip_suffix(*close)
+            });
+            match stripped {
+                Some(inner) => current = inner.to_string(),
+                None => break,
+            }
+        }
+        steps.push(TitleCleanStep { step: "strip_brackets".to_string(), result: current.clone() });
+    }
+    
+    if config.title_collapse_whitespace {
+        current = current.split_whitespace().collect::<Vec<_>>().join(" ");
+        steps.push(TitleCleanStep { step: "collapse_whitespace".to_string(), result: current.clone() });
+    }
+    
+    steps
+}
+
+// 解析调试：返回解析结果以及标题清理每一步前后的变化
+#[command]
+pub async fn debug_parse_filename(filename: String) -> Result<ParseDebug, AppError> {
+    let parsed = parse_filename_internal(&filename).map_err(AppError::Parse)?;
+    let mut title_steps = vec![TitleCleanStep {
+        step: "raw".to_string(),
+        result: parsed.anime_title.clone(),
+    }];
+    title_steps.extend(clean_title(&parsed.anime_title, &read_app_config()));
+    let episode_candidates = filename_episode_candidates(&filename).map_err(AppError::Parse)?;
+    
+    Ok(ParseDebug {
+        filename,
+        parsed,
+        title_steps,
+        episode_candidates,
+    })
+}
+
+// AniList 搜索缓存最多保留的查询数
+const ANILIST_CACHE_CAPACITY: usize = 256;
+
+// 没有 Retry-After 头时建议等待的秒数，AniList 的限流窗口为一分钟
+const DEFAULT_RETRY_AFTER_SECS: u64 = 60;
+
+struct CachedSearch {
+    results: Vec<AniListResponse>,
+    fetched_at: Instant,
+    last_used: u64,
+}
+
+// 按规范化查询字符串缓存的 AniList 搜索结果，超过容量时淘汰最久未使用的条目
+#[derive(Default)]
+pub struct AniListSearchCache {
+    entries: HashMap<String, CachedSearch>,
+    clock: u64,
+}
+
+impl AniListSearchCache {
+    fn get(&mut self, key: &str, ttl: Duration) -> Option<Vec<AniListResponse>> {
+        self.clock += 1;
+        let clock = self.clock;
+        match self.entries.get_mut(key) {
+            Some(entry) if entry.fetched_at.elapsed() < ttl => {
+                entry.last_used = clock;
+                Some(entry.results.clone())
+            }
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+    
+    fn insert(&mut self, key: String, results: Vec<AniListResponse>) {
+        self.clock += 1;
+        if self.entries.len() >= ANILIST_CACHE_CAPACITY && !self.entries.contains_key(&key) {
+            let oldest = self.entries.iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, CachedSearch {
+            results,
+            fetched_at: Instant::now(),
+            last_used: self.clock,
+        });
+    }
+}
+
+pub type AniListCache = Arc<Mutex<AniListSearchCache>>;
+
+pub fn create_anilist_cache() -> AniListCache {
+    Arc::new(Mutex::new(AniListSearchCache::default()))
+}
+
+// 缓存键：忽略大小写和多余的空白
+pub(crate) fn normalize_query(query: &str) -> String {
+    query.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AniListErrorCode {
+    RateLimited,
+    Other,
+}
+
+// AniList 请求失败的原因，被限流时附带建议的等待秒数，供界面退避重试
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AniListError {
+    pub code: AniListErrorCode,
+    pub message: String,
+    pub retry_after_secs: Option<u64>,
+}
+
+impl std::fmt::Display for AniListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<String> for AniListError {
+    fn from(message: String) -> Self {
+        AniListError { code: AniListErrorCode::Other, message, retry_after_secs: None }
+    }
+}
+
+impl From<&str> for AniListError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<AniListError> for String {
+    fn from(error: AniListError) -> Self {
+        error.message
+    }
+}
+
+#[command]
+pub async fn search_anilist(query: String, anilist_cache: State<'_, AniListCache>) -> Result<Vec<AniListResponse>, AniListError> {
+    search_anilist_cached(&anilist_cache, &query).await
+}
+
+// 先查缓存，缓存中没有或已过期时才请求 AniList；anilist_cache_ttl_secs 为 0 时不使用缓存
+pub(crate) async fn search_anilist_cached(cache: &AniListCache, query: &str) -> Result<Vec<AniListResponse>, AniListError> {
+    let ttl = Duration::from_secs(read_app_config().anilist_cache_ttl_secs);
+    let key = normalize_query(query);
+    
+    if !ttl.is_zero() {
+        if let Some(results) = cache.lock().unwrap().get(&key, ttl) {
+            return Ok(results);
+        }
+    }
+    
+    let results = fetch_anilist_search(query).await?;
+    if !ttl.is_zero() {
+        cache.lock().unwrap().insert(key, results.clone());
+    }
+    Ok(results)
+}
+
+async fn fetch_anilist_search(query: &str) -> Result<Vec<AniListResponse>, AniListError> {
+    let graphql_query = format!(r#"
+    query ($search: String) {{
+        Page(page: 1, perPage: 10) {{
+            media(search: $search, type: ANIME) {{
+                {}
+            }}
+        }}
+    }}
+    "#, ANILIST_MEDIA_FIELDS);
+    
+    let variables = serde_json::json!({
+        "search": query
+    });
+    
+    let json_response = post_anilist_query(&graphql_query, variables).await?;
+    
+    let media_list = json_response["data"]["Page"]["media"]
+        .as_array()
+        .ok_or("无效的响应格式")?;
+    
+    Ok(parse_media_list(media_list))
+}
+
+// 搜索结果中每个条目查询的字段，单个搜索和批量搜索共用
+const ANILIST_MEDIA_FIELDS: &str = r#"
                 id
                 title {
                     romaji
@@ -130,20 +763,259 @@ pub async fn search_anilist(query: String) -> Result<Vec<AniListResponse>, Strin
                 }
                 format
                 episodes
+                season
                 seasonYear
                 coverImage {
                     large
                     medium
                 }
+                description
+                genres
+"#;
+
+// 一次批量请求中合并的搜索数，过多会超出 AniList 的查询复杂度限制
+const ANILIST_BATCH_SIZE: usize = 5;
+
+fn parse_media_list(media_list: &[serde_json::Value]) -> Vec<AniListResponse> {
+    media_list.iter()
+        .filter_map(|media| serde_json::from_value::<AniListResponse>(media.clone()).ok())
+        .collect()
+}
+
+// 批量搜索多个标题，按 normalize_query 后的键返回每个标题的结果，空标题忽略
+// 先查缓存，其余标题每 ANILIST_BATCH_SIZE 个合并为一个带别名的请求；单个标题失败不影响其他标题，被限流后剩余的标题不再请求
+pub(crate) async fn search_anilist_batch_cached(
+    cache: &AniListCache,
+    queries: &[String]
+) -> HashMap<String, Result<Vec<AniListResponse>, AniListError>> {
+    let ttl = Duration::from_secs(read_app_config().anilist_cache_ttl_secs);
+    let mut results = HashMap::new();
+    // (缓存键, 查询字符串)
+    let mut pending: Vec<(String, String)> = Vec::new();
+    
+    for query in queries {
+        let key = normalize_query(query);
+        if key.is_empty() || results.contains_key(&key) || pending.iter().any(|(pending_key, _)| *pending_key == key) {
+            continue;
+        }
+        if !ttl.is_zero() {
+            if let Some(cached) = cache.lock().unwrap().get(&key, ttl) {
+                results.insert(key, Ok(cached));
+                continue;
+            }
+        }
+        pending.push((key, query.trim().to_string()));
+    }
+    
+    let mut rate_limited: Option<AniListError> = None;
+    for chunk in pending.chunks(ANILIST_BATCH_SIZE) {
+        let fetched = match &rate_limited {
+            Some(e) => Err(e.clone()),
+            None => fetch_anilist_search_batch(chunk.iter().map(|(_, query)| query.as_str()).collect()).await,
+        };
+        match fetched {
+            Ok(chunk_results) => {
+                for ((key, _), found) in chunk.iter().zip(chunk_results) {
+                    if let (Ok(found), false) = (&found, ttl.is_zero()) {
+                        cache.lock().unwrap().insert(key.clone(), found.clone());
+                    }
+                    results.insert(key.clone(), found);
+                }
+            }
+            Err(e) => {
+                if e.code == AniListErrorCode::RateLimited {
+                    rate_limited = Some(e.clone());
+                }
+                for (key, _) in chunk {
+                    results.insert(key.clone(), Err(e.clone()));
+                }
+            }
+        }
+    }
+    
+    results
+}
+
+// 用别名 q0、q1... 在一个 GraphQL 请求中执行多个搜索，按输入顺序返回每个搜索的结果
+async fn fetch_anilist_search_batch(queries: Vec<&str>) -> Result<Vec<Result<Vec<AniListResponse>, AniListError>>, AniListError> {
+    let mut definitions = Vec::new();
+    let mut selections = Vec::new();
+    let mut variables = serde_json::Map::new();
+    for (index, query) in queries.iter().enumerate() {
+        definitions.push(format!("$q{}: String", index));
+        selections.push(format!(
+            "q{0}: Page(page: 1, perPage: 10) {{ media(search: $q{0}, type: ANIME) {{ ...searchMedia }} }}",
+            index
+        ));
+        variables.insert(format!("q{}", index), serde_json::Value::String(query.to_string()));
+    }
+    let graphql_query = format!(
+        "query ({}) {{\n{}\n}}\nfragment searchMedia on Media {{{}}}",
+        definitions.join(", "),
+        selections.join("\n"),
+        ANILIST_MEDIA_FIELDS
+    );
+    
+    let json_response = post_anilist_query(&graphql_query, serde_json::Value::Object(variables)).await?;
+    
+    // 某个别名出错时 AniList 仍返回其他别名的数据，出错的别名为 null
+    let error_message = json_response["errors"][0]["message"].as_str()
+        .map(|message| format!("AniList 返回错误: {}", message))
+        .unwrap_or_else(|| "无效的响应格式".to_string());
+    Ok((0..queries.len())
+        .map(|index| {
+            json_response["data"][format!("q{}", index)]["media"]
+                .as_array()
+                .map(|media_list| parse_media_list(media_list))
+                .ok_or_else(|| AniListError::from(error_message.clone()))
+        })
+        .collect())
+}
+
+// 获取AniList条目的续作、前作和外传，用于系列导航
+#[command]
+pub async fn get_anilist_relations(id: u32) -> Result<Vec<AniListRelation>, AppError> {
+    let graphql_query = r#"
+    query ($id: Int) {
+        Media(id: $id, type: ANIME) {
+            relations {
+                edges {
+                    relationType
+                    node {
+                        id
+                        type
+                        title {
+                            romaji
+                            english
+                            native
+                        }
+                        format
+                        episodes
+                        seasonYear
+                    }
+                }
             }
         }
     }
     "#;
     
     let variables = serde_json::json!({
-        "search": query
+        "id": id
+    });
+    
+    let json_response = post_anilist_query(graphql_query, variables).await?;
+    
+    let edges = json_response["data"]["Media"]["relations"]["edges"]
+        .as_array()
+        .ok_or_else(|| AppError::Parse("无效的响应格式".to_string()))?;
+    
+    let mut relations = Vec::new();
+    for edge in edges {
+        let relation_type = edge["relationType"].as_str().unwrap_or_default();
+        let node = &edge["node"];
+        
+        // 只保留动画条目，漫画、小说等原作关联不参与整理
+        if !SERIES_RELATION_TYPES.contains(&relation_type) || node["type"].as_str() != Some("ANIME") {
+            continue;
+        }
+        
+        let Some(relation_id) = node["id"].as_u64() else {
+            continue;
+        };
+        
+        let title = match serde_json::from_value::<AniListTitle>(node["title"].clone()) {
+            Ok(title) => title,
+            Err(_) => continue,
+        };
+        
+        relations.push(AniListRelation {
+            id: relation_id as u32,
+            relation_type: relation_type.to_string(),
+            title,
+            format: node["format"].as_str().map(|s| s.to_string()),
+            episodes: node["episodes"].as_u64().map(|v| v as u32),
+            season_year: node["seasonYear"].as_u64().map(|v| v as u32),
+        });
+    }
+    
+    Ok(relations)
+}
+
+// 从AniList的 streamingEpisodes 获取单集标题并按集数建立映射
+// 数据来自各流媒体平台，格式不统一，只能尽力解析，结果按动漫缓存
+#[command]
+pub async fn get_anilist_episode_titles(id: u32) -> Result<HashMap<u32, String>, AppError> {
+    if let Some(cached) = EPISODE_TITLE_CACHE.read().unwrap().get(&id) {
+        return Ok(cached.clone());
+    }
+    
+    let graphql_query = r#"
+    query ($id: Int) {
+        Media(id: $id, type: ANIME) {
+            streamingEpisodes {
+                title
+            }
+        }
+    }
+    "#;
+    
+    let variables = serde_json::json!({
+        "id": id
     });
     
+    let json_response = post_anilist_query(graphql_query, variables).await?;
+    
+    let episodes = json_response["data"]["Media"]["streamingEpisodes"]
+        .as_array()
+        .ok_or_else(|| AppError::Parse("无效的响应格式".to_string()))?;
+    
+    let titles: HashMap<u32, String> = episodes.iter()
+        .filter_map(|episode| episode["title"].as_str())
+        .filter_map(parse_streaming_episode_title)
+        .collect();
+    
+    EPISODE_TITLE_CACHE.write().unwrap().insert(id, titles.clone());
+    
+    Ok(titles)
+}
+
+// 解析 "Episode 3 - Title"、"Ep. 3: Title" 这类标题，去掉配音版本等后缀
+fn parse_streaming_episode_title(raw: &str) -> Option<(u32, String)> {
+    let pattern = regex::Regex::new(r"(?i)^\s*(?:episode|ep\.?)\s*(\d+)\s*[-:–—]\s*(.+?)\s*$").ok()?;
+    let caps = pattern.captures(raw)?;
+    let episode = caps.get(1)?.as_str().parse().ok()?;
+    
+    let suffix = regex::Regex::new(r"(?i)\s*\((?:english|spanish|portuguese|french|german|italian)?\s*dub\)$").ok()?;
+    let title = suffix.replace(caps.get(2)?.as_str(), "").trim().to_string();
+    
+    if title.is_empty() {
+        None
+    } else {
+        Some((episode, title))
+    }
+}
+
+// 获取一个元数据请求许可，限制同时进行的网络请求数
+// 并发上限变化时重建信号量，已持有旧许可的请求不受影响
+async fn acquire_metadata_permit() -> Result<OwnedSemaphorePermit, String> {
+    let limit = read_app_config().metadata_concurrent_limit.max(1);
+    let semaphore = {
+        let mut limiter = METADATA_LIMITER.lock().unwrap();
+        if limiter.0 != limit {
+            *limiter = (limit, Arc::new(Semaphore::new(limit)));
+        }
+        limiter.1.clone()
+    };
+    
+    semaphore.acquire_owned().await
+        .map_err(|e| format!("获取元数据请求许可失败: {}", e))
+}
+
+// 发送AniList GraphQL请求并返回解析后的JSON，被限流 (429) 时返回带等待时间的错误
+async fn post_anilist_query(graphql_query: &str, variables: serde_json::Value) -> Result<serde_json::Value, AniListError> {
+    let _permit = acquire_metadata_permit().await?;
+    let client = reqwest::Client::new();
+    
     let request_body = serde_json::json!({
         "query": graphql_query,
         "variables": variables
@@ -157,25 +1029,26 @@ pub async fn search_anilist(query: String) -> Result<Vec<AniListResponse>, Strin
         .await
         .map_err(|e| format!("AniList API请求失败: {}", e))?;
     
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        let retry_after_secs = response.headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .unwrap_or(DEFAULT_RETRY_AFTER_SECS);
+        tracing::warn!("AniList 请求被限流，{} 秒后可重试", retry_after_secs);
+        return Err(AniListError {
+            code: AniListErrorCode::RateLimited,
+            message: format!("AniList 请求过于频繁，请在 {} 秒后重试", retry_after_secs),
+            retry_after_secs: Some(retry_after_secs),
+        });
+    }
+    
     let response_text = response.text().await
         .map_err(|e| format!("读取响应失败: {}", e))?;
     
     // 解析GraphQL响应
-    let json_response: serde_json::Value = serde_json::from_str(&response_text)
-        .map_err(|e| format!("解析JSON失败: {}", e))?;
-    
-    let media_list = json_response["data"]["Page"]["media"]
-        .as_array()
-        .ok_or("无效的响应格式")?;
-    
-    let mut results = Vec::new();
-    for media in media_list {
-        if let Ok(anime) = serde_json::from_value::<AniListResponse>(media.clone()) {
-            results.push(anime);
-        }
-    }
-    
-    Ok(results)
+    serde_json::from_str(&response_text)
+        .map_err(|e| format!("解析JSON失败: {}", e).into())
 }
 
 #[command]
@@ -183,16 +1056,30 @@ pub async fn generate_filename(
     anime_info: AnimeInfo,
     episode: u32,
     template: String,
-) -> Result<String, String> {
+) -> Result<String, AppError> {
+    let config = read_app_config();
+    let offset = match anime_info.episode_offset {
+        Some(offset) if offset < 0 => return Err(format!("集数偏移不能为负数: {}", offset).into()),
+        Some(offset) => offset,
+        None => {
+            let titles = [anime_info.title.as_str(), anime_info.title_romaji.as_deref().unwrap_or_default()];
+            episode_offset_for(&config, &titles, anime_info.season)
+        }
+    };
+    let episode_abs = absolute_episode(episode, offset);
     let mut filename = template;
     
     // 替换模板变量
     filename = filename.replace("{title}", &anime_info.title);
     filename = filename.replace("{title_romaji}", 
-        &anime_info.title_romaji.unwrap_or_else(|| anime_info.title.clone()));
+        anime_info.title_romaji.as_deref().filter(|title| !title.trim().is_empty()).unwrap_or(&anime_info.title));
+    filename = replace_padded_number(&filename, "episode", Some(episode));
     filename = filename.replace("{episode}", &format!("{:02}", episode));
+    filename = replace_padded_number(&filename, "episode_abs", Some(episode_abs));
+    filename = filename.replace("{episode_abs}", &format!("{:02}", episode_abs));
     
     if let Some(season) = anime_info.season {
+        filename = replace_padded_number(&filename, "season", Some(season));
         filename = filename.replace("{season}", &format!("S{:02}", season));
     }
     
@@ -200,16 +1087,259 @@ pub async fn generate_filename(
         filename = filename.replace("{year}", &year.to_string());
     }
     
-    Ok(filename)
+    Ok(normalize_rendered_name(&filename, &config.template_separators))
 }
 
-// 辅助函数用于基础文件名解析
+// 去掉搜索词中的字幕组（括号内容）和分辨率、编码等发布信息，全部去掉后为空时保留原搜索词
+pub fn strip_release_noise(query: &str) -> String {
+    let without_brackets = FALLBACK_TITLE_BRACKETS.replace_all(query, " ");
+    let cleaned = RELEASE_NOISE.replace_all(&without_brackets, " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c == '-' || c == '~' || c.is_whitespace())
+        .to_string();
+    
+    if cleaned.is_empty() {
+        query.trim().to_string()
+    } else {
+        cleaned
+    }
+}
+
+// 备用标题提取：去掉扩展名和所有括号内容，点和下划线视为空格，再从第一个集数或画质标记处截断
 fn extract_anime_title(filename: &str) -> String {
-    // 简单的标题提取逻辑，后续将被anitomy-rs替代
-    filename.split('[').next()
-        .unwrap_or(filename)
-        .trim()
-        .to_string()
+    let path = std::path::Path::new(filename);
+    let stem = match path.extension() {
+        Some(ext) if ext.len() <= 5 && ext.to_string_lossy().chars().all(|c| c.is_ascii_alphanumeric()) => {
+            path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default()
+        }
+        _ => filename.to_string(),
+    };
+    
+    let without_brackets = FALLBACK_TITLE_BRACKETS.replace_all(&stem, " ");
+    let spaced = without_brackets.replace(['.', '_'], " ");
+    let title = match FALLBACK_TITLE_STOP.find(&spaced) {
+        Some(stop) => &spaced[..stop.start()],
+        None => spaced.as_str(),
+    };
+    
+    let title = title.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(|c: char| c == '-' || c == '~' || c.is_whitespace())
+        .to_string();
+    if !title.is_empty() {
+        return title;
+    }
+    
+    // 整个文件名都在括号中时（如 "[字幕组][标题][05][1080P]"），跳过第一个括号（通常是字幕组），取第一个不是标记或纯数字的括号内容
+    FALLBACK_TITLE_BRACKETS.find_iter(&stem)
+        .skip(1)
+        .map(|bracket| {
+            let inner: String = bracket.as_str().chars().skip(1).collect();
+            let mut inner_chars = inner.chars();
+            inner_chars.next_back();
+            inner_chars.as_str().trim().to_string()
+        })
+        .find(|inner| {
+            !inner.is_empty()
+                && !inner.chars().all(|c| c.is_ascii_digit())
+                && FALLBACK_TITLE_STOP.find(&format!(" {} ", inner)).is_none_or(|stop| stop.start() > 1)
+        })
+        .unwrap_or_default()
 }
 
 // 这些函数已被anitomy-rs库替代，不再需要
+
+// 解析质量较差的文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PoorParse {
+    pub filename: String,
+    // 缺失的字段：title / episode / season
+    pub missing: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParseCoverage {
+    pub total: usize,
+    pub title_percent: f32,
+    pub episode_percent: f32,
+    pub season_percent: f32,
+    // 按缺失字段数量从多到少排序
+    pub worst: Vec<PoorParse>,
+}
+
+// 文件名解析覆盖率的最差文件列表长度
+const PARSE_COVERAGE_WORST_LIMIT: usize = 20;
+
+// 统计文件夹中视频文件名的解析覆盖率，用于判断能否信任自动命名
+#[command]
+pub async fn parse_coverage(folder: String) -> Result<ParseCoverage, AppError> {
+    use rayon::prelude::*;
+    use crate::commands::file_operations::{scan_directory_internal, FileKind};
+    
+    if !std::path::Path::new(&folder).is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", folder)));
+    }
+    
+    let (files, _) = scan_directory_internal(&folder, &read_app_config());
+    let filenames: Vec<String> = files.into_iter()
+        .filter(|file| file.kind == FileKind::Video)
+        .map(|file| file.name)
+        .collect();
+    
+    // 并行解析，解析失败视为所有字段缺失
+    let results: Vec<PoorParse> = filenames.par_iter()
+        .map(|filename| {
+            let mut missing = Vec::new();
+            match parse_filename_internal(filename) {
+                Ok(parsed) => {
+                    if parsed.anime_title.trim().is_empty() {
+                        missing.push("title".to_string());
+                    }
+                    if parsed.episode_number.is_none() {
+                        missing.push("episode".to_string());
+                    }
+                    if parsed.season.is_none() {
+                        missing.push("season".to_string());
+                    }
+                }
+                Err(_) => {
+                    missing = vec!["title".to_string(), "episode".to_string(), "season".to_string()];
+                }
+            }
+            PoorParse { filename: filename.clone(), missing }
+        })
+        .collect();
+    
+    let total = results.len();
+    let percent = |field: &str| {
+        if total == 0 {
+            return 0.0;
+        }
+        let found = results.iter().filter(|result| !result.missing.iter().any(|m| m == field)).count();
+        found as f32 * 100.0 / total as f32
+    };
+    let title_percent = percent("title");
+    let episode_percent = percent("episode");
+    let season_percent = percent("season");
+    
+    let mut worst: Vec<PoorParse> = results.into_iter()
+        .filter(|result| !result.missing.is_empty())
+        .collect();
+    worst.sort_by(|a, b| b.missing.len().cmp(&a.missing.len()).then_with(|| a.filename.cmp(&b.filename)));
+    worst.truncate(PARSE_COVERAGE_WORST_LIMIT);
+    
+    Ok(ParseCoverage {
+        total,
+        title_percent,
+        episode_percent,
+        season_percent,
+        worst,
+    })
+}
+
+// 同一源文件夹中推断为同一季的文件
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeasonGroup {
+    pub folder: String,
+    pub season: u32,
+    // 推断依据：explicit（文件名中的季度标记）/ episode_reset（集数重新开始）/ default（按第1季处理）
+    pub signal: String,
+    pub files: Vec<String>,
+}
+
+// 推断同一标题下每个文件的季度和依据，files 需按文件名排序
+// 文件名带季度标记时直接使用；否则同一标题的集数出现重复时视为进入下一季
+pub(crate) fn infer_seasons(files: &[Option<&ParsedFilename>]) -> Vec<(u32, &'static str)> {
+    let mut inferred_season = 1;
+    let mut seen_episodes = std::collections::HashSet::new();
+    
+    files.iter()
+        .map(|parsed| {
+            let explicit_season = parsed.and_then(|p| p.season);
+            let episode = parsed.and_then(|p| p.episode_number);
+            
+            match (explicit_season, episode) {
+                (Some(season), _) => (season, "explicit"),
+                (None, Some(episode)) => {
+                    if !seen_episodes.insert(episode) {
+                        inferred_season += 1;
+                        seen_episodes.clear();
+                        seen_episodes.insert(episode);
+                    }
+                    if inferred_season > 1 {
+                        (inferred_season, "episode_reset")
+                    } else {
+                        (1, "default")
+                    }
+                }
+                (None, None) => (1, "default"),
+            }
+        })
+        .collect()
+}
+
+// 检测同一个源文件夹中混有多季的情况，只返回跨越多个季度的文件夹的分组，供界面提示拆分
+// 文件名带季度标记时直接使用；否则按文件名排序，同一标题的集数出现重复时视为进入下一季
+#[command]
+pub async fn detect_multi_season(folder: String) -> Result<Vec<SeasonGroup>, AppError> {
+    use rayon::prelude::*;
+    use std::collections::{BTreeMap, HashSet};
+    use crate::commands::file_operations::{scan_directory_internal, FileKind};
+    
+    if !std::path::Path::new(&folder).is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", folder)));
+    }
+    
+    let (files, _) = scan_directory_internal(&folder, &read_app_config());
+    let mut parsed_files: Vec<(String, String, Option<ParsedFilename>)> = files.into_par_iter()
+        .filter(|file| file.kind == FileKind::Video)
+        .map(|file| {
+            let parent = std::path::Path::new(&file.path).parent()
+                .map(|parent| parent.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let parsed = parse_path_internal(std::path::Path::new(&file.path)).ok();
+            (parent, file.path, parsed)
+        })
+        .collect();
+    parsed_files.sort_by(|a, b| a.1.cmp(&b.1));
+    
+    // 文件夹 -> 标题 -> 按文件名排序的 (路径, 解析结果)
+    type FilesByTitle = BTreeMap<String, Vec<(String, Option<ParsedFilename>)>>;
+    let mut by_folder: BTreeMap<String, FilesByTitle> = BTreeMap::new();
+    for (parent, path, parsed) in parsed_files {
+        let title = parsed.as_ref()
+            .map(|p| p.anime_title.trim().to_lowercase())
+            .unwrap_or_default();
+        by_folder.entry(parent).or_default().entry(title).or_default().push((path, parsed));
+    }
+    
+    let mut groups = Vec::new();
+    for (parent, titles) in by_folder {
+        // (季度, 依据) -> 文件
+        let mut seasons: BTreeMap<(u32, &str), Vec<String>> = BTreeMap::new();
+        
+        for (_, files) in titles {
+            let parsed: Vec<Option<&ParsedFilename>> = files.iter().map(|(_, parsed)| parsed.as_ref()).collect();
+            for ((path, _), key) in files.iter().zip(infer_seasons(&parsed)) {
+                seasons.entry(key).or_default().push(path.clone());
+            }
+        }
+        
+        let distinct: HashSet<u32> = seasons.keys().map(|(season, _)| *season).collect();
+        if distinct.len() < 2 {
+            continue;
+        }
+        
+        groups.extend(seasons.into_iter().map(|((season, signal), files)| SeasonGroup {
+            folder: parent.clone(),
+            season,
+            signal: signal.to_string(),
+            files,
+        }));
+    }
+    
+    Ok(groups)
+}
@@ -2,8 +2,46 @@ pub mod file_operations;
 pub mod metadata;
 pub mod config;
 pub mod logs;
+pub mod manifest;
+pub mod providers;
+pub mod library;
+pub mod nfo;
+pub mod template;
+pub mod auto_match;
+pub mod sidecars;
+pub mod devices;
+pub mod copy_mode;
+pub mod history;
+pub mod diagnostics;
+pub mod duplicates;
+pub mod transaction;
+pub mod plans;
+pub mod text_output;
+pub mod throughput;
+pub mod junk;
+pub mod tmdb;
+pub mod undo;
+pub mod checksum;
+pub mod watch;
+pub mod errors;
 
 pub use file_operations::*;
 pub use metadata::*;
 pub use config::*;
 pub use logs::*;
+pub use providers::*;
+pub use library::*;
+pub use nfo::*;
+pub use auto_match::*;
+pub use template::*;
+pub use sidecars::*;
+pub use copy_mode::*;
+pub use history::*;
+pub use diagnostics::*;
+pub use duplicates::*;
+pub use transaction::*;
+pub use plans::*;
+pub use junk::*;
+pub use tmdb::*;
+pub use undo::*;
+pub use watch::*;
@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::command;
+use tracing::info;
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::file_operations::generate_season_folder_name;
+use crate::commands::metadata::{AniListResponse, best_title};
+use crate::commands::text_output::write_text_file;
+use crate::commands::errors::AppError;
+
+// 媒体服务器（Kodi/Jellyfin）识别的剧集级NFO文件名
+pub const SERIES_NFO_FILE_NAME: &str = "tvshow.nfo";
+
+// 季度文件夹中的季度级NFO文件名
+pub const SEASON_NFO_FILE_NAME: &str = "season.nfo";
+
+// 转义XML特殊字符
+pub fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// AniList的简介包含 <br> 等HTML标签，写入NFO前转换为纯文本
+fn strip_html(text: &str) -> String {
+    let with_newlines = regex::Regex::new(r"(?i)<br\s*/?>")
+        .map(|re| re.replace_all(text, "\n").to_string())
+        .unwrap_or_else(|_| text.to_string());
+    regex::Regex::new(r"<[^>]+>")
+        .map(|re| re.replace_all(&with_newlines, "").to_string())
+        .unwrap_or(with_newlines)
+        .trim()
+        .to_string()
+}
+
+fn display_title(anime: &AniListResponse) -> String {
+    best_title(Some(anime), true, "").0
+}
+
+// 生成剧集级NFO的XML内容
+pub fn build_series_nfo(anime: &AniListResponse) -> String {
+    let title = display_title(anime);
+    
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#.to_string(),
+        "<tvshow>".to_string(),
+        format!("  <title>{}</title>", xml_escape(&title)),
+    ];
+    
+    if let Some(native) = &anime.title.native {
+        lines.push(format!("  <originaltitle>{}</originaltitle>", xml_escape(native)));
+    }
+    if let Some(year) = anime.season_year {
+        lines.push(format!("  <year>{}</year>", year));
+    }
+    if let Some(description) = &anime.description {
+        lines.push(format!("  <plot>{}</plot>", xml_escape(&strip_html(description))));
+    }
+    for genre in anime.genres.iter().flatten() {
+        lines.push(format!("  <genre>{}</genre>", xml_escape(genre)));
+    }
+    lines.push(format!(r#"  <uniqueid type="anilist" default="true">{}</uniqueid>"#, anime.id));
+    lines.push("</tvshow>".to_string());
+    
+    lines.join("\n") + "\n"
+}
+
+// 将剧集级NFO写入文件夹，已存在且未强制覆盖时跳过，返回是否写入
+pub fn write_series_nfo(anime: &AniListResponse, folder: &Path, force: bool, config: &AppConfig) -> Result<bool, String> {
+    let nfo_path = folder.join(SERIES_NFO_FILE_NAME);
+    
+    if nfo_path.exists() && !force {
+        info!("NFO已存在，跳过写入: {}", nfo_path.display());
+        return Ok(false);
+    }
+    
+    std::fs::create_dir_all(folder)
+        .map_err(|e| format!("创建文件夹失败: {}", e))?;
+    write_text_file(&nfo_path, &build_series_nfo(anime), config)
+        .map_err(|e| format!("写入NFO失败: {}", e))?;
+    
+    info!("NFO已写入: {}", nfo_path.display());
+    Ok(true)
+}
+
+// 为动漫文件夹生成 tvshow.nfo
+#[command]
+pub async fn generate_series_nfo(anime: AniListResponse, folder: String, force: Option<bool>) -> Result<bool, AppError> {
+    write_series_nfo(&anime, Path::new(&folder), force.unwrap_or(false), &read_app_config()).map_err(AppError::from)
+}
+
+// 生成季度级NFO的XML内容，多季动漫在 AniList 上每季是独立条目，anime 应为该季对应的条目
+pub fn build_season_nfo(anime: &AniListResponse, season: u32) -> String {
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#.to_string(),
+        "<season>".to_string(),
+        format!("  <title>{}</title>", xml_escape(&display_title(anime))),
+        format!("  <seasonnumber>{}</seasonnumber>", season),
+    ];
+    
+    if let Some(year) = anime.season_year {
+        lines.push(format!("  <year>{}</year>", year));
+    }
+    if let Some(description) = &anime.description {
+        lines.push(format!("  <plot>{}</plot>", xml_escape(&strip_html(description))));
+    }
+    lines.push(format!(r#"  <uniqueid type="anilist" default="true">{}</uniqueid>"#, anime.id));
+    lines.push("</season>".to_string());
+    
+    lines.join("\n") + "\n"
+}
+
+// 媒体服务器识别的季度海报文件名，位于动漫文件夹中：season02-poster.jpg，特别篇为 season-specials-poster.jpg
+pub fn season_poster_file_name(season: u32, extension: &str) -> String {
+    if season == 0 {
+        format!("season-specials-poster.{}", extension)
+    } else {
+        format!("season{:02}-poster.{}", season, extension)
+    }
+}
+
+// 季度资源的生成结果
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SeasonAssets {
+    pub nfo: Option<String>,
+    pub poster: Option<String>,
+    // 已存在而跳过的文件
+    pub skipped: Vec<String>,
+}
+
+// 为一季写入 season.nfo（放在季度文件夹中）和季度海报（放在动漫文件夹中），已存在且未强制覆盖时跳过
+pub async fn write_season_assets(
+    anime: &AniListResponse,
+    anime_folder: &Path,
+    season_folder: &Path,
+    season: u32,
+    force: bool,
+    config: &AppConfig
+) -> Result<SeasonAssets, String> {
+    let mut assets = SeasonAssets::default();
+    
+    let nfo_path = season_folder.join(SEASON_NFO_FILE_NAME);
+    if nfo_path.exists() && !force {
+        assets.skipped.push(nfo_path.to_string_lossy().to_string());
+    } else {
+        std::fs::create_dir_all(season_folder)
+            .map_err(|e| format!("创建季度文件夹失败: {}", e))?;
+        write_text_file(&nfo_path, &build_season_nfo(anime, season), config)
+            .map_err(|e| format!("写入季度NFO失败: {}", e))?;
+        info!("季度NFO已写入: {}", nfo_path.display());
+        assets.nfo = Some(nfo_path.to_string_lossy().to_string());
+    }
+    
+    let cover_url = anime.cover_image.as_ref()
+        .and_then(|cover| cover.large.as_deref().or(cover.medium.as_deref()));
+    if let Some(url) = cover_url {
+        let extension = Path::new(url.split('?').next().unwrap_or(url))
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .filter(|ext| ["jpg", "jpeg", "png", "webp"].contains(&ext.as_str()))
+            .unwrap_or_else(|| "jpg".to_string());
+        let poster_path = anime_folder.join(season_poster_file_name(season, &extension));
+        
+        if poster_path.exists() && !force {
+            assets.skipped.push(poster_path.to_string_lossy().to_string());
+        } else {
+            let bytes = reqwest::get(url).await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| format!("下载季度海报失败: {}", e))?
+                .bytes().await
+                .map_err(|e| format!("下载季度海报失败: {}", e))?;
+            std::fs::write(&poster_path, &bytes)
+                .map_err(|e| format!("写入季度海报失败: {}", e))?;
+            info!("季度海报已写入: {}", poster_path.display());
+            assets.poster = Some(poster_path.to_string_lossy().to_string());
+        }
+    }
+    
+    Ok(assets)
+}
+
+// 为动漫文件夹中的某一季生成 season.nfo 和季度海报，季度文件夹名按当前配置的模板生成
+#[command]
+pub async fn generate_season_assets(
+    folder: String,
+    anime: AniListResponse,
+    season: u32,
+    force: Option<bool>
+) -> Result<SeasonAssets, AppError> {
+    let config = read_app_config();
+    let anime_folder = Path::new(&folder);
+    if !anime_folder.is_dir() {
+        return Err(AppError::NotFound(format!("动漫文件夹不存在: {}", folder)));
+    }
+    
+    let template = if season == 0 { &config.specials_folder_template } else { &config.season_folder_template };
+    let season_folder = anime_folder.join(generate_season_folder_name(template, season, &config.template_separators));
+    write_season_assets(&anime, anime_folder, &season_folder, season, force.unwrap_or(false), &config).await.map_err(AppError::from)
+}
@@ -0,0 +1,259 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tauri::{command, AppHandle, State};
+use tracing::{info, warn};
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::devices::process_grouped_by_device;
+use crate::commands::duplicates::{apply_duplicate_resolution, check_existing_in_output};
+use crate::commands::history::record_operation;
+use crate::commands::file_operations::{
+    FileError, ProcessResult, sanitize_path, ensure_output_dir, preview_target, invalid_rename_targets,
+    check_extension_changes, link_sidecars_for_batch, write_batch_manifest,
+};
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_EXECUTE_PLAN};
+use crate::commands::manifest::build_manifest_entry;
+use crate::commands::metadata::AniListResponse;
+use crate::commands::throughput::ThroughputTracker;
+use crate::commands::undo::BatchJournal;
+use crate::commands::errors::AppError;
+
+// 计划中的单个文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannedLink {
+    pub source: String,
+    pub target: String,
+    // 生成计划时源文件的大小和修改时间，执行前据此判断源文件是否变化
+    pub size: u64,
+    pub modified: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPlan {
+    pub token: String,
+    pub output_dir: String,
+    pub links: Vec<PlannedLink>,
+    // 不会处理的文件及原因（非法的重命名目标、重复集数等）
+    pub excluded: Vec<FileError>,
+    // 会处理但需要注意的问题（目标已存在、扩展名改变等）
+    pub warnings: Vec<FileError>,
+}
+
+// 等待确认的计划，保存生成计划时的配置快照，执行时不再读取配置
+pub struct PendingPlan {
+    plan: BatchPlan,
+    config: AppConfig,
+}
+
+pub type PlanStore = Arc<Mutex<HashMap<String, PendingPlan>>>;
+
+pub fn create_plan_store() -> PlanStore {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+// 两阶段处理的第一步：只计算目标路径和问题，不做任何改动，返回计划和用于执行的令牌
+// 界面展示计划并经用户确认后，用 execute_plan 执行完全相同的计划
+#[command]
+pub async fn plan_batch(
+    files: Vec<String>,
+    output_dir: String,
+    mut rename_map: HashMap<String, String>,
+    anime_metadata: Option<AniListResponse>,
+    plan_store: State<'_, PlanStore>
+) -> Result<BatchPlan, AppError> {
+    let config = read_app_config();
+    let sanitized_output_dir = sanitize_path(&PathBuf::from(&output_dir));
+    
+    let mut excluded = invalid_rename_targets(&files, &rename_map);
+    let excluded_paths: HashSet<String> = excluded.iter().map(|issue| issue.path.clone()).collect();
+    let valid_files: Vec<String> = files.iter()
+        .filter(|file_path| !excluded_paths.contains(*file_path))
+        .cloned()
+        .collect();
+    let (extension_rejected, mut warnings) = check_extension_changes(&valid_files, &rename_map, &config);
+    let rejected_paths: HashSet<String> = extension_rejected.iter().map(|issue| issue.path.clone()).collect();
+    excluded.extend(extension_rejected);
+    let valid_files: Vec<String> = valid_files.into_iter()
+        .filter(|file_path| !rejected_paths.contains(file_path))
+        .collect();
+    
+    let (_, duplicate_dropped) = apply_duplicate_resolution(&valid_files, &mut rename_map, &config);
+    let mut dropped_paths: HashSet<String> = duplicate_dropped.iter().map(|issue| issue.path.clone()).collect();
+    excluded.extend(duplicate_dropped);
+    
+    let (existing_skipped, existing_warnings) = check_existing_in_output(&valid_files, &sanitized_output_dir, &config);
+    dropped_paths.extend(existing_skipped.iter().map(|issue| issue.path.clone()));
+    excluded.extend(existing_skipped);
+    warnings.extend(existing_warnings);
+    
+    let mut links = Vec::new();
+    let mut planned_targets = HashSet::new();
+    for file_path in valid_files.into_iter().filter(|file_path| !dropped_paths.contains(file_path)) {
+        let metadata = match fs::metadata(&file_path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            _ => {
+                excluded.push(FileError {
+                    path: file_path,
+                    error: "源文件不存在".to_string(),
+                });
+                continue;
+            }
+        };
+        
+        let target = preview_target(&file_path, &sanitized_output_dir, &rename_map, anime_metadata.as_ref(), &config);
+        if !planned_targets.insert(target.clone()) {
+            excluded.push(FileError {
+                path: file_path,
+                error: format!("与计划中的其他文件目标相同: {}", target.display()),
+            });
+            continue;
+        }
+        if target.exists() {
+            warnings.push(FileError {
+                path: file_path.clone(),
+                error: format!("目标已存在，执行时将失败: {}", target.display()),
+            });
+        }
+        
+        links.push(PlannedLink {
+            source: file_path,
+            target: target.to_string_lossy().to_string(),
+            size: metadata.len(),
+            modified: modified_secs(&metadata),
+        });
+    }
+    
+    let plan = BatchPlan {
+        token: uuid::Uuid::new_v4().to_string(),
+        output_dir: sanitized_output_dir.to_string_lossy().to_string(),
+        links,
+        excluded,
+        warnings,
+    };
+    info!("已生成处理计划 {}: {} 个文件", plan.token, plan.links.len());
+    
+    plan_store.lock().unwrap().insert(plan.token.clone(), PendingPlan {
+        plan: plan.clone(),
+        config,
+    });
+    
+    Ok(plan)
+}
+
+// 两阶段处理的第二步：执行令牌对应的计划，每个计划只能执行一次
+// 任何源文件在生成计划后被删除或大小、修改时间发生变化时，整个计划作废，需要重新生成
+#[command]
+pub async fn execute_plan(
+    token: String,
+    plan_store: State<'_, PlanStore>,
+    app: AppHandle,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_EXECUTE_PLAN);
+    let PendingPlan { plan, config } = plan_store.lock().unwrap()
+        .remove(&token)
+        .ok_or("计划不存在或已执行，请重新生成计划")?;
+    
+    for link in &plan.links {
+        let unchanged = fs::metadata(&link.source)
+            .map(|metadata| metadata.len() == link.size && modified_secs(&metadata) == link.modified)
+            .unwrap_or(false);
+        if !unchanged {
+            log.warn(format!("源文件在生成计划后发生变化，计划已作废: {}", link.source));
+            return Err(format!("源文件在生成计划后发生变化，请重新生成计划: {}", link.source).into());
+        }
+    }
+    
+    info!("开始执行处理计划 {}: {} 个文件", token, plan.links.len());
+    log.info(format!("开始执行处理计划: {} 个文件", plan.links.len()));
+    
+    let output_dir = ensure_output_dir(Path::new(&plan.output_dir))
+        .map_err(|e| e.to_string())?;
+    
+    let targets: HashMap<String, String> = plan.links.iter()
+        .map(|link| (link.source.clone(), link.target.clone()))
+        .collect();
+    let sources: Vec<String> = plan.links.iter().map(|link| link.source.clone()).collect();
+    let input_files: HashSet<String> = sources.iter().cloned().collect();
+    
+    let processed_files = Mutex::new(Vec::new());
+    let failed_files = Mutex::new(Vec::new());
+    let manifest_entries = Mutex::new(Vec::new());
+    let linked_sidecars = Mutex::new(Vec::new());
+    let journal = BatchJournal::new();
+    
+    let tracker = ThroughputTracker::new(app, SOURCE_EXECUTE_PLAN, &sources);
+    process_grouped_by_device(&sources, &config, |file_path| {
+        let source = PathBuf::from(file_path);
+        let mut pending = tracker.begin(&source);
+        let target = PathBuf::from(&targets[file_path]);
+        
+        match journal.link(&source, &target, &config) {
+            Ok(outcome) => {
+                pending.succeeded(outcome.action);
+                linked_sidecars.lock().unwrap().extend(link_sidecars_for_batch(&source, &outcome, &input_files, &config));
+                if config.write_manifest {
+                    manifest_entries.lock().unwrap().push(build_manifest_entry(&source, &outcome.target, &output_dir, outcome.action.as_str()));
+                }
+                processed_files.lock().unwrap().push(file_path.clone());
+            }
+            Err(e) => {
+                warn!("执行计划时链接失败: {}, 错误: {}", file_path, e);
+                failed_files.lock().unwrap().push(FileError {
+                    path: file_path.clone(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    });
+    tracker.finish();
+    
+    let processed = processed_files.into_inner().unwrap();
+    let failed = failed_files.into_inner().unwrap();
+    let linked_sidecars = linked_sidecars.into_inner().unwrap();
+    
+    if config.write_manifest {
+        let mut entries = manifest_entries.into_inner().unwrap();
+        entries.extend(linked_sidecars.iter().map(|link| {
+            build_manifest_entry(Path::new(&link.source), Path::new(&link.target), &output_dir, &link.action)
+        }));
+        write_batch_manifest(&log, &output_dir, entries, &config);
+    }
+    
+    for failed_file in &failed {
+        log.error(format!("处理失败: {} - {}", failed_file.path, failed_file.error));
+    }
+    log.info(format!("处理计划执行完成: 成功 {}, 失败 {}", processed.len(), failed.len()));
+    
+    let result = ProcessResult {
+        success: failed.is_empty(),
+        message: format!("计划执行完成: 成功 {}/{}, 失败 {}", processed.len(), plan.links.len(), failed.len()),
+        processed_files: processed,
+        failed_files: failed,
+        skipped_files: plan.excluded,
+        linked_sidecars,
+        warnings: plan.warnings,
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    journal.save(SOURCE_EXECUTE_PLAN, &result.linked_sidecars, &log);
+    record_operation(SOURCE_EXECUTE_PLAN, &plan.output_dir, &result, &config);
+    
+    Ok(result)
+}
+
+// 丢弃未执行的计划
+#[command]
+pub async fn discard_plan(token: String, plan_store: State<'_, PlanStore>) -> Result<bool, AppError> {
+    Ok(plan_store.lock().unwrap().remove(&token).is_some())
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> Option<u64> {
+    metadata.modified().ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+}
@@ -0,0 +1,397 @@
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
+use tracing::{info, warn};
+use std::collections::{HashMap, HashSet};
+use crate::commands::config::{read_app_config, write_config_file};
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_SEARCH_ALL};
+use crate::commands::metadata::{
+    AniListCache, AniListError, AniListErrorCode, AniListResponse, TitleField, best_title, normalize_query, search_anilist_cached, search_anilist_batch_cached,
+    strip_release_noise,
+};
+use crate::commands::tmdb::{TmdbResult, TMDB_POSTER_BASE, search_tmdb};
+use crate::commands::errors::AppError;
+
+// 多个数据源统一后的匹配结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnifiedMatch {
+    pub source: String,
+    pub id: String,
+    pub title: String,
+    pub original_title: Option<String>,
+    pub year: Option<u32>,
+    pub format: Option<String>,
+    pub episodes: Option<u32>,
+    pub cover_url: Option<String>,
+    pub confidence: f32,
+    // AniList 结果的标题实际取自哪个字段
+    #[serde(default)]
+    pub title_field: Option<TitleField>,
+}
+
+// 已知的元数据数据源名称，也是默认的查询优先顺序
+pub const KNOWN_PROVIDERS: [&str; 2] = ["anilist", "tmdb"];
+
+// 判断为同一作品的标题相似度阈值
+const DUPLICATE_SIMILARITY: f32 = 0.9;
+
+// 并行查询所有启用的数据源，合并去重后按置信度排序
+#[command]
+pub async fn search_all(
+    query: String,
+    anilist_cache: State<'_, AniListCache>,
+    log_store: State<'_, LogStore>
+) -> Result<Vec<UnifiedMatch>, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_SEARCH_ALL);
+    let config = read_app_config();
+    let mut tasks = Vec::new();
+    
+    for provider in provider_priority(&config.provider_order) {
+        match provider {
+            "anilist" if config.anilist_enabled => {
+                let anilist_query = query.clone();
+                let prefer_romaji = config.use_romaji_names;
+                let cache = anilist_cache.inner().clone();
+                tasks.push(("AniList", tokio::spawn(async move {
+                    search_anilist_cached(&cache, &anilist_query).await
+                        .map(|results| {
+                            results.iter()
+                                .map(|anime| anilist_to_unified(anime, &anilist_query, prefer_romaji))
+                                .collect::<Vec<_>>()
+                        })
+                        .map_err(String::from)
+                })));
+            }
+            "tmdb" if config.tmdb_enabled => {
+                let tmdb_query = query.clone();
+                tasks.push(("TMDB", tokio::spawn(async move {
+                    search_tmdb(tmdb_query.clone()).await
+                        .map(|results| {
+                            results.iter()
+                                .map(|result| tmdb_to_unified(result, &tmdb_query))
+                                .collect::<Vec<_>>()
+                        })
+                        .map_err(String::from)
+                })));
+            }
+            _ => {}
+        }
+    }
+    
+    if tasks.is_empty() {
+        return Err("没有启用任何元数据数据源".into());
+    }
+    
+    let mut matches = Vec::new();
+    let mut errors = Vec::new();
+    for (source, task) in tasks {
+        match task.await {
+            Ok(Ok(results)) => matches.extend(results),
+            Ok(Err(e)) => {
+                warn!("{} 查询失败: {}", source, e);
+                errors.push(format!("{}: {}", source, e));
+            }
+            Err(e) => {
+                warn!("{} 查询任务异常: {}", source, e);
+                errors.push(format!("{}: {}", source, e));
+            }
+        }
+    }
+    
+    // 所有数据源都失败时才返回错误
+    if matches.is_empty() && !errors.is_empty() {
+        log.error(format!("统一搜索失败: {}", errors.join("; ")));
+        return Err(format!("所有数据源查询失败: {}", errors.join("; ")).into());
+    }
+    
+    // 先按数据源优先顺序去重，重复条目保留优先数据源的结果，再按置信度排序（相同时优先数据源在前）
+    let priority = provider_priority(&config.provider_order);
+    let rank = |source: &str| priority.iter().position(|provider| *provider == source).unwrap_or(priority.len());
+    matches.sort_by(|a, b| rank(&a.source).cmp(&rank(&b.source)).then_with(|| b.confidence.total_cmp(&a.confidence)));
+    let mut unified = dedupe_matches(matches);
+    unified.sort_by(|a, b| b.confidence.total_cmp(&a.confidence).then_with(|| rank(&a.source).cmp(&rank(&b.source))));
+    
+    info!("统一搜索完成: {} -> {} 个结果", query, unified.len());
+    log.info(format!("统一搜索 \"{}\" 得到 {} 个结果", query, unified.len()));
+    
+    Ok(unified)
+}
+
+// 带匹配度的 AniList 搜索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoredAniListResponse {
+    pub anime: AniListResponse,
+    // 去掉发布信息后的搜索词与各标题相似度的最大值（0.0 - 1.0）
+    pub score: f32,
+    // 得分最高的标题字段
+    pub matched_field: Option<TitleField>,
+}
+
+// 按匹配度排序的 AniList 搜索：搜索词先去掉字幕组、分辨率等发布信息，
+// 再与每个候选的罗马音、英文和原文标题比较，返回的分数供界面自动选择高匹配度结果、提示低匹配度结果
+#[command]
+pub async fn search_anilist_scored(query: String, anilist_cache: State<'_, AniListCache>) -> Result<Vec<ScoredAniListResponse>, AniListError> {
+    let cleaned = strip_release_noise(&query);
+    let results = search_anilist_cached(&anilist_cache, &cleaned).await?;
+    
+    let mut scored: Vec<ScoredAniListResponse> = results.into_iter()
+        .map(|anime| {
+            let (score, matched_field) = [
+                (TitleField::Romaji, &anime.title.romaji),
+                (TitleField::English, &anime.title.english),
+                (TitleField::Native, &anime.title.native),
+            ]
+                .into_iter()
+                .filter_map(|(field, title)| Some((title_similarity(&cleaned, title.as_deref()?), field)))
+                .fold((0.0, None), |best, (score, field)| if score > best.0 { (score, Some(field)) } else { best });
+            ScoredAniListResponse { anime, score, matched_field }
+        })
+        .collect();
+    // 分数相同时保持 AniList 的原始顺序
+    scored.sort_by(|a, b| b.score.total_cmp(&a.score));
+    
+    Ok(scored)
+}
+
+// 批量匹配中每个标题的结果：最佳候选及其余候选
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimeBatchMatch {
+    pub anime: AniListResponse,
+    pub best: UnifiedMatch,
+    // 按置信度从高到低，最多 MAX_BATCH_ALTERNATIVES 个
+    pub alternatives: Vec<UnifiedMatch>,
+}
+
+// 批量匹配时每个标题最多返回的其他候选数
+const MAX_BATCH_ALTERNATIVES: usize = 4;
+
+// 一次匹配多个解析出的标题：去重后合并为尽量少的 AniList 请求（遵循缓存和请求并发限制）
+// 没有结果或查询失败的标题对应 None，只有全部查询都失败时才返回错误
+#[command]
+pub async fn match_anime_batch(
+    titles: Vec<String>,
+    anilist_cache: State<'_, AniListCache>
+) -> Result<HashMap<String, Option<AnimeBatchMatch>>, AniListError> {
+    let prefer_romaji = read_app_config().use_romaji_names;
+    let searches = search_anilist_batch_cached(&anilist_cache, &titles).await;
+    
+    if !searches.is_empty() && searches.values().all(|found| found.is_err()) {
+        let error = searches.into_values()
+            .filter_map(Result::err)
+            .max_by_key(|e| e.code == AniListErrorCode::RateLimited);
+        return Err(error.unwrap_or_else(|| "AniList 查询失败".into()));
+    }
+    
+    let mut matches = HashMap::new();
+    for title in titles {
+        let found = match searches.get(&normalize_query(&title)) {
+            Some(Ok(candidates)) => {
+                let mut ranked = rank_anilist_candidates(candidates.clone(), &title, prefer_romaji).into_iter();
+                ranked.next().map(|(anime, best)| AnimeBatchMatch {
+                    anime,
+                    best,
+                    alternatives: ranked.take(MAX_BATCH_ALTERNATIVES).map(|(_, unified)| unified).collect(),
+                })
+            }
+            Some(Err(e)) => {
+                warn!("批量匹配 \"{}\" 失败，按无结果处理: {}", title, e);
+                None
+            }
+            None => None,
+        };
+        matches.insert(title, found);
+    }
+    
+    info!("批量匹配完成: {} 个标题，{} 个有结果", matches.len(), matches.values().filter(|found| found.is_some()).count());
+    Ok(matches)
+}
+
+// AniList 候选按与标题的置信度从高到低排序
+pub(crate) fn rank_anilist_candidates(candidates: Vec<AniListResponse>, title: &str, prefer_romaji: bool) -> Vec<(AniListResponse, UnifiedMatch)> {
+    let mut ranked: Vec<(AniListResponse, UnifiedMatch)> = candidates.into_iter()
+        .map(|anime| {
+            let unified = anilist_to_unified(&anime, title, prefer_romaji);
+            (anime, unified)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.confidence.total_cmp(&a.1.confidence));
+    ranked
+}
+
+// 数据源查询顺序：配置中列出的在前，未列出的已知数据源按默认顺序排在后面
+fn provider_priority(order: &[String]) -> Vec<&'static str> {
+    let mut priority: Vec<&'static str> = order.iter()
+        .filter_map(|name| KNOWN_PROVIDERS.iter().find(|known| known.eq_ignore_ascii_case(name.trim())).copied())
+        .collect();
+    for provider in KNOWN_PROVIDERS {
+        if !priority.contains(&provider) {
+            priority.push(provider);
+        }
+    }
+    priority
+}
+
+// 检查数据源顺序：只能包含已知数据源，且不能重复
+pub(crate) fn check_provider_order(order: &[String]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for name in order {
+        let normalized = name.trim().to_lowercase();
+        if !KNOWN_PROVIDERS.contains(&normalized.as_str()) {
+            return Err(format!("未知的数据源: {}，可选值: {}", name, KNOWN_PROVIDERS.join(", ")));
+        }
+        if !seen.insert(normalized) {
+            return Err(format!("数据源重复: {}", name));
+        }
+    }
+    Ok(())
+}
+
+// 当前的数据源查询顺序（包含未在配置中列出的数据源）
+#[command]
+pub async fn get_provider_order() -> Result<Vec<String>, AppError> {
+    Ok(provider_priority(&read_app_config().provider_order).into_iter().map(|provider| provider.to_string()).collect())
+}
+
+// 设置数据源查询顺序并立即保存到配置
+#[command]
+pub async fn set_provider_order(order: Vec<String>) -> Result<Vec<String>, AppError> {
+    check_provider_order(&order)?;
+    
+    let mut config = read_app_config();
+    config.provider_order = order.iter().map(|name| name.trim().to_lowercase()).collect();
+    write_config_file(&config)?;
+    
+    Ok(provider_priority(&config.provider_order).into_iter().map(|provider| provider.to_string()).collect())
+}
+
+pub(crate) fn anilist_to_unified(anime: &AniListResponse, query: &str, prefer_romaji: bool) -> UnifiedMatch {
+    let (title, title_field) = best_title(Some(anime), prefer_romaji, "");
+    
+    let confidence = [&anime.title.romaji, &anime.title.english, &anime.title.native]
+        .iter()
+        .filter_map(|candidate| candidate.as_deref())
+        .map(|candidate| title_similarity(query, candidate))
+        .fold(0.0, f32::max);
+    
+    UnifiedMatch {
+        source: "anilist".to_string(),
+        id: anime.id.to_string(),
+        title,
+        original_title: anime.title.native.clone(),
+        year: anime.season_year,
+        format: anime.format.clone(),
+        episodes: anime.episodes,
+        cover_url: anime.cover_image.as_ref().and_then(|cover| cover.large.clone()),
+        confidence,
+        title_field: Some(title_field),
+    }
+}
+
+pub(crate) fn tmdb_to_unified(result: &TmdbResult, query: &str) -> UnifiedMatch {
+    let confidence = [Some(&result.title), result.original_title.as_ref()]
+        .iter()
+        .flatten()
+        .map(|candidate| title_similarity(query, candidate))
+        .fold(0.0, f32::max);
+    
+    UnifiedMatch {
+        source: "tmdb".to_string(),
+        id: format!("{}/{}", result.media_type, result.id),
+        title: result.title.clone(),
+        original_title: result.original_title.clone(),
+        year: result.year,
+        format: Some(result.media_type.to_uppercase()),
+        episodes: None,
+        cover_url: result.poster_path.as_ref().map(|path| format!("{}{}", TMDB_POSTER_BASE, path)),
+        confidence,
+        title_field: None,
+    }
+}
+
+// 去除可能重复的条目（标题高度相似且年份一致），输入需已按置信度降序排列
+fn dedupe_matches(matches: Vec<UnifiedMatch>) -> Vec<UnifiedMatch> {
+    let mut unique: Vec<UnifiedMatch> = Vec::new();
+    
+    for candidate in matches {
+        let is_duplicate = unique.iter().any(|existing| {
+            let same_year = match (existing.year, candidate.year) {
+                (Some(a), Some(b)) => a == b,
+                _ => true,
+            };
+            same_year && title_similarity(&existing.title, &candidate.title) >= DUPLICATE_SIMILARITY
+        });
+        
+        if !is_duplicate {
+            unique.push(candidate);
+        }
+    }
+    
+    unique
+}
+
+// 标题归一化：小写并将标点替换为空格
+fn normalize_title(title: &str) -> String {
+    title.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 计算两个标题的相似度（0.0 - 1.0），取词集合重合度、字符二元组相似度和归一化编辑距离相似度中的最大值
+pub fn title_similarity(a: &str, b: &str) -> f32 {
+    let a = normalize_title(a);
+    let b = normalize_title(b);
+    
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    if a == b {
+        return 1.0;
+    }
+    
+    let tokens_a: HashSet<&str> = a.split(' ').collect();
+    let tokens_b: HashSet<&str> = b.split(' ').collect();
+    let token_score = tokens_a.intersection(&tokens_b).count() as f32
+        / tokens_a.union(&tokens_b).count() as f32;
+    
+    // 字符二元组对没有空格分词的日文、中文标题更有效
+    let bigrams = |s: &str| -> HashSet<(char, char)> {
+        let chars: Vec<char> = s.chars().filter(|c| *c != ' ').collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let bigrams_a = bigrams(&a);
+    let bigrams_b = bigrams(&b);
+    let bigram_score = if bigrams_a.is_empty() || bigrams_b.is_empty() {
+        0.0
+    } else {
+        2.0 * bigrams_a.intersection(&bigrams_b).count() as f32
+            / (bigrams_a.len() + bigrams_b.len()) as f32
+    };
+    
+    token_score.max(bigram_score).max(levenshtein_similarity(&a, &b))
+}
+
+// 1 - 编辑距离 / 较长标题的字符数，用于容忍拼写差异（例如 Kyoujin 与 Kyojin）
+fn levenshtein_similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, char_a) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, char_b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(char_a != char_b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    
+    1.0 - previous[b.len()] as f32 / longest as f32
+}
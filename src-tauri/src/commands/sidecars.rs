@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, State};
+use tracing::{info, warn};
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::file_operations::{FileError, create_link_with_config, is_subtitle_extension, place_subtitle_target};
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_LINK_WITH_SIDECARS};
+use crate::commands::errors::AppError;
+
+// 随视频一起链接的附属文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SidecarLink {
+    pub source: String,
+    pub target: String,
+    pub action: String,
+    // 字幕文件识别出的语言（规范名称）
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SidecarOptions {
+    // 覆盖配置中的附属文件扩展名
+    pub extensions: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SidecarReport {
+    pub video_target: String,
+    pub linked_sidecars: Vec<SidecarLink>,
+    pub failed_sidecars: Vec<FileError>,
+}
+
+// 查找与视频同名的附属文件，返回文件路径和去掉视频文件名主干后的后缀
+// 例如 "Ep01.mkv" 对应 "Ep01.chs.ass" -> ".chs.ass"、"Ep01-thumb.jpg" -> "-thumb.jpg"
+pub fn find_sidecars(video: &Path, extensions: &[String]) -> Vec<(PathBuf, String)> {
+    let (Some(parent), Some(stem)) = (video.parent(), video.file_stem()) else {
+        return Vec::new();
+    };
+    let stem = stem.to_string_lossy().to_string();
+    
+    let entries = match fs::read_dir(parent) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("读取附属文件目录失败: {}, 错误: {}", parent.display(), e);
+            return Vec::new();
+        }
+    };
+    
+    let mut sidecars: Vec<(PathBuf, String)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path != video)
+        .filter(|path| {
+            path.extension()
+                .map(|ext| ext.to_string_lossy().to_string())
+                .is_some_and(|ext| extensions.iter().any(|allowed| allowed.trim_start_matches('.').eq_ignore_ascii_case(&ext)))
+        })
+        .filter_map(|path| {
+            let name = path.file_name()?.to_string_lossy().to_string();
+            let suffix = name.strip_prefix(&stem)?;
+            if suffix.starts_with('.') || suffix.starts_with('-') {
+                let suffix = suffix.to_string();
+                Some((path, suffix))
+            } else {
+                None
+            }
+        })
+        .collect();
+    
+    sidecars.sort();
+    sidecars
+}
+
+// 从字幕后缀中识别语言，例如 ".sc.ass"、".[CHS].ass"、".zh-Hans.ass"，返回配置中的规范名称
+pub(crate) fn detect_subtitle_language(suffix: &str, config: &AppConfig) -> Option<String> {
+    let without_extension = suffix.rsplit_once('.').map(|(rest, _)| rest).unwrap_or_default();
+    
+    without_extension
+        .split(['.', '[', ']', '(', ')', ' ', '_'])
+        .map(|token| token.trim_start_matches('-').to_lowercase())
+        .filter(|token| !token.is_empty())
+        .find_map(|token| {
+            config.subtitle_language_aliases.iter()
+                .find(|(alias, _)| alias.to_lowercase() == token)
+                .map(|(_, language)| language.clone())
+        })
+}
+
+// 以视频的新文件名主干链接其附属文件，exclude 中的源文件（例如已单独处理的字幕）不会重复链接
+pub(crate) fn link_sidecars(
+    video: &Path,
+    video_target: &Path,
+    extensions: &[String],
+    exclude: &HashSet<String>,
+    config: &AppConfig
+) -> (Vec<SidecarLink>, Vec<FileError>) {
+    let mut linked = Vec::new();
+    let mut failed = Vec::new();
+    
+    let (Some(target_dir), Some(target_stem)) = (video_target.parent(), video_target.file_stem()) else {
+        return (linked, failed);
+    };
+    let target_stem = target_stem.to_string_lossy();
+    // 已使用的字幕语言和扩展名，同一语言出现多次时（例如 .sc.ass 和 .chs.ass）后者保留原后缀
+    let mut used_languages = HashSet::new();
+    
+    for (sidecar, suffix) in find_sidecars(video, extensions) {
+        let sidecar_path = sidecar.to_string_lossy().to_string();
+        if exclude.contains(&sidecar_path) {
+            continue;
+        }
+        
+        // 多语言字幕按识别出的语言统一命名为 "<视频名>.<语言>.<扩展名>"
+        let extension = sidecar.extension()
+            .map(|ext| ext.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let language = if is_subtitle_extension(&extension, config) {
+            detect_subtitle_language(&suffix, config)
+        } else {
+            None
+        };
+        let sidecar_name = match &language {
+            Some(language) if used_languages.insert((language.clone(), extension.to_lowercase())) => format!("{}.{}.{}", target_stem, language, extension),
+            _ => format!("{}{}", target_stem, suffix),
+        };
+        
+        let sidecar_target = place_subtitle_target(target_dir.join(sidecar_name), config);
+        match create_link_with_config(&sidecar, &sidecar_target, config) {
+            Ok(outcome) => {
+                info!("附属文件链接成功: {} -> {}", sidecar_path, outcome.target.display());
+                linked.push(SidecarLink {
+                    source: sidecar_path,
+                    target: outcome.target.to_string_lossy().to_string(),
+                    action: outcome.action.as_str().to_string(),
+                    language,
+                });
+            }
+            Err(e) => {
+                warn!("附属文件链接失败: {}, 错误: {}", sidecar_path, e);
+                failed.push(FileError {
+                    path: sidecar_path,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    
+    (linked, failed)
+}
+
+// 链接视频及其附属文件
+#[command]
+pub async fn link_with_sidecars(
+    video: String,
+    target: String,
+    options: Option<SidecarOptions>,
+    log_store: State<'_, LogStore>
+) -> Result<SidecarReport, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_LINK_WITH_SIDECARS);
+    let config = read_app_config();
+    let extensions = options
+        .and_then(|options| options.extensions)
+        .unwrap_or_else(|| config.sidecar_extensions.clone());
+    
+    let video_path = PathBuf::from(&video);
+    let outcome = create_link_with_config(&video_path, &PathBuf::from(&target), &config)
+        .map_err(|e| {
+            log.error(format!("视频链接失败: {} - {}", video, e));
+            e.to_string()
+        })?;
+    
+    let (linked_sidecars, failed_sidecars) = link_sidecars(&video_path, &outcome.target, &extensions, &HashSet::new(), &config);
+    
+    log.info(format!("视频链接成功: {}，附属文件 {} 个，失败 {} 个", video, linked_sidecars.len(), failed_sidecars.len()));
+    for failed_sidecar in &failed_sidecars {
+        log.warn(format!("附属文件链接失败: {} - {}", failed_sidecar.path, failed_sidecar.error));
+    }
+    
+    Ok(SidecarReport {
+        video_target: outcome.target.to_string_lossy().to_string(),
+        linked_sidecars,
+        failed_sidecars,
+    })
+}
@@ -0,0 +1,430 @@
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::command;
+use crate::commands::config::{AppConfig, read_app_config, write_config_file};
+use crate::commands::metadata::{AniListResponse, ParsedFilename, TitleField, best_title, parse_filename_internal, get_anilist_episode_titles};
+use crate::commands::errors::AppError;
+
+// 命名模板支持的全部字段
+// {episode} 是本季内的集数，{episode_abs} 是加上 episode_offsets 中该季偏移后的绝对集数，没有配置偏移时两者相同
+pub const TEMPLATE_TOKENS: [&str; 15] = [
+    "{title}",
+    "{title_clean}",
+    "{title_romaji}",
+    "{title_english}",
+    "{season}",
+    "{season:02}",
+    "{episode}",
+    "{episode:02}",
+    "{episode:03}",
+    "{episode_abs}",
+    "{episode_title}",
+    "{year}",
+    "{group}",
+    "{resolution}",
+    "{ext}",
+];
+
+lazy_static! {
+    // 带补零宽度的数字字段，例如 {episode:04}、{season:02}
+    static ref PADDED_NUMBER_TOKEN: Regex = Regex::new(r"\{(episode_abs|episode|season):0(\d{1,2})\}").unwrap();
+}
+
+// 命名模板可用的字段，缺失的字段渲染为空字符串，再由 normalize_rendered_name 清理
+#[derive(Debug, Default, Clone)]
+pub struct TemplateContext {
+    pub title: String,
+    pub title_clean: Option<String>,
+    pub title_romaji: Option<String>,
+    pub title_english: Option<String>,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+    pub episode_abs: Option<u32>,
+    pub episode_title: Option<String>,
+    pub year: Option<u32>,
+    pub group: Option<String>,
+    pub resolution: Option<String>,
+    pub ext: Option<String>,
+}
+
+impl TemplateContext {
+    // 由文件名解析结果和可选的AniList元数据构建上下文，季度缺失时按第1季处理
+    pub fn from_parsed(parsed: Option<&ParsedFilename>, metadata: Option<&AniListResponse>, prefer_romaji: bool) -> Self {
+        let parsed_title = parsed.map(|p| p.anime_title.as_str()).unwrap_or_default();
+        let (title, _) = best_title(metadata, prefer_romaji, parsed_title);
+        
+        Self {
+            title,
+            title_clean: parsed.map(|p| p.title_clean.clone()).filter(|clean| !clean.is_empty()),
+            title_romaji: metadata.and_then(|anime| anime.title.get(TitleField::Romaji)).map(str::to_string),
+            title_english: metadata.and_then(|anime| anime.title.get(TitleField::English)).map(str::to_string),
+            season: Some(parsed.and_then(|p| p.season).unwrap_or(1)),
+            episode: parsed.and_then(|p| p.episode_number),
+            episode_abs: parsed.and_then(|p| p.episode_number),
+            episode_title: None,
+            year: metadata.and_then(|anime| anime.season_year),
+            group: parsed.and_then(|p| p.group.clone()),
+            resolution: parsed.and_then(|p| p.resolution.clone()),
+            ext: None,
+        }
+    }
+    
+    // 按配置的 episode_offsets 计算绝对集数，依次用上下文标题和文件名解析出的标题查找系列
+    pub fn apply_episode_offsets(&mut self, config: &AppConfig, parsed_title: &str) {
+        let offset = episode_offset_for(config, &[&self.title, parsed_title], self.season);
+        self.episode_abs = self.episode.map(|episode| absolute_episode(episode, offset));
+    }
+    
+    // 各字段在渲染时是否有值
+    pub fn available_tokens(&self) -> HashMap<String, bool> {
+        let has_title = !self.title.is_empty();
+        
+        TEMPLATE_TOKENS.iter()
+            .map(|token| {
+                let available = match *token {
+                    "{title}" => has_title,
+                    "{title_clean}" => self.title_clean.is_some() || has_title,
+                    "{title_romaji}" => self.title_romaji.is_some() || has_title,
+                    "{title_english}" => self.title_english.is_some() || has_title,
+                    "{season}" | "{season:02}" => self.season.is_some(),
+                    "{episode}" | "{episode:02}" | "{episode:03}" => self.episode.is_some(),
+                    "{episode_abs}" => self.episode_abs.is_some(),
+                    "{episode_title}" => self.episode_title.is_some(),
+                    "{year}" => self.year.is_some(),
+                    "{group}" => self.group.is_some(),
+                    "{resolution}" => self.resolution.is_some(),
+                    "{ext}" => self.ext.is_some(),
+                    _ => false,
+                };
+                (token.to_string(), available)
+            })
+            .collect()
+    }
+}
+
+// 按上下文渲染命名模板
+pub fn render_template(template: &str, context: &TemplateContext, separators: &[String]) -> String {
+    normalize_rendered_name(&render_template_raw(template, context), separators)
+}
+
+// 只替换字段，不做清理
+fn render_template_raw(template: &str, context: &TemplateContext) -> String {
+    let number = |value: Option<u32>, width: usize| {
+        value.map(|v| format!("{:0width$}", v, width = width)).unwrap_or_default()
+    };
+    
+    let template = replace_padded_number(template, "season", context.season);
+    let template = replace_padded_number(&template, "episode", context.episode);
+    let template = replace_padded_number(&template, "episode_abs", context.episode_abs);
+    template
+        .replace("{title_romaji}", context.title_romaji.as_deref().unwrap_or(&context.title))
+        .replace("{title_english}", context.title_english.as_deref().unwrap_or(&context.title))
+        .replace("{title_clean}", context.title_clean.as_deref().unwrap_or(&context.title))
+        .replace("{title}", &context.title)
+        .replace("{season}", &number(context.season, 1))
+        .replace("{episode}", &number(context.episode, 2))
+        .replace("{episode_abs}", &number(context.episode_abs, 2))
+        .replace("{episode_title}", context.episode_title.as_deref().unwrap_or_default())
+        .replace("{year}", &number(context.year, 1))
+        .replace("{group}", context.group.as_deref().unwrap_or_default())
+        .replace("{resolution}", context.resolution.as_deref().unwrap_or_default())
+        .replace("{ext}", context.ext.as_deref().unwrap_or_default())
+}
+
+// 提取模板中出现的所有字段（包括未知字段），按出现顺序去重
+pub fn template_tokens(template: &str) -> Vec<String> {
+    let token_pattern = Regex::new(r"\{[^{}]*\}").unwrap();
+    let mut tokens: Vec<String> = Vec::new();
+    for token in token_pattern.find_iter(template).map(|m| m.as_str().to_string()) {
+        if !tokens.contains(&token) {
+            tokens.push(token);
+        }
+    }
+    tokens
+}
+
+// 配置中某系列某季的集数偏移，标题不区分大小写，未配置时为 0
+pub fn episode_offset_for(config: &AppConfig, titles: &[&str], season: Option<u32>) -> i64 {
+    let season = season.unwrap_or(1);
+    titles.iter()
+        .filter(|title| !title.trim().is_empty())
+        .find_map(|title| {
+            config.episode_offsets.iter()
+                .find(|(series, _)| series.trim().eq_ignore_ascii_case(title.trim()))
+                .and_then(|(_, seasons)| seasons.get(&season))
+        })
+        .copied()
+        .unwrap_or(0)
+}
+
+// 加上偏移后的绝对集数，负数偏移按 0 处理（保存配置时会报错）
+pub fn absolute_episode(episode: u32, offset: i64) -> u32 {
+    episode.saturating_add(u32::try_from(offset).unwrap_or(0))
+}
+
+// 替换 {name:0N} 形式的补零数字字段，N 为任意宽度，值缺失时替换为空字符串
+// 不带宽度的 {name} 不在这里处理，由调用方按各自的默认宽度替换
+pub fn replace_padded_number(template: &str, name: &str, value: Option<u32>) -> String {
+    PADDED_NUMBER_TOKEN.replace_all(template, |caps: &Captures| {
+        if &caps[1] != name {
+            return caps[0].to_string();
+        }
+        let width: usize = caps[2].parse().unwrap_or(0);
+        value.map(|v| format!("{:0width$}", v, width = width)).unwrap_or_default()
+    }).into_owned()
+}
+
+// 补零数字字段对应的基础字段，例如 {episode:04} -> {episode}，其他字段返回 None
+pub fn padded_token_base(token: &str) -> Option<String> {
+    let caps = PADDED_NUMBER_TOKEN.captures(token)?;
+    (caps[0].len() == token.len()).then(|| format!("{{{}}}", &caps[1]))
+}
+
+// 字段是否在允许的列表中，补零数字字段只要基础字段在列表中即可
+pub fn token_allowed(token: &str, allowed: &[&str]) -> bool {
+    allowed.contains(&token) || padded_token_base(token).is_some_and(|base| allowed.contains(&base.as_str()))
+}
+
+// 模板中不在 TEMPLATE_TOKENS 里的字段，例如拼写错误的 {titel_romaji}，按出现顺序去重
+pub fn unknown_template_tokens(template: &str) -> Vec<String> {
+    template_tokens(template).into_iter()
+        .filter(|token| !token_allowed(token, &TEMPLATE_TOKENS))
+        .collect()
+}
+
+// 检查命名模板，返回无法识别的字段，全部可识别时返回空列表
+#[command]
+pub async fn validate_naming_template(template: String) -> Result<Vec<String>, AppError> {
+    Ok(unknown_template_tokens(&template))
+}
+
+// 模板渲染后的清理：合并多余空格、重复的分隔符，去除空括号以及首尾的分隔符
+// 可选字段缺失时（例如年份未知）避免生成 "Show -  - E01"、"Show ()" 之类的名称
+pub fn normalize_rendered_name(name: &str, separators: &[String]) -> String {
+    let whitespace = Regex::new(r"\s+").unwrap();
+    let empty_brackets = Regex::new(r"\(\s*\)|\[\s*\]").unwrap();
+    
+    let mut normalized = whitespace.replace_all(name, " ").to_string();
+    normalized = empty_brackets.replace_all(&normalized, "").to_string();
+    
+    let separators: Vec<&str> = separators.iter()
+        .map(|sep| sep.trim())
+        .filter(|sep| !sep.is_empty())
+        .collect();
+    
+    for sep in &separators {
+        // 连续出现两次及以上的同一分隔符合并为一个，保留原有的空格风格
+        let pattern = format!(r"\s*{0}(?:\s*{0})+\s*", regex::escape(sep));
+        if let Ok(run) = Regex::new(&pattern) {
+            normalized = run.replace_all(&normalized, |caps: &regex::Captures| {
+                if caps[0].chars().any(char::is_whitespace) {
+                    format!(" {} ", sep)
+                } else {
+                    sep.to_string()
+                }
+            }).to_string();
+        }
+    }
+    
+    normalized = whitespace.replace_all(&normalized, " ").to_string();
+    
+    // 反复去除首尾的空格和分隔符，直到不再变化
+    loop {
+        let mut trimmed = normalized.trim();
+        for sep in &separators {
+            trimmed = trimmed.trim_start_matches(sep).trim_end_matches(sep).trim();
+        }
+        if trimmed.len() == normalized.len() {
+            break;
+        }
+        normalized = trimmed.to_string();
+    }
+    
+    normalized
+}
+
+
+// 根据AniList的播出季度生成分组文件夹名，例如 "2023 Fall"
+// 未开启分组或缺少季度/年份信息时返回 None
+pub fn broadcast_season_folder(anime: &AniListResponse, config: &AppConfig) -> Option<String> {
+    if !config.group_by_broadcast_season {
+        return None;
+    }
+    
+    let season = anime.season.as_deref()?;
+    let year = anime.season_year?;
+    
+    let label = config.broadcast_season_labels.get(&season.to_uppercase())
+        .cloned()
+        .unwrap_or_else(|| season.to_string());
+    
+    let folder = config.broadcast_season_template
+        .replace("{year}", &year.to_string())
+        .replace("{broadcast_season}", &label);
+    
+    Some(normalize_rendered_name(&folder, &config.template_separators))
+}
+
+// 查询某个文件在命名模板中哪些字段会有值，供界面将无值的字段置灰
+// 季度在文件名中缺失时按第1季处理，因此总是可用
+#[command]
+pub async fn available_tokens_for(filename: String, metadata: Option<AniListResponse>) -> Result<HashMap<String, bool>, AppError> {
+    let config = read_app_config();
+    let parsed = parse_filename_internal(&filename).ok();
+    let mut context = TemplateContext::from_parsed(parsed.as_ref(), metadata.as_ref(), config.use_romaji_names);
+    context.apply_episode_offsets(&config, parsed.as_ref().map(|p| p.anime_title.as_str()).unwrap_or_default());
+    context.ext = Path::new(&filename).extension().map(|ext| ext.to_string_lossy().to_string());
+    
+    if let (Some(anime), Some(episode)) = (&metadata, context.episode) {
+        if config.fetch_episode_titles {
+            context.episode_title = get_anilist_episode_titles(anime.id).await
+                .ok()
+                .and_then(|titles| titles.get(&episode).cloned());
+        }
+    }
+    
+    Ok(context.available_tokens())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub token: String,
+    pub known: bool,
+    pub resolves: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateDescription {
+    pub template: String,
+    pub tokens: Vec<TokenUsage>,
+    // 仅替换字段、未清理的结果，便于对比清理做了什么
+    pub raw_rendered: String,
+    pub rendered: String,
+    pub warnings: Vec<String>,
+}
+
+// 解释命名模板：用到的字段、每个字段在示例中是否有值、渲染结果以及问题提示
+#[command]
+pub async fn describe_template(
+    template: String,
+    sample: ParsedFilename,
+    metadata: Option<AniListResponse>
+) -> Result<TemplateDescription, AppError> {
+    let config = read_app_config();
+    let mut context = TemplateContext::from_parsed(Some(&sample), metadata.as_ref(), config.use_romaji_names);
+    context.apply_episode_offsets(&config, &sample.anime_title);
+    let available = context.available_tokens();
+    
+    let mut warnings = Vec::new();
+    let tokens: Vec<TokenUsage> = template_tokens(&template).into_iter()
+        .map(|token| {
+            let known = token_allowed(&token, &TEMPLATE_TOKENS);
+            let resolves = available.get(&padded_token_base(&token).unwrap_or_else(|| token.clone())).copied().unwrap_or(false);
+            if !known {
+                warnings.push(format!("未知的模板字段: {}", token));
+            } else if !resolves {
+                warnings.push(format!("字段 {} 在示例中没有值，渲染时该部分将被省略", token));
+            }
+            TokenUsage { token, known, resolves }
+        })
+        .collect();
+    
+    let trimmed = template.trim();
+    if config.template_separators.iter()
+        .map(|sep| sep.trim())
+        .filter(|sep| !sep.is_empty())
+        .any(|sep| trimmed.starts_with(sep) || trimmed.ends_with(sep))
+    {
+        warnings.push("模板以分隔符开头或结尾，渲染时会被去除".to_string());
+    }
+    
+    let raw_rendered = render_template_raw(&template, &context);
+    let rendered = normalize_rendered_name(&raw_rendered, &config.template_separators);
+    if rendered.is_empty() {
+        warnings.push("示例渲染结果为空".to_string());
+    }
+    
+    Ok(TemplateDescription {
+        template,
+        tokens,
+        raw_rendered,
+        rendered,
+        warnings,
+    })
+}
+
+// 标题覆盖的键：AniList 条目为 "anilist:<id>"，其余为规范化后的解析标题（小写、合并空白）
+pub fn title_override_key(title: &str) -> String {
+    let key = title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+    match key.strip_prefix("anilist:") {
+        Some(id) => format!("anilist:{}", id.trim()),
+        None => key,
+    }
+}
+
+// 查找动漫文件夹名的标题覆盖，AniList ID 优先于解析标题
+pub(crate) fn title_override<'a>(anime: Option<&AniListResponse>, parsed_title: &str, config: &'a AppConfig) -> Option<&'a str> {
+    anime.and_then(|anime| config.title_overrides.get(&format!("anilist:{}", anime.id)))
+        .or_else(|| config.title_overrides.get(&title_override_key(parsed_title)))
+        .map(|title| title.as_str())
+        .filter(|title| !title.trim().is_empty())
+}
+
+// 用覆盖标题替换上下文中所有标题字段，用于渲染动漫文件夹名
+pub(crate) fn with_title_override(context: &TemplateContext, folder_title: &str) -> TemplateContext {
+    TemplateContext {
+        title: folder_title.to_string(),
+        title_clean: Some(folder_title.to_string()),
+        title_romaji: Some(folder_title.to_string()),
+        title_english: Some(folder_title.to_string()),
+        ..context.clone()
+    }
+}
+
+#[command]
+pub async fn get_title_overrides() -> Result<HashMap<String, String>, AppError> {
+    Ok(read_app_config().title_overrides)
+}
+
+// 设置或删除（title 为空时）一条标题覆盖并立即保存到配置，返回全部覆盖
+#[command]
+pub async fn set_title_override(key: String, title: Option<String>) -> Result<HashMap<String, String>, AppError> {
+    let key = title_override_key(&key);
+    if key.is_empty() || key == "anilist:" {
+        return Err("标题覆盖的键不能为空".into());
+    }
+    
+    let mut config = read_app_config();
+    match title.map(|title| title.trim().to_string()).filter(|title| !title.is_empty()) {
+        Some(title) => {
+            config.title_overrides.insert(key, title);
+        }
+        None => {
+            config.title_overrides.remove(&key);
+        }
+    }
+    write_config_file(&config)?;
+    
+    Ok(config.title_overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn unknown_template_tokens_reports_misspelled_fields() {
+        assert_eq!(unknown_template_tokens("{titel_romaji} - {episode}"), vec!["{titel_romaji}"]);
+        assert_eq!(unknown_template_tokens("{titel_romaji} {titel_romaji}"), vec!["{titel_romaji}"]);
+    }
+    
+    #[test]
+    fn unknown_template_tokens_accepts_padded_and_absolute_episodes() {
+        assert!(unknown_template_tokens("{title} - S{season:02}E{episode:04}").is_empty());
+        assert!(unknown_template_tokens("{title} - {episode_abs} ({episode_abs:03})").is_empty());
+        assert_eq!(unknown_template_tokens("{title:02}"), vec!["{title:02}"]);
+    }
+}
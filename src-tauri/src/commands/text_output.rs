@@ -0,0 +1,47 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::commands::config::AppConfig;
+
+// UTF-8 BOM，部分 Kodi 环境需要它才能正确识别 NFO 编码
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+pub const LINE_ENDINGS: [&str; 3] = ["native", "lf", "crlf"];
+
+// 根据配置返回写入文本文件时使用的换行符，native 时跟随当前平台
+pub fn line_ending(config: &AppConfig) -> &'static str {
+    match config.line_ending.as_str() {
+        "lf" => "\n",
+        "crlf" => "\r\n",
+        _ if cfg!(windows) => "\r\n",
+        _ => "\n",
+    }
+}
+
+// 将文本统一换行符后编码为 UTF-8 字节，按配置决定是否添加 BOM
+pub fn encode_text(content: &str, config: &AppConfig) -> Vec<u8> {
+    let newline = line_ending(config);
+    let normalized = content.replace("\r\n", "\n");
+    let text = if newline == "\n" {
+        normalized
+    } else {
+        normalized.replace('\n', newline)
+    };
+    
+    let mut bytes = Vec::with_capacity(text.len() + UTF8_BOM.len());
+    if config.write_utf8_bom {
+        bytes.extend_from_slice(UTF8_BOM);
+    }
+    bytes.extend_from_slice(text.as_bytes());
+    bytes
+}
+
+// 写入 NFO、清单、strm 等文本文件，换行符和 BOM 按配置处理
+pub fn write_text_file(path: &Path, content: &str, config: &AppConfig) -> io::Result<()> {
+    fs::write(path, encode_text(content, config))
+}
+
+// 读取本程序写出的文本文件前去掉可能存在的 BOM
+pub fn strip_bom(content: &str) -> &str {
+    content.strip_prefix('\u{FEFF}').unwrap_or(content)
+}
@@ -0,0 +1,222 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+use crate::commands::file_operations::LinkAction;
+
+// 批量处理期间定期发送的吞吐量事件名
+pub const THROUGHPUT_EVENT: &str = "throughput";
+
+// 两次事件之间的最短间隔，避免大量硬链接瞬间完成时刷屏
+const EMIT_INTERVAL: Duration = Duration::from_millis(500);
+
+// 计算速率的滚动窗口长度
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThroughputStats {
+    // 操作类型，与日志来源标签一致
+    pub operation: String,
+    pub completed: usize,
+    pub total: usize,
+    pub bytes_done: u64,
+    pub total_bytes: u64,
+    // 以下速率按最近的滚动窗口计算
+    pub files_per_sec: f64,
+    // 硬链接几乎瞬间完成，单独统计每秒链接数
+    pub links_per_sec: f64,
+    // 只统计复制的字节，硬链接不产生数据写入
+    pub copy_mb_per_sec: f64,
+    pub links: usize,
+    pub copies: usize,
+    pub failed: usize,
+    pub eta_secs: Option<u64>,
+    pub finished: bool,
+}
+
+// 一次完成记录：(完成时间, 复制的字节数, 是否为复制)
+type Completion = (Instant, u64, bool);
+
+struct TrackerState {
+    // 取消的文件会从总数中扣除
+    total: usize,
+    total_bytes: u64,
+    window: VecDeque<Completion>,
+    completed: usize,
+    bytes_done: u64,
+    links: usize,
+    copies: usize,
+    failed: usize,
+    last_emit: Option<Instant>,
+}
+
+// 跟踪批量处理的完成情况，并通过 throughput 事件定期报告速率和预计剩余时间
+// 由多个处理线程共享，每个文件通过 begin 返回的记录计入统计
+pub struct ThroughputTracker {
+    app: AppHandle,
+    operation: &'static str,
+    state: Mutex<TrackerState>,
+}
+
+impl ThroughputTracker {
+    pub fn new(app: AppHandle, operation: &'static str, files: &[String]) -> Self {
+        let total_bytes = files.iter()
+            .filter_map(|file_path| fs::metadata(file_path).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        
+        ThroughputTracker {
+            app,
+            operation,
+            state: Mutex::new(TrackerState {
+                total: files.len(),
+                total_bytes,
+                window: VecDeque::new(),
+                completed: 0,
+                bytes_done: 0,
+                links: 0,
+                copies: 0,
+                failed: 0,
+                last_emit: None,
+            }),
+        }
+    }
+    
+    // 开始处理一个文件，返回的记录在丢弃时计入统计，未标记成功的按失败计
+    pub(crate) fn begin(&self, source: &Path) -> PendingFile<'_> {
+        PendingFile {
+            tracker: self,
+            size: fs::metadata(source).map(|metadata| metadata.len()).unwrap_or(0),
+            action: None,
+        }
+    }
+    
+    fn record_success(&self, source_size: u64, action: LinkAction) {
+        let copied = action == LinkAction::Copy;
+        let mut state = self.state.lock().unwrap();
+        state.completed += 1;
+        state.bytes_done += source_size;
+        if copied {
+            state.copies += 1;
+        } else {
+            state.links += 1;
+        }
+        state.window.push_back((Instant::now(), if copied { source_size } else { 0 }, copied));
+        self.emit_if_due(&mut state, false);
+    }
+    
+    // 失败或跳过的文件计入完成数，但不计入速率
+    fn record_failure(&self, source_size: u64) {
+        let mut state = self.state.lock().unwrap();
+        state.completed += 1;
+        state.bytes_done += source_size;
+        state.failed += 1;
+        self.emit_if_due(&mut state, false);
+    }
+    
+    // 收到停止请求后未处理的文件从总数中扣除，使完成数最终等于总数
+    pub(crate) fn cancelled(&self, source: &Path) {
+        let size = fs::metadata(source).map(|metadata| metadata.len()).unwrap_or(0);
+        let mut state = self.state.lock().unwrap();
+        state.total = state.total.saturating_sub(1);
+        state.total_bytes = state.total_bytes.saturating_sub(size);
+    }
+    
+    // 批量处理结束时发送最终统计
+    pub fn finish(&self) {
+        let mut state = self.state.lock().unwrap();
+        self.emit_if_due(&mut state, true);
+    }
+    
+    fn emit_if_due(&self, state: &mut TrackerState, finished: bool) {
+        let now = Instant::now();
+        if !finished && state.last_emit.is_some_and(|last| now.duration_since(last) < EMIT_INTERVAL) {
+            return;
+        }
+        state.last_emit = Some(now);
+        
+        while state.window.front().is_some_and(|(at, _, _)| now.duration_since(*at) > RATE_WINDOW) {
+            state.window.pop_front();
+        }
+        
+        // 窗口从最早一次完成算起，刚开始时至少按 1 秒计，避免速率虚高
+        let elapsed = state.window.front()
+            .map(|(at, _, _)| now.duration_since(*at).as_secs_f64())
+            .unwrap_or(0.0)
+            .max(1.0);
+        let files_in_window = state.window.len() as f64;
+        let links_in_window = state.window.iter().filter(|(_, _, copied)| !copied).count() as f64;
+        let copy_bytes_in_window: u64 = state.window.iter().map(|(_, bytes, _)| bytes).sum();
+        
+        let files_per_sec = files_in_window / elapsed;
+        let copy_bytes_per_sec = copy_bytes_in_window as f64 / elapsed;
+        
+        let stats = ThroughputStats {
+            operation: self.operation.to_string(),
+            completed: state.completed,
+            total: state.total,
+            bytes_done: state.bytes_done,
+            total_bytes: state.total_bytes,
+            files_per_sec,
+            links_per_sec: links_in_window / elapsed,
+            copy_mb_per_sec: copy_bytes_per_sec / (1024.0 * 1024.0),
+            links: state.links,
+            copies: state.copies,
+            failed: state.failed,
+            eta_secs: if finished { Some(0) } else { self.estimate_remaining(state, files_per_sec, copy_bytes_per_sec) },
+            finished,
+        };
+        
+        if let Err(e) = self.app.emit(THROUGHPUT_EVENT, stats) {
+            warn!("发送吞吐量事件失败: {}", e);
+        }
+    }
+    
+    // 剩余时间：已有复制时，按复制所占比例估算剩余需要复制的字节数并按复制速率计算；
+    // 与按文件数估算的时间取较大者，全部是硬链接时只按文件数估算
+    fn estimate_remaining(&self, state: &TrackerState, files_per_sec: f64, copy_bytes_per_sec: f64) -> Option<u64> {
+        if files_per_sec <= 0.0 {
+            return None;
+        }
+        
+        let remaining_files = state.total.saturating_sub(state.completed) as f64;
+        let by_files = remaining_files / files_per_sec;
+        
+        let succeeded = state.links + state.copies;
+        let by_bytes = if state.copies > 0 && copy_bytes_per_sec > 0.0 {
+            let copy_share = state.copies as f64 / succeeded as f64;
+            let remaining_bytes = state.total_bytes.saturating_sub(state.bytes_done) as f64;
+            remaining_bytes * copy_share / copy_bytes_per_sec
+        } else {
+            0.0
+        };
+        
+        Some(by_files.max(by_bytes).round() as u64)
+    }
+}
+
+// 正在处理的文件，处理函数提前返回时也能保证计入完成数
+pub(crate) struct PendingFile<'a> {
+    tracker: &'a ThroughputTracker,
+    size: u64,
+    action: Option<LinkAction>,
+}
+
+impl PendingFile<'_> {
+    pub(crate) fn succeeded(&mut self, action: LinkAction) {
+        self.action = Some(action);
+    }
+}
+
+impl Drop for PendingFile<'_> {
+    fn drop(&mut self) {
+        match self.action {
+            Some(action) => self.tracker.record_success(self.size, action),
+            None => self.tracker.record_failure(self.size),
+        }
+    }
+}
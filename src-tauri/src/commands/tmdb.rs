@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use tauri::command;
+use tracing::warn;
+use crate::commands::config::read_app_config;
+use crate::commands::errors::AppError;
+
+const TMDB_API_BASE: &str = "https://api.themoviedb.org/3";
+
+// 海报地址前缀，poster_path 是相对路径
+pub const TMDB_POSTER_BASE: &str = "https://image.tmdb.org/t/p/w500";
+
+// 每类搜索最多返回的结果数
+const TMDB_RESULT_LIMIT: usize = 10;
+
+// 统一后的 TMDB 搜索结果，剧集和电影共用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmdbResult {
+    pub id: u64,
+    // tv 或 movie
+    pub media_type: String,
+    pub title: String,
+    pub original_title: Option<String>,
+    // 首播日期（电影为上映日期）的年份
+    pub year: Option<u32>,
+    // 只有剧集有季数，需要额外查询详情
+    pub number_of_seasons: Option<u32>,
+    pub poster_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TmdbSearchResponse<T> {
+    results: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TmdbTvResult {
+    id: u64,
+    name: String,
+    original_name: Option<String>,
+    first_air_date: Option<String>,
+    poster_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TmdbMovieResult {
+    id: u64,
+    title: String,
+    original_title: Option<String>,
+    release_date: Option<String>,
+    poster_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TmdbTvDetails {
+    number_of_seasons: Option<u32>,
+}
+
+// 日期格式为 YYYY-MM-DD，未知时 TMDB 返回空字符串
+fn year_of(date: Option<&str>) -> Option<u32> {
+    date?.get(..4)?.parse().ok()
+}
+
+fn tmdb_api_key() -> Result<String, String> {
+    read_app_config().tmdb_api_key
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+        .ok_or_else(|| "未配置 TMDB API 密钥，请在设置中填写 tmdb_api_key".to_string())
+}
+
+// 发送 TMDB GET 请求并解析JSON，401 时返回明确的密钥错误
+async fn get_tmdb<T: for<'de> Deserialize<'de>>(path: &str, api_key: &str, params: &[(&str, &str)]) -> Result<T, String> {
+    let client = reqwest::Client::new();
+    let response = client
+        .get(format!("{}{}", TMDB_API_BASE, path))
+        .query(&[("api_key", api_key)])
+        .query(params)
+        .send()
+        .await
+        .map_err(|e| format!("TMDB API请求失败: {}", e))?;
+    
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err("TMDB API 密钥无效或缺失 (401)，请检查设置中的 tmdb_api_key".to_string());
+    }
+    if !status.is_success() {
+        return Err(format!("TMDB API返回错误状态: {}", status));
+    }
+    
+    response.json::<T>().await
+        .map_err(|e| format!("解析TMDB响应失败: {}", e))
+}
+
+// 同时搜索 TMDB 的剧集和电影，剧集结果会额外查询季数
+#[command]
+pub async fn search_tmdb(query: String) -> Result<Vec<TmdbResult>, AppError> {
+    let api_key = tmdb_api_key()?;
+    let params = [("query", query.as_str()), ("include_adult", "false")];
+    
+    let (tv, movies) = tokio::join!(
+        get_tmdb::<TmdbSearchResponse<TmdbTvResult>>("/search/tv", &api_key, &params),
+        get_tmdb::<TmdbSearchResponse<TmdbMovieResult>>("/search/movie", &api_key, &params),
+    );
+    // 两类搜索都失败时才返回错误，密钥错误时两者都会失败
+    let (tv, movies) = match (tv, movies) {
+        (Err(e), Err(_)) => return Err(AppError::Network(e)),
+        (tv, movies) => (
+            tv.map(|response| response.results).unwrap_or_else(|e| { warn!("TMDB 剧集搜索失败: {}", e); Vec::new() }),
+            movies.map(|response| response.results).unwrap_or_else(|e| { warn!("TMDB 电影搜索失败: {}", e); Vec::new() }),
+        ),
+    };
+    
+    let mut details = Vec::new();
+    for show in tv.iter().take(TMDB_RESULT_LIMIT) {
+        let api_key = api_key.clone();
+        let id = show.id;
+        details.push(tokio::spawn(async move {
+            get_tmdb::<TmdbTvDetails>(&format!("/tv/{}", id), &api_key, &[]).await
+        }));
+    }
+    
+    let mut results = Vec::new();
+    for (show, detail) in tv.into_iter().zip(details) {
+        // 季数只是附加信息，查询失败时留空
+        let number_of_seasons = match detail.await {
+            Ok(Ok(detail)) => detail.number_of_seasons,
+            Ok(Err(e)) => {
+                warn!("获取 TMDB 剧集详情失败 ({}): {}", show.id, e);
+                None
+            }
+            Err(e) => {
+                warn!("TMDB 剧集详情任务异常: {}", e);
+                None
+            }
+        };
+        results.push(TmdbResult {
+            id: show.id,
+            media_type: "tv".to_string(),
+            year: year_of(show.first_air_date.as_deref()),
+            title: show.name,
+            original_title: show.original_name,
+            number_of_seasons,
+            poster_path: show.poster_path,
+        });
+    }
+    
+    results.extend(movies.into_iter().take(TMDB_RESULT_LIMIT).map(|movie| TmdbResult {
+        id: movie.id,
+        media_type: "movie".to_string(),
+        year: year_of(movie.release_date.as_deref()),
+        title: movie.title,
+        original_title: movie.original_title,
+        number_of_seasons: None,
+        poster_path: movie.poster_path,
+    }));
+    
+    Ok(results)
+}
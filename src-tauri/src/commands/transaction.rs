@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, State};
+use tracing::{info, warn, error};
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::history::record_operation;
+use crate::commands::file_operations::{
+    FileError, FileSystemError, LinkAction, LinkOutcome, ProcessResult, sanitize_path, create_link_with_config,
+    copy_or_clean_up, copy_forbidden_extension, remove_created_dirs, write_batch_manifest,
+};
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_BATCH_LINK_TRANSACTIONAL};
+use crate::commands::manifest::build_manifest_entry;
+use crate::commands::errors::AppError;
+
+// 一组源文件和目标路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPair {
+    pub source: String,
+    pub target: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TransactionalOptions {
+    // 源文件和目标不在同一文件系统时改为复制，默认不允许
+    pub allow_copy: Option<bool>,
+    // 提供时在全部成功后将清单写入该目录
+    pub manifest_dir: Option<String>,
+}
+
+// 本次已创建的链接，回滚时删除
+struct CreatedLink {
+    target: PathBuf,
+    // 本次为该链接新建的最上层目录
+    created_dir: Option<PathBuf>,
+}
+
+// 全有或全无地创建一组链接：任意一个失败时删除本次已创建的所有链接和新建的空目录后返回错误
+// 链接按顺序逐个创建，适用于一致性比部分进度更重要的小批量文件，大批量请使用普通的批量处理
+#[command]
+pub async fn batch_link_transactional(
+    pairs: Vec<LinkPair>,
+    options: Option<TransactionalOptions>,
+    log_store: State<'_, LogStore>
+) -> Result<ProcessResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_BATCH_LINK_TRANSACTIONAL);
+    let options = options.unwrap_or_default();
+    let allow_copy = options.allow_copy.unwrap_or(false);
+    let config = read_app_config();
+    
+    info!("开始事务性链接 {} 个文件", pairs.len());
+    log.info(format!("开始事务性链接 {} 个文件", pairs.len()));
+    
+    // 先检查所有条目，有问题时不做任何改动
+    let mut targets = HashSet::new();
+    for pair in &pairs {
+        let target = sanitize_path(&PathBuf::from(&pair.target));
+        if !Path::new(&pair.source).is_file() {
+            return Err(AppError::NotFound(format!("源文件不存在: {}", pair.source)));
+        }
+        if target.exists() {
+            return Err(format!("目标文件已存在: {}", target.display()).into());
+        }
+        if !targets.insert(target) {
+            return Err(format!("多个条目指向同一目标: {}", pair.target).into());
+        }
+    }
+    
+    let mut created: Vec<CreatedLink> = Vec::new();
+    let mut manifest_entries = Vec::new();
+    let mut failure = None;
+    
+    for pair in &pairs {
+        let source = PathBuf::from(&pair.source);
+        let target = sanitize_path(&PathBuf::from(&pair.target));
+        let created_dir = target.parent().and_then(|parent| {
+            parent.ancestors()
+                .take_while(|ancestor| !ancestor.exists())
+                .last()
+                .map(Path::to_path_buf)
+        });
+        
+        match link_pair(&source, &target, allow_copy, &config) {
+            Ok(outcome) => {
+                if let Some(manifest_dir) = &options.manifest_dir {
+                    manifest_entries.push(build_manifest_entry(&source, &outcome.target, Path::new(manifest_dir), outcome.action.as_str()));
+                }
+                created.push(CreatedLink { target: outcome.target, created_dir });
+            }
+            Err(e) => {
+                failure = Some(FileError {
+                    path: pair.source.clone(),
+                    error: e.to_string(),
+                });
+                break;
+            }
+        }
+    }
+    
+    if let Some(failure) = failure {
+        error!("事务性链接失败: {} - {}", failure.path, failure.error);
+        log.error(format!("链接失败: {} - {}，开始回滚 {} 个已创建的链接", failure.path, failure.error, created.len()));
+        
+        let rollback_failed = rollback(&created);
+        for failed in &rollback_failed {
+            log.error(format!("回滚失败: {} - {}", failed.path, failed.error));
+        }
+        
+        return Err(if rollback_failed.is_empty() {
+            format!("链接失败，已回滚 {} 个链接: {} - {}", created.len(), failure.path, failure.error).into()
+        } else {
+            format!("链接失败: {} - {}；回滚时有 {} 个文件未能删除，请手动检查", failure.path, failure.error, rollback_failed.len()).into()
+        });
+    }
+    
+    if let Some(manifest_dir) = &options.manifest_dir {
+        write_batch_manifest(&log, Path::new(manifest_dir), manifest_entries, &config);
+    }
+    
+    info!("事务性链接完成: {} 个文件", created.len());
+    log.info(format!("事务性链接完成: {} 个文件", created.len()));
+    
+    let result = ProcessResult {
+        success: true,
+        message: format!("全部 {} 个文件链接成功", created.len()),
+        processed_files: pairs.iter().map(|pair| pair.source.clone()).collect(),
+        failed_files: Vec::new(),
+        skipped_files: Vec::new(),
+        linked_sidecars: Vec::new(),
+        warnings: Vec::new(),
+        link_actions: HashMap::new(),
+        cancelled_files: Vec::new(),
+        checksums: HashMap::new(),
+    };
+    let target = options.manifest_dir.clone().unwrap_or_default();
+    record_operation(SOURCE_BATCH_LINK_TRANSACTIONAL, &target, &result, &config);
+    
+    Ok(result)
+}
+
+// 创建单个链接，允许复制时跨文件系统改为复制
+fn link_pair(source: &Path, target: &Path, allow_copy: bool, config: &AppConfig) -> Result<LinkOutcome, FileSystemError> {
+    match create_link_with_config(source, target, config) {
+        Err(FileSystemError::DifferentFilesystems) if allow_copy => {
+            if let Some(extension) = copy_forbidden_extension(source, config) {
+                return Err(FileSystemError::CopyForbidden(extension));
+            }
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            copy_or_clean_up(source, target)?;
+            Ok(LinkOutcome { target: target.to_path_buf(), action: LinkAction::Copy })
+        }
+        other => other,
+    }
+}
+
+// 按创建的逆序删除链接及本次新建的空目录，返回未能删除的文件
+fn rollback(created: &[CreatedLink]) -> Vec<FileError> {
+    let mut failed = Vec::new();
+    
+    for link in created.iter().rev() {
+        match fs::remove_file(&link.target) {
+            Ok(_) => {
+                info!("已回滚链接: {}", link.target.display());
+                if let (Some(created_dir), Some(parent)) = (&link.created_dir, link.target.parent()) {
+                    remove_created_dirs(parent, created_dir);
+                }
+            }
+            Err(e) => {
+                warn!("回滚链接失败: {}, 错误: {}", link.target.display(), e);
+                failed.push(FileError {
+                    path: link.target.to_string_lossy().to_string(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+    
+    failed
+}
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use chrono::Utc;
+use tauri::{command, State};
+use tracing::{info, warn};
+use crate::commands::config::{AppConfig, get_config_dir};
+use crate::commands::file_operations::{FileError, FileSystemError, LinkOutcome, create_link_with_config, is_same_file};
+use crate::commands::library::file_content_hash;
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_UNDO_LAST_BATCH};
+use crate::commands::sidecars::SidecarLink;
+use crate::commands::errors::AppError;
+
+// 最近一次批量操作的链接记录，存放在配置目录中，撤销时读取
+const JOURNAL_FILE_NAME: &str = "last_batch_journal.json";
+
+// 一个由批量操作创建的目标文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub source: String,
+    pub target: String,
+    // hardlink / symlink / copy
+    pub action: String,
+    // 创建该链接时新建的最上层目录，撤销时如果为空则一并删除
+    #[serde(default)]
+    pub created_dir: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchJournalRecord {
+    pub id: String,
+    // 操作类型，与日志来源标签一致，例如 "batch_process_with_rename"
+    pub operation: String,
+    pub timestamp: String,
+    pub entries: Vec<JournalEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UndoResult {
+    pub operation: String,
+    pub reverted: usize,
+    // 目标已不存在或已不是记录中源文件的链接，未删除
+    pub skipped: Vec<FileError>,
+    pub failed: Vec<FileError>,
+    pub removed_dirs: usize,
+}
+
+// 批量处理过程中记录成功创建的链接，处理结束后保存为最近一次批量操作
+#[derive(Default)]
+pub(crate) struct BatchJournal {
+    entries: Mutex<Vec<JournalEntry>>,
+}
+
+impl BatchJournal {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+    
+    // 按配置创建链接并记录，目标目录不存在时记录本次新建的最上层目录
+    pub(crate) fn link(&self, source: &Path, target: &Path, config: &AppConfig) -> Result<LinkOutcome, FileSystemError> {
+        let created_dir = first_missing_dir(target);
+        let outcome = create_link_with_config(source, target, config)?;
+        self.record(source, &outcome, created_dir);
+        Ok(outcome)
+    }
+    
+    pub(crate) fn record(&self, source: &Path, outcome: &LinkOutcome, created_dir: Option<PathBuf>) {
+        self.entries.lock().unwrap().push(JournalEntry {
+            source: source.to_string_lossy().to_string(),
+            target: outcome.target.to_string_lossy().to_string(),
+            action: outcome.action.as_str().to_string(),
+            created_dir: created_dir.map(|dir| dir.to_string_lossy().to_string()),
+        });
+    }
+    
+    // 连同附属文件一起保存；没有创建任何文件时保留上一次的记录
+    pub(crate) fn save(&self, operation: &str, sidecars: &[SidecarLink], log: &CommandLog<'_>) {
+        let mut entries = self.entries.lock().unwrap().clone();
+        entries.extend(sidecars.iter().map(|link| JournalEntry {
+            source: link.source.clone(),
+            target: link.target.clone(),
+            action: link.action.clone(),
+            created_dir: None,
+        }));
+        if entries.is_empty() {
+            return;
+        }
+        
+        let record = BatchJournalRecord {
+            id: uuid::Uuid::new_v4().to_string(),
+            operation: operation.to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+            entries,
+        };
+        if let Err(e) = write_journal(&record) {
+            warn!("写入撤销记录失败: {}", e);
+            log.warn(format!("写入撤销记录失败，本次操作无法撤销: {}", e));
+        }
+    }
+}
+
+// 目标路径上第一个尚不存在的上级目录
+pub(crate) fn first_missing_dir(target: &Path) -> Option<PathBuf> {
+    target.parent()?
+        .ancestors()
+        .take_while(|ancestor| !ancestor.exists())
+        .last()
+        .map(Path::to_path_buf)
+}
+
+fn get_journal_path() -> Result<PathBuf, String> {
+    Ok(get_config_dir()?.join(JOURNAL_FILE_NAME))
+}
+
+fn write_journal(record: &BatchJournalRecord) -> Result<(), String> {
+    let journal_path = get_journal_path()?;
+    if let Some(parent) = journal_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    
+    let content = serde_json::to_string_pretty(record)
+        .map_err(|e| format!("序列化撤销记录失败: {}", e))?;
+    fs::write(&journal_path, content)
+        .map_err(|e| format!("写入撤销记录文件失败: {}", e))
+}
+
+fn read_journal() -> Result<Option<BatchJournalRecord>, String> {
+    let journal_path = get_journal_path()?;
+    if !journal_path.exists() {
+        return Ok(None);
+    }
+    
+    let content = fs::read_to_string(&journal_path)
+        .map_err(|e| format!("读取撤销记录失败: {}", e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("撤销记录格式错误: {}", e))
+}
+
+// 目标是否仍是记录中源文件的链接：硬链接比较是否为同一文件，符号链接比较指向，复制比较内容
+fn still_links_to_source(entry: &JournalEntry) -> bool {
+    let source = Path::new(&entry.source);
+    let target = Path::new(&entry.target);
+    
+    match entry.action.as_str() {
+        "symlink" => match (fs::read_link(target), source.canonicalize()) {
+            (Ok(link), Ok(source)) => link == source,
+            _ => false,
+        },
+        // 非 Unix 平台无法比较文件编号，退回比较内容
+        "hardlink" if cfg!(unix) => is_same_file(source, target),
+        _ => same_content(source, target),
+    }
+}
+
+fn same_content(source: &Path, target: &Path) -> bool {
+    let same_size = match (fs::metadata(source), fs::metadata(target)) {
+        (Ok(source_meta), Ok(target_meta)) => source_meta.len() == target_meta.len(),
+        _ => false,
+    };
+    same_size && matches!(
+        (file_content_hash(source), file_content_hash(target)),
+        (Ok(source_hash), Ok(target_hash)) if source_hash == target_hash
+    )
+}
+
+// 删除 dir 到 created_dir（含）之间的空目录，返回删除的数量
+fn remove_empty_dirs(dir: &Path, created_dir: &Path) -> usize {
+    let mut removed = 0;
+    for ancestor in dir.ancestors() {
+        if !ancestor.starts_with(created_dir) || fs::remove_dir(ancestor).is_err() {
+            break;
+        }
+        info!("撤销时删除空目录: {}", ancestor.display());
+        removed += 1;
+    }
+    removed
+}
+
+// 撤销最近一次批量操作：删除其创建的目标文件和新建的空目录，不动源文件
+// 删除前确认目标仍链接到记录中的源文件，避免误删之后被替换的无关文件
+#[command]
+pub async fn undo_last_batch(log_store: State<'_, LogStore>) -> Result<UndoResult, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_UNDO_LAST_BATCH);
+    let record = read_journal()?.ok_or("没有可撤销的批量操作")?;
+    
+    info!("开始撤销批量操作 {} ({} 个文件)", record.operation, record.entries.len());
+    log.info(format!("开始撤销批量操作 {} ({} 个文件)", record.operation, record.entries.len()));
+    
+    let mut reverted = Vec::new();
+    let mut skipped = Vec::new();
+    let mut failed = Vec::new();
+    let mut remaining = Vec::new();
+    
+    for entry in record.entries.iter().rev() {
+        let target = Path::new(&entry.target);
+        if fs::symlink_metadata(target).is_err() {
+            skipped.push(FileError {
+                path: entry.target.clone(),
+                error: "目标文件已不存在".to_string(),
+            });
+            continue;
+        }
+        if !still_links_to_source(entry) {
+            warn!("目标已不是源文件的链接，跳过: {}", entry.target);
+            skipped.push(FileError {
+                path: entry.target.clone(),
+                error: format!("目标已不是 {} 的链接，未删除", entry.source),
+            });
+            continue;
+        }
+        
+        match fs::remove_file(target) {
+            Ok(_) => {
+                info!("已撤销: {}", entry.target);
+                reverted.push(entry);
+            }
+            Err(e) => {
+                warn!("撤销失败: {}, 错误: {}", entry.target, e);
+                failed.push(FileError {
+                    path: entry.target.clone(),
+                    error: e.to_string(),
+                });
+                remaining.push(entry.clone());
+            }
+        }
+    }
+    
+    // 文件全部删除后再清理目录，同一目录中的其他文件不影响顺序
+    let removed_dirs = reverted.iter()
+        .filter_map(|entry| {
+            let created_dir = entry.created_dir.as_ref()?;
+            Some(remove_empty_dirs(Path::new(&entry.target).parent()?, Path::new(created_dir)))
+        })
+        .sum();
+    
+    // 删除失败的条目保留下来，可以再次撤销
+    if remaining.is_empty() {
+        if let Err(e) = fs::remove_file(get_journal_path()?) {
+            log.warn(format!("删除撤销记录失败: {}", e));
+        }
+    } else {
+        remaining.reverse();
+        write_journal(&BatchJournalRecord { entries: remaining, ..record.clone() })?;
+    }
+    
+    for skipped_file in &skipped {
+        log.warn(format!("未撤销: {} - {}", skipped_file.path, skipped_file.error));
+    }
+    for failed_file in &failed {
+        log.error(format!("撤销失败: {} - {}", failed_file.path, failed_file.error));
+    }
+    log.info(format!("撤销完成: 删除 {}, 跳过 {}, 失败 {}, 删除空目录 {}", reverted.len(), skipped.len(), failed.len(), removed_dirs));
+    
+    Ok(UndoResult {
+        operation: record.operation,
+        reverted: reverted.len(),
+        skipped,
+        failed,
+        removed_dirs,
+    })
+}
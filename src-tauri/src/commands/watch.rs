@@ -0,0 +1,377 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{command, AppHandle, Emitter, State};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use crate::commands::auto_match::{AutoMatchOptions, run_auto_match};
+use crate::commands::config::{AppConfig, read_app_config};
+use crate::commands::file_operations::FileKind;
+use crate::commands::logs::{LogStore, CommandLog, SOURCE_WATCH};
+use crate::commands::metadata::AniListCache;
+use crate::commands::errors::AppError;
+
+// 每处理完一个新文件发送的事件名
+pub const WATCH_EVENT: &str = "watch_processed";
+
+// 监视停止后发送汇总的事件名
+pub const WATCH_STOPPED_EVENT: &str = "watch_stopped";
+
+// 文件大小保持不变的时长，超过后才认为下载完成
+const STABLE_DURATION: Duration = Duration::from_secs(2);
+
+// 检查待处理文件大小的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+// 监视中的目录，丢弃后停止监视，处理任务随停止信号或事件通道关闭而结束
+pub struct WatchSession {
+    directory: String,
+    _watcher: RecommendedWatcher,
+    // 停止信号，值为是否处理尚在等待写入完成的文件
+    stop: oneshot::Sender<bool>,
+    task: JoinHandle<WatchSummary>,
+}
+
+// 监视ID -> 监视会话，可同时监视多个目录
+pub type WatchState = Arc<Mutex<HashMap<String, WatchSession>>>;
+
+pub fn create_watch_state() -> WatchState {
+    Arc::new(Mutex::new(HashMap::new()))
+}
+
+// 一次监视的处理汇总，停止时随 watch_stopped 事件发送
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchSummary {
+    pub id: String,
+    pub directory: String,
+    pub processed: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    // 停止时尚在等待写入完成、未处理就丢弃的文件数
+    pub discarded: usize,
+}
+
+// 单个新文件的处理结果，随 watch_processed 事件发送
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchProcessedFile {
+    pub source: String,
+    pub target: Option<String>,
+    // 是否按元数据自动命名，否则链接到未匹配文件夹
+    pub auto_applied: bool,
+    // processed / skipped / failed
+    pub status: String,
+    pub error: Option<String>,
+}
+
+// 等待写入完成的文件：(上次看到的大小, 大小最近一次变化的时间)
+type PendingFiles = HashMap<PathBuf, (u64, Instant)>;
+
+// 开始监视下载目录，新出现的视频文件写入完成后自动解析、匹配并链接到输出目录，返回监视ID
+// 目录和输出目录为空时使用配置中的 watch_directory 和 output_directory；同一目录不能重复监视
+#[command]
+pub async fn start_watch(
+    directory: Option<String>,
+    output_dir: Option<String>,
+    app: AppHandle,
+    watch_state: State<'_, WatchState>,
+    anilist_cache: State<'_, AniListCache>,
+    log_store: State<'_, LogStore>
+) -> Result<String, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_WATCH);
+    let config = read_app_config();
+    let directory = directory.unwrap_or_else(|| config.watch_directory.clone());
+    let output_dir = output_dir.unwrap_or_else(|| config.output_directory.clone());
+    
+    if directory.trim().is_empty() {
+        return Err("没有指定监视目录".into());
+    }
+    if !Path::new(&directory).is_dir() {
+        return Err(AppError::NotFound(format!("目录不存在: {}", directory)));
+    }
+    if !config.anilist_enabled {
+        return Err("监视模式需要启用 AniList 数据源".into());
+    }
+    if watch_state.lock().unwrap().values().any(|session| session.directory == directory) {
+        return Err(format!("目录已在监视中: {}", directory).into());
+    }
+    
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+        // 新建、写入和移入都可能是新下载的文件，是否完成由大小是否稳定判断
+        Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+            for path in event.paths {
+                let _ = sender.send(path);
+            }
+        }
+        Ok(_) => {}
+        Err(e) => warn!("监视事件错误: {}", e),
+    }).map_err(|e| format!("创建监视失败: {}", e))?;
+    watcher.watch(Path::new(&directory), RecursiveMode::Recursive)
+        .map_err(|e| format!("监视目录失败: {}", e))?;
+    
+    let id = uuid::Uuid::new_v4().to_string();
+    let (stop, stop_receiver) = oneshot::channel();
+    let task = tokio::spawn(process_watch_events(
+        receiver,
+        stop_receiver,
+        WatchSummary { id: id.clone(), directory: directory.clone(), ..WatchSummary::default() },
+        output_dir.clone(),
+        app,
+        anilist_cache.inner().clone(),
+        log_store.inner().clone(),
+    ));
+    watch_state.lock().unwrap().insert(id.clone(), WatchSession {
+        directory: directory.clone(),
+        _watcher: watcher,
+        stop,
+        task,
+    });
+    
+    info!("开始监视目录: {} -> {} ({})", directory, output_dir, id);
+    log.info(format!("开始监视目录: {} -> {}", directory, output_dir));
+    Ok(id)
+}
+
+// 停止监视并等待处理任务结束，返回本次监视的汇总，同时发送 watch_stopped 事件
+// flush_pending 为 true 时立即处理尚在等待写入完成的文件，否则丢弃；正在处理的一批文件总会完成
+#[command]
+pub async fn stop_watching(
+    id: String,
+    flush_pending: bool,
+    app: AppHandle,
+    watch_state: State<'_, WatchState>,
+    log_store: State<'_, LogStore>
+) -> Result<WatchSummary, AppError> {
+    let log = CommandLog::new(&log_store, SOURCE_WATCH);
+    let session = watch_state.lock().unwrap().remove(&id)
+        .ok_or_else(|| AppError::NotFound(format!("监视不存在: {}", id)))?;
+    
+    // 通知处理任务收尾并等待结束，之后再丢弃监视，避免事件通道先关闭导致等待中的文件被丢弃
+    let WatchSession { _watcher: watcher, stop, task, .. } = session;
+    let _ = stop.send(flush_pending);
+    let summary = task.await
+        .map_err(|e| format!("监视处理任务异常: {}", e))?;
+    drop(watcher);
+    
+    info!("停止监视目录: {}, 处理 {}, 跳过 {}, 失败 {}, 丢弃 {}", summary.directory, summary.processed, summary.skipped, summary.failed, summary.discarded);
+    log.info(format!("停止监视目录: {}, 处理 {}, 跳过 {}, 失败 {}, 丢弃 {}", summary.directory, summary.processed, summary.skipped, summary.failed, summary.discarded));
+    if let Err(e) = app.emit(WATCH_STOPPED_EVENT, &summary) {
+        warn!("发送监视事件失败: {}", e);
+    }
+    Ok(summary)
+}
+
+// 收集监视事件，文件大小稳定后批量交给自动匹配流程处理，收到停止信号后按要求处理或丢弃等待中的文件并返回汇总
+async fn process_watch_events(
+    mut receiver: UnboundedReceiver<PathBuf>,
+    mut stop: oneshot::Receiver<bool>,
+    mut summary: WatchSummary,
+    output_dir: String,
+    app: AppHandle,
+    anilist_cache: AniListCache,
+    log_store: LogStore,
+) -> WatchSummary {
+    let context = WatchContext {
+        output_dir,
+        app,
+        anilist_cache,
+        log: CommandLog::new(&log_store, SOURCE_WATCH),
+    };
+    let mut config = read_app_config();
+    let mut pending = PendingFiles::new();
+    // 本次监视中已处理的文件，同一文件的后续事件不再处理
+    let mut handled: HashSet<PathBuf> = HashSet::new();
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    
+    let flush_pending = loop {
+        tokio::select! {
+            // 停止信号的发送端被丢弃时按丢弃处理
+            flush = &mut stop => break flush.unwrap_or(false),
+            path = receiver.recv() => match path {
+                // 每个新事件都重新开始计时，写入中的文件会不断推迟
+                Some(path) => {
+                    if !handled.contains(&path) && is_watched_video(&path, &config) {
+                        pending.insert(path, (0, Instant::now()));
+                    }
+                }
+                None => break false,
+            },
+            _ = interval.tick() => {
+                let ready = take_stable_files(&mut pending);
+                // 每批读取最新配置，监视期间修改的设置立即生效
+                config = read_app_config();
+                process_batch(ready, &mut handled, &mut summary, &context, &config).await;
+            }
+        }
+    };
+    
+    if flush_pending {
+        // 不再等待大小稳定，处理仍然存在的非空文件
+        let ready: Vec<(PathBuf, u64)> = pending.drain()
+            .filter_map(|(path, _)| match fs::metadata(&path) {
+                Ok(metadata) if metadata.is_file() && metadata.len() > 0 => Some((path, metadata.len())),
+                _ => None,
+            })
+            .collect();
+        process_batch(ready, &mut handled, &mut summary, &context, &config).await;
+    } else {
+        summary.discarded = pending.len();
+    }
+    
+    info!("监视处理任务已结束: {}", summary.directory);
+    summary
+}
+
+// 处理任务共用的输出目录和句柄
+struct WatchContext<'a> {
+    output_dir: String,
+    app: AppHandle,
+    anilist_cache: AniListCache,
+    log: CommandLog<'a>,
+}
+
+// 跳过已有其他硬链接的文件，其余交给自动匹配流程
+async fn process_batch(
+    ready: Vec<(PathBuf, u64)>,
+    handled: &mut HashSet<PathBuf>,
+    summary: &mut WatchSummary,
+    context: &WatchContext<'_>,
+    config: &AppConfig
+) {
+    let mut files = Vec::new();
+    let mut sizes = HashMap::new();
+    for (path, size) in ready {
+        handled.insert(path.clone());
+        if already_linked(&path) {
+            info!("文件已有其他硬链接，跳过: {}", path.display());
+            emit_processed(&context.app, summary, WatchProcessedFile {
+                source: path.to_string_lossy().to_string(),
+                target: None,
+                auto_applied: false,
+                status: "skipped".to_string(),
+                error: Some("文件已被链接".to_string()),
+            });
+            continue;
+        }
+        let file_path = path.to_string_lossy().to_string();
+        sizes.insert(file_path.clone(), size);
+        files.push(file_path);
+    }
+    if !files.is_empty() {
+        process_ready_files(&files, &sizes, context, config, summary).await;
+    }
+}
+
+// 监视的扩展名与扫描一致，只处理视频文件，字幕等附属文件随视频一起链接
+fn is_watched_video(path: &Path, config: &AppConfig) -> bool {
+    let extension = path.extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    FileKind::from_extension(extension, config) == FileKind::Video
+}
+
+// 取出大小在 STABLE_DURATION 内没有变化的文件，返回 (路径, 大小)；已被删除或移走的文件直接丢弃
+fn take_stable_files(pending: &mut PendingFiles) -> Vec<(PathBuf, u64)> {
+    let now = Instant::now();
+    let mut ready = Vec::new();
+    pending.retain(|path, (size, changed_at)| {
+        let current = match fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => return false,
+        };
+        if current != *size {
+            *size = current;
+            *changed_at = now;
+            return true;
+        }
+        if current > 0 && now.duration_since(*changed_at) >= STABLE_DURATION {
+            ready.push((path.clone(), current));
+            return false;
+        }
+        true
+    });
+    ready
+}
+
+// 源文件已有其他硬链接时视为已处理过（例如上一次监视或手动整理时链接过）
+#[cfg(unix)]
+fn already_linked(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).map(|metadata| metadata.nlink() > 1).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn already_linked(_path: &Path) -> bool {
+    false
+}
+
+async fn process_ready_files(
+    files: &[String],
+    sizes: &HashMap<String, u64>,
+    context: &WatchContext<'_>,
+    config: &AppConfig,
+    summary: &mut WatchSummary
+) {
+    let WatchContext { output_dir, app, anilist_cache, log } = context;
+    let options = AutoMatchOptions {
+        scanned_sizes: Some(sizes),
+        ..AutoMatchOptions::default()
+    };
+    
+    let result = match run_auto_match(files, output_dir.as_str(), config.auto_match_threshold, options, anilist_cache, log, config).await {
+        Ok(result) => result,
+        Err(e) => {
+            warn!("监视处理失败: {}", e);
+            log.error(format!("监视处理失败: {}", e));
+            for file_path in files {
+                emit_processed(app, summary, WatchProcessedFile {
+                    source: file_path.clone(),
+                    target: None,
+                    auto_applied: false,
+                    status: "failed".to_string(),
+                    error: Some(e.clone()),
+                });
+            }
+            return;
+        }
+    };
+    
+    // 链接失败的文件也有匹配决定，但没有目标，统一按失败列表发送
+    for decision in result.decisions.into_iter().filter(|decision| decision.target.is_some()) {
+        emit_processed(app, summary, WatchProcessedFile {
+            source: decision.source,
+            target: decision.target,
+            auto_applied: decision.auto_applied,
+            status: "processed".to_string(),
+            error: None,
+        });
+    }
+    for (status, errors) in [("failed", result.result.failed_files), ("skipped", result.result.skipped_files)] {
+        for file_error in errors {
+            emit_processed(app, summary, WatchProcessedFile {
+                source: file_error.path,
+                target: None,
+                auto_applied: false,
+                status: status.to_string(),
+                error: Some(file_error.error),
+            });
+        }
+    }
+}
+
+fn emit_processed(app: &AppHandle, summary: &mut WatchSummary, file: WatchProcessedFile) {
+    match file.status.as_str() {
+        "processed" => summary.processed += 1,
+        "skipped" => summary.skipped += 1,
+        _ => summary.failed += 1,
+    }
+    if let Err(e) = app.emit(WATCH_EVENT, file) {
+        warn!("发送监视事件失败: {}", e);
+    }
+}
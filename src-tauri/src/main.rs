@@ -7,6 +7,10 @@ mod commands;
 
 use commands::*;
 use commands::logs::create_log_store;
+use commands::plans::create_plan_store;
+use commands::file_operations::create_batch_cancel_flag;
+use commands::metadata::create_anilist_cache;
+use commands::watch::create_watch_state;
 
 fn main() {
     // 初始化日志系统
@@ -21,31 +25,101 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_opener::init())
         .manage(log_store)
+        .manage(create_plan_store())
+        .manage(create_batch_cancel_flag())
+        .manage(create_anilist_cache())
+        .manage(create_watch_state())
         .invoke_handler(tauri::generate_handler![
             // 文件操作命令
             scan_directory,
+            scan_directory_grouped,
+            scan_directory_with_progress,
+            cancel_scan,
+            stop_batch_processing,
+            diff_scans,
             create_hard_link,
+            link_with_sidecars,
             batch_process_files,
             batch_process_with_rename,
+            batch_auto_match,
+            auto_organize_folder,
+            find_unidentified,
+            export_metadata,
+            batch_copy_files,
+            batch_link_transactional,
+            get_operation_history,
+            clear_operation_history,
+            undo_last_batch,
+            start_watch,
+            stop_watching,
+            get_diagnostics,
+            benchmark_volume,
+            preflight_batch,
             batch_process_with_season_folders,
             check_hardlink_capability,
+            precheck_sources,
+            check_case_collisions,
             test_path_sanitization,
+            reload_char_map,
             preview_file_processing,
+            preview_config_effect,
+            plan_batch,
+            execute_plan,
+            discard_plan,
+            apply_map_transform,
+            detect_duplicate_episodes,
+            find_duplicate_files,
             get_filesystem_info,
             handle_file_conflict,
+            link_with_conflict_strategy,
             is_directory,
             get_file_info,
+            relocate_library,
+            mirror_link_tree,
+            reorganize_file,
+            relink_by_hash,
+            generate_strm,
+            restructure_seasons,
+            find_junk_files,
+            delete_junk_files,
+            find_orphan_seasons,
+            recent_anime,
+            library_signature,
+            reveal_in_explorer,
             // 元数据处理命令
             parse_anime_filename,
+            parse_anime_path,
+            debug_parse_filename,
+            detect_episode_field,
+            test_episode_regex,
+            parse_coverage,
+            detect_multi_season,
             search_anilist,
+            search_anilist_scored,
+            search_tmdb,
+            get_anilist_relations,
+            get_anilist_episode_titles,
+            search_all,
+            match_anime_batch,
+            get_provider_order,
+            set_provider_order,
+            get_title_overrides,
+            set_title_override,
             generate_filename,
+            generate_series_nfo,
+            generate_season_assets,
             // 配置管理命令
             load_config,
             save_config,
             reset_config,
             validate_output_directory,
+            validate_config,
+            validate_season_template,
+            validate_naming_template,
             get_default_directories,
             preview_naming,
+            available_tokens_for,
+            describe_template,
             // 日志管理命令
             get_logs,
             clear_logs,
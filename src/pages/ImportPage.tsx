@@ -1,6 +1,7 @@
 import React, { useState, useCallback, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { open } from "@tauri-apps/plugin-dialog";
+import { listen } from "@tauri-apps/api/event";
+import { open, ask } from "@tauri-apps/plugin-dialog";
 
 // Tauri v2 API 可用性检查
 const isTauriAvailable = () => {
@@ -15,6 +16,7 @@ const isDialogAvailable = () => {
 };
 
 import { bytesToSize } from "../utils/formatters";
+import { errorMessage } from "../utils/errors";
 import { Button } from "../components/ui/button";
 import { Progress } from "../components/ui/progress";
 import { Loader2, X, FileVideo, FileText, FolderOpen, Upload, Search, Info, Edit, Settings } from "lucide-react";
@@ -27,16 +29,63 @@ interface FileInfo {
   file_type: string;
   is_video: boolean;
   is_subtitle: boolean;
+  kind?: 'video' | 'subtitle' | 'image' | 'nfo' | 'other';
+  modified?: number;
+  probed_container?: string | null;
   parsed?: ParsedFilename;
   metadata?: AnimeInfo;
   new_name?: string;
 }
 
+// 批量处理期间后端定期发送的吞吐量统计
+interface ThroughputStats {
+  operation: string;
+  completed: number;
+  total: number;
+  bytes_done: number;
+  total_bytes: number;
+  files_per_sec: number;
+  links_per_sec: number;
+  copy_mb_per_sec: number;
+  links: number;
+  copies: number;
+  failed: number;
+  eta_secs: number | null;
+  finished: boolean;
+}
+
 interface ProcessResult {
   success: boolean;
   message: string;
   processed_files: string[];
   failed_files: FileError[];
+  skipped_files: FileError[];
+  linked_sidecars: SidecarLink[];
+  warnings?: FileError[];
+  link_actions?: Record<string, string>;
+  cancelled_files?: string[];
+  checksums?: Record<string, string>;
+}
+
+interface BatchPreflight {
+  link_mode: string;
+  linked_files: number;
+  linked_bytes: number;
+  copied_files: number;
+  copied_bytes: number;
+  required_bytes: number;
+  available_bytes?: number;
+  unlinkable_files: FileError[];
+  existing_targets: { source: string; target: string }[];
+  missing_files: string[];
+  directories: string[];
+}
+
+interface SidecarLink {
+  source: string;
+  target: string;
+  action: string;
+  language?: string;
 }
 
 interface FileError {
@@ -46,12 +95,21 @@ interface FileError {
 
 interface ParsedFilename {
   anime_title: string;
+  title_clean?: string;
   episode_number?: number;
   season?: number;
   group?: string;
   resolution?: string;
   video_codec?: string;
   audio_codec?: string;
+  is_special?: boolean;
+  anime_type?: string;
+  title_source?: 'anitomy' | 'fallback' | 'folder' | 'none';
+  folder_fields?: string[];
+  episode_source?: 'episode' | 'episode_alt' | 'volume' | 'regex' | 'anime_type' | 'folder' | '';
+  release_version?: string;
+  file_checksum?: string;
+  confident?: boolean;
 }
 
 interface AnimeInfo {
@@ -62,6 +120,7 @@ interface AnimeInfo {
   season?: number;
   year?: number;
   format?: string;
+  episode_offset?: number;
 }
 
 interface AniListResponse {
@@ -80,6 +139,12 @@ interface AniListResponse {
   };
 }
 
+interface ScoredAniListResponse {
+  anime: AniListResponse;
+  score: number;
+  matched_field?: 'romaji' | 'english' | 'native';
+}
+
 interface AppConfig {
   output_directory: string;
   naming_template: string;
@@ -94,8 +159,23 @@ interface AppConfig {
   tmdb_enabled: boolean;
   concurrent_limit: number;
   log_level: string;
+  // 动漫文件夹标题覆盖："anilist:<id>" 或规范化的解析标题 -> 文件夹标题
+  title_overrides?: Record<string, string>;
 }
 
+// 与后端 title_override_key 一致：小写并合并空白
+const titleOverrideKey = (title: string) => title.trim().split(/\s+/).join(' ').toLowerCase();
+
+// 搜索结果匹配度低于该值时提示人工确认
+const LOW_MATCH_SCORE = 0.6;
+
+// 从所在文件夹补全的字段名
+const FOLDER_FIELD_LABELS: Record<string, string> = {
+  title: '标题',
+  season: '季度',
+  episode: '集数',
+};
+
 function ImportPage() {
   const [files, setFiles] = useState<FileInfo[]>([]);
   const [isDragging, setIsDragging] = useState(false);
@@ -103,12 +183,14 @@ function ImportPage() {
   const [isAnalyzing, setIsAnalyzing] = useState(false);
   const [outputDir, setOutputDir] = useState<string | null>(null);
   const [processingProgress, setProcessingProgress] = useState(0);
+  const [throughput, setThroughput] = useState<ThroughputStats | null>(null);
   const [fileNameTemplate, setFileNameTemplate] = useState<string>("{title_romaji} - S{season}E{episode:02}");
   const [subtitleSuffix, setSubtitleSuffix] = useState<string>(".chs");
   const [folderTemplate, setFolderTemplate] = useState<string>("{title_romaji} ({year})");
   const [seasonFolderTemplate, setSeasonFolderTemplate] = useState<string>("Season {season}");
   const [selectedAnimeId, setSelectedAnimeId] = useState<number | null>(null);
   const [animeSearchResults, setAnimeSearchResults] = useState<AniListResponse[]>([]);
+  const [searchScores, setSearchScores] = useState<Record<number, number>>({});
   const [showMetadataPanel, setShowMetadataPanel] = useState(false);
   const [currentEditingFile, setCurrentEditingFile] = useState<number | null>(null);
   const [config, setConfig] = useState<AppConfig | null>(null);
@@ -183,7 +265,7 @@ function ImportPage() {
       setCreateAnimeFolders(appConfig.create_anime_folders !== false); // 默认为true
     } catch (error) {
       console.error("加载配置失败:", error);
-      toast.error(`加载配置失败: ${error}`);
+      toast.error(`加载配置失败: ${errorMessage(error)}`);
       // 使用默认配置
       setFileNameTemplate("{title_romaji} - S{season}E{episode:02}");
       setSubtitleSuffix(".chs");
@@ -230,7 +312,7 @@ function ImportPage() {
       await handleFiles(files);
     } catch (error) {
       console.error("拖拽处理失败:", error);
-      toast.error(`拖拽处理失败: ${error}`);
+      toast.error(`拖拽处理失败: ${errorMessage(error)}`);
     }
   }, []);
 
@@ -275,7 +357,7 @@ function ImportPage() {
     } catch (error) {
       console.error('选择文件错误:', error);
       if (error !== "User cancelled the dialog") {
-        toast.error(`选择文件失败: ${error}`);
+        toast.error(`选择文件失败: ${errorMessage(error)}`);
       }
     }
   }, []);
@@ -331,7 +413,7 @@ function ImportPage() {
       toast.success(`成功扫描目录: ${path}`);
     } catch (error) {
       console.error('扫描目录错误:', error);
-      toast.error(`扫描目录失败: ${error}`);
+      toast.error(`扫描目录失败: ${errorMessage(error)}`);
     }
   };
 
@@ -362,7 +444,7 @@ function ImportPage() {
     } catch (error) {
       console.error('选择目录错误:', error);
       if (error !== "User cancelled the dialog") {
-        toast.error(`选择输出目录失败: ${error}`);
+        toast.error(`选择输出目录失败: ${errorMessage(error)}`);
       }
     }
   };
@@ -387,11 +469,11 @@ function ImportPage() {
       let parsedCount = 0;
       let firstAnimeTitle = '';
       
-      // 只解析视频文件名
+      // 只解析视频文件，按完整路径解析，文件名缺少的标题、季度由所在文件夹补全
       for (let i = 0; i < videoFiles.length; i++) {
         try {
-          const parsed = await invoke<ParsedFilename>('parse_anime_filename', {
-            filename: videoFiles[i].name
+          const parsed = await invoke<ParsedFilename>('parse_anime_path', {
+            path: videoFiles[i].path
           });
           
           // 更新文件信息
@@ -422,7 +504,7 @@ function ImportPage() {
       }
     } catch (error) {
       console.error('解析文件名错误:', error);
-      toast.error(`文件名解析失败: ${error}`);
+      toast.error(`文件名解析失败: ${errorMessage(error)}`);
     } finally {
       setIsAnalyzing(false);
     }
@@ -438,11 +520,14 @@ function ImportPage() {
     setIsSearching(true);
     
     try {
-      const results = await invoke<AniListResponse[]>('search_anilist', {
+      // 结果按匹配度排序，第一个即为最佳匹配
+      const scored = await invoke<ScoredAniListResponse[]>('search_anilist_scored', {
         query: title
       });
+      const results = scored.map(result => result.anime);
       
       setAnimeSearchResults(results);
+      setSearchScores(Object.fromEntries(scored.map(result => [result.anime.id, result.score])));
       
       if (results.length > 0) {
         setSelectedAnimeId(results[0].id);
@@ -450,9 +535,13 @@ function ImportPage() {
       }
       
       setShowMetadataPanel(true);
-      toast.success(`找到 ${results.length} 个搜索结果`);
+      if (scored.length > 0 && scored[0].score < LOW_MATCH_SCORE) {
+        toast.warning(`找到 ${results.length} 个搜索结果，最佳结果匹配度较低 (${Math.round(scored[0].score * 100)}%)，请确认`);
+      } else {
+        toast.success(`找到 ${results.length} 个搜索结果`);
+      }
     } catch (error) {
-      toast.error(`搜索元数据失败: ${error}`);
+      toast.error(`搜索元数据失败: ${errorMessage(error)}`);
     } finally {
       setIsSearching(false);
     }
@@ -742,6 +831,7 @@ function ImportPage() {
       return;
     }
     
+    let unlistenThroughput: (() => void) | undefined;
     try {
       // 检查硬链接能力
       const canHardlink = await invoke<boolean>('check_hardlink_capability', {
@@ -756,14 +846,16 @@ function ImportPage() {
       
       setIsProcessing(true);
       setProcessingProgress(0);
+      setThroughput(null);
       
-      // 模拟进度
-      const progressInterval = setInterval(() => {
-        setProcessingProgress(prev => {
-          const newProgress = prev + (100 - prev) * 0.1;
-          return newProgress > 95 ? 95 : newProgress;
-        });
-      }, 300);
+      // 按后端的吞吐量事件更新进度
+      unlistenThroughput = await listen<ThroughputStats>('throughput', (event) => {
+        const stats = event.payload;
+        setThroughput(stats);
+        if (stats.total > 0) {
+          setProcessingProgress(stats.completed / stats.total * 100);
+        }
+      });
       
       // 准备重命名映射
       const renameMap: Record<string, string> = {};
@@ -782,10 +874,14 @@ function ImportPage() {
               
               // 如果启用创建动漫文件夹
               if (createAnimeFolders) {
+                // 配置了标题覆盖时，文件夹模板中的标题字段统一使用覆盖的标题
+                const overrides = config?.title_overrides ?? {};
+                const overrideTitle = (selectedAnimeId !== null ? overrides[`anilist:${selectedAnimeId}`] : undefined)
+                  ?? overrides[titleOverrideKey(fileWithMetadata.parsed?.anime_title ?? animeInfo.title)];
                 let animeFolder = folderTemplate;
-                animeFolder = animeFolder.replace("{title}", animeInfo.title);
-                animeFolder = animeFolder.replace("{title_romaji}", animeInfo.title_romaji || animeInfo.title);
-                animeFolder = animeFolder.replace("{title_english}", animeInfo.title_english || animeInfo.title);
+                animeFolder = animeFolder.replace("{title}", overrideTitle || animeInfo.title);
+                animeFolder = animeFolder.replace("{title_romaji}", overrideTitle || animeInfo.title_romaji || animeInfo.title);
+                animeFolder = animeFolder.replace("{title_english}", overrideTitle || animeInfo.title_english || animeInfo.title);
                 
                 if (animeInfo.year) {
                   animeFolder = animeFolder.replace("{year}", animeInfo.year.toString());
@@ -834,6 +930,38 @@ function ImportPage() {
         });
       }
       
+      // 预检：会被复制而非链接、无法处理或目标已存在的文件需要用户确认
+      const preflight = await invoke<BatchPreflight>('preflight_batch', {
+        files: files.map(f => f.path),
+        outputDir,
+        renameMap
+      });
+      const notices: string[] = [];
+      if (preflight.copied_files > 0) {
+        notices.push(`${preflight.copied_files} 个文件将被复制，占用 ${bytesToSize(preflight.required_bytes)}`);
+      }
+      if (preflight.available_bytes != null && preflight.required_bytes > preflight.available_bytes) {
+        notices.push(`输出目录可用空间不足 (剩余 ${bytesToSize(preflight.available_bytes)})`);
+      }
+      if (preflight.unlinkable_files.length > 0) {
+        notices.push(`${preflight.unlinkable_files.length} 个文件将处理失败: ${preflight.unlinkable_files[0].error}`);
+      }
+      if (preflight.directories.length > 0) {
+        notices.push(`${preflight.directories.length} 个路径是文件夹，将不会被处理`);
+      }
+      if (preflight.existing_targets.length > 0) {
+        notices.push(`${preflight.existing_targets.length} 个目标文件已存在`);
+      }
+      if (notices.length > 0) {
+        const confirmed = await ask(`${notices.join("\n")}\n\n是否继续处理？`, {
+          title: "处理前确认",
+          kind: "warning"
+        });
+        if (!confirmed) {
+          return;
+        }
+      }
+      
       // 批量处理文件 - 使用新的季度文件夹处理函数
       const result = await invoke<ProcessResult>('batch_process_with_season_folders', {
         files: files.map(f => f.path),
@@ -843,11 +971,18 @@ function ImportPage() {
         seasonFolderTemplate: seasonFolderTemplate
       });
       
-      clearInterval(progressInterval);
       setProcessingProgress(100);
       
       if (result.success) {
-        toast.success(result.message);
+        toast.success(result.message, {
+          action: {
+            label: "打开输出目录",
+            onClick: () => {
+              invoke('reveal_in_explorer', { path: outputDir })
+                .catch(error => toast.error(errorMessage(error)));
+            }
+          }
+        });
       } else {
         toast.warning(result.message);
         if (result.failed_files.length > 0) {
@@ -855,11 +990,15 @@ function ImportPage() {
         }
       }
     } catch (error) {
-      toast.error(`处理文件失败: ${error}`);
+      toast.error(`处理文件失败: ${errorMessage(error)}`);
     } finally {
+      unlistenThroughput?.();
       setIsProcessing(false);
       // 延迟重置进度条，以便用户看到完成状态
-      setTimeout(() => setProcessingProgress(0), 2000);
+      setTimeout(() => {
+        setProcessingProgress(0);
+        setThroughput(null);
+      }, 2000);
     }
   };
   
@@ -887,7 +1026,7 @@ function ImportPage() {
         setConfig(updatedConfig);
       }
     } catch (error) {
-      toast.error(`保存设置失败: ${error}`);
+      toast.error(`保存设置失败: ${errorMessage(error)}`);
     }
   };
   
@@ -1088,6 +1227,14 @@ function ImportPage() {
               <Progress value={processingProgress} className="w-full" />
               <p className="text-sm text-muted-foreground mt-1">
                 处理进度: {Math.round(processingProgress)}%
+                {throughput && (
+                  <>
+                    {` · ${throughput.completed}/${throughput.total} 个文件`}
+                    {` · ${throughput.files_per_sec.toFixed(1)} 个/秒`}
+                    {throughput.copies > 0 && ` · 复制 ${throughput.copy_mb_per_sec.toFixed(1)} MB/s`}
+                    {throughput.eta_secs !== null && !throughput.finished && ` · 剩余约 ${throughput.eta_secs} 秒`}
+                  </>
+                )}
               </p>
             </div>
           )}
@@ -1267,6 +1414,11 @@ function ImportPage() {
                       {anime.season_year && <span>{anime.season_year}</span>}
                       {anime.format && <span>{anime.format}</span>}
                       {anime.episodes && <span>{anime.episodes}话</span>}
+                      {searchScores[anime.id] !== undefined && (
+                        <span className={searchScores[anime.id] < LOW_MATCH_SCORE ? 'text-yellow-600' : ''}>
+                          匹配度 {Math.round(searchScores[anime.id] * 100)}%
+                        </span>
+                      )}
                     </div>
                   </div>
                 </div>
@@ -1321,6 +1473,15 @@ function ImportPage() {
                       {file.parsed.group && (
                         <span> [{file.parsed.group}]</span>
                       )}
+                      {file.parsed.release_version && (
+                        <span> v{file.parsed.release_version}</span>
+                      )}
+                      {file.parsed.folder_fields && file.parsed.folder_fields.length > 0 && (
+                        <span> (来自文件夹: {file.parsed.folder_fields.map(field => FOLDER_FIELD_LABELS[field] ?? field).join('、')})</span>
+                      )}
+                      {file.parsed.confident === false && (
+                        <span className="text-yellow-600"> (解析存疑，请人工确认)</span>
+                      )}
                     </div>
                   )}
                   
@@ -1,6 +1,7 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
+import { errorMessage } from "../utils/errors";
 import { Button } from "../components/ui/button";
 import { toast } from "sonner";
 
@@ -18,6 +19,18 @@ interface AppConfig {
   tmdb_enabled: boolean;
   concurrent_limit: number;
   log_level: string;
+  write_manifest?: boolean;
+  manifest_append?: boolean;
+}
+
+interface VolumeBenchmark {
+  path: string;
+  hardlink_supported: boolean;
+  hardlink_ms?: number;
+  copy_bytes: number;
+  copy_ms: number;
+  copy_mb_per_second: number;
+  hardlink_error?: string;
 }
 
 export default function SettingsPage() {
@@ -34,6 +47,10 @@ export default function SettingsPage() {
   const [logLevel, setLogLevel] = useState("info");
   const [isSaving, setIsSaving] = useState(false);
   const [isLoading, setIsLoading] = useState(true);
+  const [benchmark, setBenchmark] = useState<VolumeBenchmark | null>(null);
+  const [isBenchmarking, setIsBenchmarking] = useState(false);
+  // 保留完整的已加载配置，保存时不丢失页面上未展示的字段
+  const [loadedConfig, setLoadedConfig] = useState<AppConfig | null>(null);
   
   // 加载配置
   useEffect(() => {
@@ -44,6 +61,7 @@ export default function SettingsPage() {
     try {
       setIsLoading(true);
       const config = await invoke<AppConfig>("load_config");
+      setLoadedConfig(config);
       
       setOutputPath(config.output_directory || "");
       setNamingTemplate(config.naming_template || "{title_romaji} - {episode:02} [{group}].{ext}");
@@ -54,14 +72,14 @@ export default function SettingsPage() {
       setCreateAnimeFolders(config.create_anime_folders !== false); // 默认为true
       setUseRomajiNames(config.use_romaji_names);
       setCreateSeasonFolders(config.create_season_folders);
-      setConcurrentLimit(config.concurrent_limit || 4);
+      setConcurrentLimit(config.concurrent_limit ?? 4);
       // AniList和TMDB选项已移除，使用默认值
       setLogLevel(config.log_level || "info");
       
       setIsLoading(false);
     } catch (error) {
       console.error("加载配置失败:", error);
-      toast.error("加载配置失败: " + String(error));
+      toast.error("加载配置失败: " + errorMessage(error));
       setIsLoading(false);
     }
   };
@@ -72,6 +90,7 @@ export default function SettingsPage() {
       setIsSaving(true);
       
       const config: AppConfig = {
+        ...loadedConfig,
         output_directory: outputPath,
         naming_template: namingTemplate,
         subtitle_template: subtitleTemplate,
@@ -92,7 +111,7 @@ export default function SettingsPage() {
       setIsSaving(false);
     } catch (error) {
       console.error("保存配置失败:", error);
-      toast.error("保存配置失败: " + String(error));
+      toast.error("保存配置失败: " + errorMessage(error));
       setIsSaving(false);
     }
   };
@@ -135,7 +154,7 @@ export default function SettingsPage() {
         toast.success("已重置为默认设置");
       } catch (error) {
         console.error("重置配置失败:", error);
-        toast.error("重置配置失败: " + String(error));
+        toast.error("重置配置失败: " + errorMessage(error));
       }
     }
   };
@@ -154,7 +173,26 @@ export default function SettingsPage() {
       toast.info(`模板预览: ${result}`);
     } catch (error) {
       console.error("模板测试失败:", error);
-      toast.error("模板测试失败: " + String(error));
+      toast.error("模板测试失败: " + errorMessage(error));
+    }
+  };
+  
+  // 在输出目录所在的卷上测量硬链接和复制的速度
+  const runBenchmark = async () => {
+    if (!outputPath) {
+      toast.error("请先选择输出目录");
+      return;
+    }
+    
+    setIsBenchmarking(true);
+    try {
+      const result = await invoke<VolumeBenchmark>("benchmark_volume", { path: outputPath });
+      setBenchmark(result);
+    } catch (error) {
+      console.error("测速失败:", error);
+      toast.error("测速失败: " + errorMessage(error));
+    } finally {
+      setIsBenchmarking(false);
     }
   };
   
@@ -309,17 +347,43 @@ export default function SettingsPage() {
           <h2 className="text-xl font-semibold">性能设置</h2>
           <div className="space-y-1">
             <p className="text-sm text-muted-foreground">
-              并发处理任务数 (1-10)
+              并发处理任务数 (0-10，0 表示使用所有核心)
             </p>
             <input
               type="number"
-              min="1"
+              min="0"
               max="10"
               value={concurrentLimit}
               onChange={(e) => setConcurrentLimit(Number(e.target.value))}
               className="w-full p-2 border rounded bg-background"
             />
           </div>
+          
+          <div className="space-y-1">
+            <p className="text-sm text-muted-foreground">
+              在输出目录所在的磁盘上实测硬链接与复制的速度
+            </p>
+            <Button
+              variant="outline"
+              onClick={runBenchmark}
+              disabled={isBenchmarking}
+            >
+              {isBenchmarking ? "测速中..." : "测试输出目录速度"}
+            </Button>
+            {benchmark && (
+              <div className="text-sm space-y-1 pt-2">
+                {benchmark.hardlink_supported ? (
+                  <p>硬链接: 每个文件约 {benchmark.hardlink_ms?.toFixed(2)} ms，与文件大小无关</p>
+                ) : (
+                  <p className="text-destructive">不支持硬链接: {benchmark.hardlink_error}</p>
+                )}
+                <p>
+                  复制: {benchmark.copy_mb_per_second.toFixed(1)} MB/s
+                  （{(benchmark.copy_bytes / 1024 / 1024).toFixed(0)} MB 用时 {benchmark.copy_ms.toFixed(0)} ms）
+                </p>
+              </div>
+            )}
+          </div>
         </div>
         
         {/* 日志设置 */}
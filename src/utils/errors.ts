@@ -0,0 +1,35 @@
+/**
+ * 后端命令返回的错误，code 为稳定的错误类型，message 为中文说明
+ */
+export interface AppError {
+  code:
+    | "io"
+    | "different_filesystems"
+    | "target_exists"
+    | "permission_denied"
+    | "source_not_found"
+    | "source_immutable"
+    | "copy_forbidden"
+    | "not_found"
+    | "network"
+    | "parse"
+    | "other";
+  message: string;
+}
+
+/**
+ * 判断捕获到的错误是否为后端返回的结构化错误
+ */
+export function isAppError(error: unknown): error is AppError {
+  return typeof error === "object" && error !== null && "code" in error && "message" in error;
+}
+
+/**
+ * 取出错误的说明文字，兼容结构化错误、带 message 的对象和字符串
+ */
+export function errorMessage(error: unknown): string {
+  if (typeof error === "object" && error !== null && "message" in error) {
+    return String((error as { message: unknown }).message);
+  }
+  return String(error);
+}